@@ -1,4 +1,4 @@
-from typing import Dict, List, Optional, Callable, Union
+from typing import Any, Dict, List, Optional, Callable, Union
 from rpaudio import effects, rpaudio_exceptions
 
 __all__ = [
@@ -7,10 +7,83 @@ __all__ = [
     "AudioChannel",
     "rpaudio_exceptions",
     "ActionType",
+    "ReplayGainMode",
+    "OutputConfig",
+    "ResamplerQuality",
+    "SyncGroup",
+    "ScheduledEvent",
+    "EffectHandle",
+    "EffectState",
     "effects"
 ]
 
 
+class ReplayGainMode:
+    """
+    Selects which ReplayGain tags are used for playback gain.
+
+    Attributes:
+        Track: Use REPLAYGAIN_TRACK_GAIN / REPLAYGAIN_TRACK_PEAK.
+        Album: Use REPLAYGAIN_ALBUM_GAIN / REPLAYGAIN_ALBUM_PEAK, falling back to the track tags.
+    """
+    Track: 'ReplayGainMode'
+    Album: 'ReplayGainMode'
+
+
+class ResamplerQuality:
+    """
+    Interpolation used when a file's sample rate differs from the output sample rate.
+
+    Attributes:
+        Linear: Linear interpolation. Cheapest, and the default.
+        Cubic: Catmull-Rom cubic interpolation.
+        Sinc: Windowed-sinc interpolation with anti-aliasing. Highest quality, most CPU.
+    """
+    Linear: 'ResamplerQuality'
+    Cubic: 'ResamplerQuality'
+    Sinc: 'ResamplerQuality'
+
+
+class OutputConfig:
+    """
+    Requested format of the output device stream. Fields left as None use the device default.
+
+    Sinks with the same configuration share one device stream. The format the device actually
+    opened with is reported by `AudioSink.output_format()` and `ChannelManager.output_format()`.
+
+    Example:
+
+    .. code-block:: python
+
+        config = OutputConfig(sample_rate=48000, channels=2, latency=0.02, resampler=ResamplerQuality.Sinc)
+        sink = AudioSink(output_config=config)
+        sink.load_audio("ex.wav")
+        print(sink.output_format())
+
+    :param sample_rate: Output sample rate in Hz.
+    :param channels: Number of output channels.
+    :param buffer_size: Device buffer size in frames. Clamped to the range the device supports.
+    :param latency: Device buffer length in seconds. Ignored when `buffer_size` is given.
+    :param resampler: Resampling quality used when a file's rate differs from the output rate.
+    :raises ValueError: If a value is zero or negative, or the device does not support the
+        requested sample rate and channel count.
+    """
+    sample_rate: Optional[int]
+    channels: Optional[int]
+    buffer_size: Optional[int]
+    latency: Optional[float]
+    resampler: ResamplerQuality
+
+    def __init__(
+        self,
+        sample_rate: Optional[int] = None,
+        channels: Optional[int] = None,
+        buffer_size: Optional[int] = None,
+        latency: Optional[float] = None,
+        resampler: ResamplerQuality = ResamplerQuality.Linear,
+    ) -> None: ...
+
+
 class MetaData:
     """
     Represents metadata for an audio file.
@@ -32,6 +105,13 @@ class MetaData:
         sample_rate (int): The sample rate of the audio file.
         channels (int): The number of channels in the audio file.
         duration (float): The duration of the audio file in seconds.
+        integrated_loudness (float): Integrated loudness in LUFS, once measured with `AudioSink.analyze_loudness`.
+        loudness_range (float): Loudness range in LU, once measured.
+        true_peak (float): True peak level in dBTP, once measured.
+        replaygain_track_gain (float): REPLAYGAIN_TRACK_GAIN tag in dB, if present.
+        replaygain_track_peak (float): REPLAYGAIN_TRACK_PEAK tag as a linear peak, if present.
+        replaygain_album_gain (float): REPLAYGAIN_ALBUM_GAIN tag in dB, if present.
+        replaygain_album_peak (float): REPLAYGAIN_ALBUM_PEAK tag as a linear peak, if present.
     """
 
     @property
@@ -98,6 +178,34 @@ class MetaData:
     def duration(self) -> float:
         ...
 
+    @property
+    def integrated_loudness(self) -> Optional[float]:
+        ...
+
+    @property
+    def loudness_range(self) -> Optional[float]:
+        ...
+
+    @property
+    def true_peak(self) -> Optional[float]:
+        ...
+
+    @property
+    def replaygain_track_gain(self) -> Optional[float]:
+        ...
+
+    @property
+    def replaygain_track_peak(self) -> Optional[float]:
+        ...
+
+    @property
+    def replaygain_album_gain(self) -> Optional[float]:
+        ...
+
+    @property
+    def replaygain_album_peak(self) -> Optional[float]:
+        ...
+
 
 
 class AudioSink:
@@ -108,6 +216,9 @@ class AudioSink:
     and manipulate playback speed and volume. An optional callback function can be invoked when
     the audio stops playing.
 
+    Playback runs on a shared audio thread that owns the output device. An AudioSink is a handle
+    to its voice on that thread, so control methods can be called safely from multiple Python threads.
+
     Example:
 
     .. code-block:: python
@@ -125,7 +236,7 @@ class AudioSink:
         is_playing (bool): Flag indicating whether the audio is currently playing.
     """
 
-    def __init__(self, callback: Optional[Callable[[], None]] = None) -> 'AudioSink':
+    def __init__(self, callback: Optional[Callable[[], None]] = None, output_config: Optional[OutputConfig] = None) -> 'AudioSink':
         """
         Constructor method.
 
@@ -133,6 +244,7 @@ class AudioSink:
 
         Args:
             callback (Optional[Callable[[], None]]): A function that will be called when the audio stops playing.
+            output_config (Optional[OutputConfig]): Output stream format. Defaults to the device default.
 
         Returns:
             None: This method does not return any value.
@@ -185,8 +297,39 @@ class AudioSink:
         """
         Load an audio file for playback.
 
+        A sink can be reused: once the previous audio has finished or been stopped, loading a
+        new file replaces it. The callback, volume, effects chain and gain settings are kept.
+
         :param filename: The path to the audio file to load.
         :type filename: str
+        :raises RuntimeError: If audio is still loaded and has not finished or been stopped.
+        """
+
+    def unload(self) -> None:
+        """
+        Stop playback and release the loaded file without invoking the callback.
+
+        The callback, volume, effects chain and gain settings are kept, so another file can be
+        loaded into the same sink afterwards.
+
+        Example:
+
+        .. code-block:: python
+
+            sink = AudioSink(callback=on_audio_stop).load_audio("intro.wav")
+            sink.play()
+            sink.unload()
+            sink.load_audio("loop.wav")
+            sink.play()
+        """
+
+    def replay(self) -> None:
+        """
+        Reload the current file and start playing it again from the beginning.
+
+        Useful to restart a sound after it has finished. The effects chain is applied again.
+
+        :raises RuntimeError: If no audio has been loaded.
         """
 
     def play(self) -> None:
@@ -242,6 +385,41 @@ class AudioSink:
             handler.stop()
         """
 
+    def play_at(self, timestamp: float) -> ScheduledEvent:
+        """
+        Start playing at an absolute time on the monotonic clock. The start is scheduled on
+        the audio thread, so it is not affected by Python scheduling jitter.
+
+        :param timestamp: The start time, in seconds on the `time.monotonic()` clock. Times in
+            the past start immediately.
+        :type timestamp: float
+        :return: The scheduled event, which can be cancelled until it fires.
+        :rtype: ScheduledEvent
+        :raises RuntimeError: If no audio has been loaded.
+
+        Example:
+
+        .. code-block:: python
+
+            import time
+
+            handler = AudioSink().load_audio("my_audio_file.mp3")
+            start = handler.play_at(time.monotonic() + 5.0)
+            stop = handler.stop_at(time.monotonic() + 35.0)
+            stop.cancel()
+        """
+
+    def stop_at(self, timestamp: float) -> ScheduledEvent:
+        """
+        Stop playback at an absolute time on the monotonic clock. See `play_at`.
+
+        :param timestamp: The stop time, in seconds on the `time.monotonic()` clock.
+        :type timestamp: float
+        :return: The scheduled event, which can be cancelled until it fires.
+        :rtype: ScheduledEvent
+        :raises RuntimeError: If no audio has been loaded.
+        """
+
     @property
     def metadata(self) -> MetaData:
         """
@@ -292,8 +470,8 @@ class AudioSink:
         :type speed: float
 
         :raises ValueError: If the speed is not a valid float.
-        :raises EffectConflictException: Raised when an attempt is made to change the volume while
-        effects are actively being applied. This ensures that audio effects do not conflict during playback.
+        :raises EffectConflictException: Raised when an attempt is made to change the speed while
+        effects are actively being applied and `conflict_policy` is `ConflictPolicy.Reject`.
         """
 
     def get_pos(self) -> float:
@@ -325,7 +503,7 @@ class AudioSink:
 
         :raises ValueError: If the volume is not between 0.0 and 1.0.
         :raises EffectConflictException: Raised when an attempt is made to change the volume while
-        effects are actively being applied. This ensures that audio effects do not conflict during playback.
+        effects are actively being applied and `conflict_policy` is `ConflictPolicy.Reject`.
         """
 
     def get_volume(self) -> float:
@@ -355,12 +533,23 @@ class AudioSink:
         :raises RuntimeError: If no sink is available or audio is not loaded.
         """
 
-    def apply_effects(self, effect_list: list) -> None:
+    def apply_effects(self, effect_list: list) -> List["EffectHandle"]:
         """
         Apply a list of audio effects such as fade-in, fade-out, or speed changes.
 
+        Example:
+
+        .. code-block:: python
+
+            fade, = sink.apply_effects([FadeOut(duration=4.0, apply_after=10.0)])
+            sink.play()
+            ...
+            fade.cancel()
+
         :param effect_list: A list of effects to apply. Each effect must be an instance of `FadeIn`, `FadeOut`, `ChangeSpeed`, or similar.
         :type effect_list: list
+        :return: A handle for each effect, in the order given.
+        :rtype: List[EffectHandle]
         :raises TypeError: If an unknown effect type is provided.
         :raises RuntimeError: If an error occurs while applying the effects.
         """
@@ -383,6 +572,190 @@ class AudioSink:
         :raises RuntimeError: If there is an issue acquiring the lock on the callback.
         """
 
+    def analyze_loudness(self) -> Dict[str, Optional[float]]:
+        """
+        Measure the loudness of the loaded file following EBU R128.
+
+        The whole file is decoded once and the results are cached on the sink's metadata
+        (`integrated_loudness`, `loudness_range` and `true_peak`).
+
+        Example:
+
+        .. code-block:: python
+
+            sink = AudioSink().load_audio("ex.wav")
+            stats = sink.analyze_loudness()
+            print(stats["integrated_loudness"], stats["true_peak"])
+
+        :return: A dictionary with `integrated_loudness` (LUFS), `loudness_range` (LU) and
+            `true_peak` (dBTP). Values are None for silent files.
+        :rtype: Dict[str, Optional[float]]
+        :raises RuntimeError: If no audio has been loaded or the file cannot be decoded.
+        """
+
+    @property
+    def conflict_policy(self) -> effects.ConflictPolicy:
+        """
+        How `set_volume`, `set_speed` and overlapping effects on volume or speed combine.
+        Defaults to `ConflictPolicy.Reject`. The policy is kept in snapshots.
+
+        Example:
+
+        .. code-block:: python
+
+            sink = AudioSink().load_audio("ex.wav")
+            sink.conflict_policy = ConflictPolicy.Multiply
+            sink.apply_effects([FadeIn(duration=2.0)])
+            sink.set_volume(0.5)  # fades in to 0.5
+
+        :rtype: ConflictPolicy
+        """
+
+    @property
+    def normalization(self) -> Optional[float]:
+        """
+        The target loudness in LUFS the audio is normalized to, or None when disabled.
+
+        Setting a target measures the file if needed and applies a gain so its integrated
+        loudness reaches the target. The gain is limited so the true peak stays below 0 dBTP.
+        The gain is applied before the sink volume, so fades and `set_volume` keep working.
+
+        Example:
+
+        .. code-block:: python
+
+            sink = AudioSink().load_audio("ex.wav")
+            sink.normalization = -23.0
+            sink.play()
+
+        :rtype: Optional[float]
+        """
+
+    @property
+    def replay_gain(self) -> Optional[ReplayGainMode]:
+        """
+        The active ReplayGain mode, or None when ReplayGain is disabled.
+
+        :rtype: Optional[ReplayGainMode]
+        """
+
+    def set_replay_gain(self, mode: Optional[ReplayGainMode] = None, preamp: float = 0.0, prevent_clipping: bool = True) -> None:
+        """
+        Apply gain from the file's ReplayGain tags during playback.
+
+        ReplayGain takes precedence over `normalization`. Files without gain tags fall back to
+        `normalization` if it is set, and play unchanged otherwise.
+
+        Example:
+
+        .. code-block:: python
+
+            sink = AudioSink().load_audio("track.flac")
+            sink.set_replay_gain(ReplayGainMode.Album, preamp=3.0)
+
+        :param mode: Track or Album mode. Pass None to disable ReplayGain.
+        :type mode: Optional[ReplayGainMode]
+        :param preamp: Extra gain in dB added to the tag value.
+        :type preamp: float
+        :param prevent_clipping: Lower the gain so the tagged peak does not exceed full scale.
+        :type prevent_clipping: bool
+        """
+
+    def snapshot(self) -> Dict[str, Any]:
+        """
+        Capture the playback state of the sink as a JSON-compatible dictionary.
+
+        The snapshot contains the file path, position, playing state, the volume and speed set
+        with `set_volume` and `set_speed`, the effects chain set with `apply_effects`, the effects
+        still in progress, the normalization target and the ReplayGain settings. Effects in
+        progress are saved separately, so a fade running when the snapshot is taken resumes
+        from the set volume rather than being applied twice.
+        Pass it to `AudioSink.from_snapshot` to restore the sink, for example after a restart.
+
+        Example:
+
+        .. code-block:: python
+
+            state = sink.snapshot()
+            with open("state.json", "w") as f:
+                json.dump(state, f)
+
+        :rtype: Dict[str, Any]
+        :raises RuntimeError: If no audio has been loaded.
+        :raises ValueError: If a `PythonEffect` or `Automation` is applied, since their Python
+            functions cannot be stored.
+        """
+
+    def snapshot_json(self) -> str:
+        """
+        Same as `snapshot`, serialized to a JSON string.
+
+        :rtype: str
+        :raises RuntimeError: If no audio has been loaded.
+        :raises ValueError: If a `PythonEffect` or `Automation` is applied.
+        """
+
+    @property
+    def output_config(self) -> OutputConfig:
+        """
+        The output configuration this sink plays through.
+
+        :rtype: OutputConfig
+        """
+
+    def set_output_config(self, output_config: OutputConfig) -> None:
+        """
+        Move the sink to another output configuration.
+
+        If audio is loaded, it is reloaded on the new output at the same position, keeping its
+        volume, speed, effects and play state.
+
+        :param output_config: The new output configuration.
+        :type output_config: OutputConfig
+        :raises ValueError: If the device does not support the requested format.
+        """
+
+    def output_format(self) -> Dict[str, Any]:
+        """
+        The format the output device stream was opened with.
+
+        Example:
+
+        .. code-block:: python
+
+            sink.output_format()
+            # {'buffer_size': 1024, 'channels': 2, 'device': 'default', 'latency': 0.021,
+            #  'resampler': 'Sinc', 'sample_format': 'f32', 'sample_rate': 48000}
+
+        :return: A dictionary with `device`, `sample_rate`, `channels`, `sample_format`,
+            `buffer_size` and `latency` (None when the device default is used) and `resampler`.
+        :rtype: dict
+        :raises RuntimeError: If the output device cannot be opened.
+        """
+
+    @staticmethod
+    def from_snapshot(snapshot: Union[Dict[str, Any], str], callback: Optional[Callable[[], None]] = None) -> 'AudioSink':
+        """
+        Create an AudioSink from a snapshot taken with `snapshot` or `snapshot_json`.
+
+        The file is loaded, seeked to the saved position and the volume, speed and effects are
+        restored, including the progress of effects that were running. A sink that was playing
+        when the snapshot was taken starts playing again; otherwise it is returned paused.
+
+        Example:
+
+        .. code-block:: python
+
+            with open("state.json") as f:
+                sink = AudioSink.from_snapshot(json.load(f), callback=on_audio_stop)
+
+        :param snapshot: A snapshot dictionary or JSON string.
+        :param callback: A function that will be called when the audio stops playing.
+        :raises ValueError: If the snapshot is malformed, from a newer version, contains a
+            `PythonEffect` or `Automation`, or has a volume or effect parameter out of range.
+        :raises RuntimeError: If the file cannot be loaded or seeked.
+        """
+
     def playback_data(self) -> Dict[str, Any]:
         """
         Retrieve playback metadata and effects associated with the audio sink.
@@ -420,6 +793,8 @@ class AudioChannel:
         """
         Adds an AudioSink object to the queue.
 
+        If the channel has a normalization target, it is applied to the sink when pushed.
+
         Example:
 
         .. code-block:: python
@@ -453,6 +828,77 @@ class AudioChannel:
         :type value: bool
         """
 
+    @property
+    def normalization(self) -> Optional[float]:
+        """
+        The target loudness in LUFS applied to every sink on the channel, or None when disabled.
+
+        Example:
+
+        .. code-block:: python
+
+            channel = AudioChannel()
+            channel.normalization = -16.0
+            channel.push(AudioSink().load_audio("ex.wav"))
+
+        :rtype: Optional[float]
+        """
+
+    @property
+    def replay_gain(self) -> Optional[ReplayGainMode]:
+        """
+        The ReplayGain mode applied to every sink on the channel, or None when disabled.
+
+        :rtype: Optional[ReplayGainMode]
+        """
+
+    def set_replay_gain(self, mode: Optional[ReplayGainMode] = None, preamp: float = 0.0, prevent_clipping: bool = True) -> None:
+        """
+        Apply ReplayGain to the queued sinks, the current sink and every sink pushed later.
+
+        Example:
+
+        .. code-block:: python
+
+            channel = AudioChannel()
+            channel.set_replay_gain(ReplayGainMode.Track, preamp=0.0, prevent_clipping=True)
+
+        :param mode: Track or Album mode. Pass None to disable ReplayGain.
+        :type mode: Optional[ReplayGainMode]
+        :param preamp: Extra gain in dB added to the tag value.
+        :type preamp: float
+        :param prevent_clipping: Lower the gain so the tagged peak does not exceed full scale.
+        :type prevent_clipping: bool
+        """
+
+    @property
+    def output_config(self) -> Optional[OutputConfig]:
+        """
+        The output configuration applied to sinks in this channel, or None if unset.
+
+        :rtype: Optional[OutputConfig]
+        """
+
+    def set_output_config(self, output_config: OutputConfig) -> None:
+        """
+        Apply an output configuration to the queued sinks, the current sink and every sink pushed later.
+
+        :param output_config: The output configuration.
+        :type output_config: OutputConfig
+        """
+
+    def start_at(self, timestamp: float) -> ScheduledEvent:
+        """
+        Start the next sink in the queue at an absolute time on the monotonic clock and enable
+        auto consume, so the rest of the queue follows it.
+
+        :param timestamp: The start time, in seconds on the `time.monotonic()` clock.
+        :type timestamp: float
+        :return: The scheduled event, which can be cancelled until it fires.
+        :rtype: ScheduledEvent
+        :raises RuntimeError: If the channel has no audio to start.
+        """
+
     def drop_current_audio(self) -> None:
         """
         Stops the currently playing audio, if any, and removes it from the channel.
@@ -536,6 +982,23 @@ class AudioChannel:
         :raises TypeError: If an unknown effect type is provided.
         """
 
+    def load_effects_preset(self, path: str) -> None:
+        """
+        Sets the effects chain from a preset file written by `effects.save_preset`.
+
+        Example:
+
+        .. code-block:: python
+
+            channel = AudioChannel()
+            channel.load_effects_preset("presets/radio.json")
+
+        :param path: Path to the preset file.
+        :type path: str
+        :raises ValueError: If the file is not a valid preset.
+        :raises RuntimeError: If the file cannot be read.
+        """
+
     def current_audio_data(self) -> Dict[str, Union[str, float, int, None]]:
         """
         Retrieves metadata and current playback information.
@@ -622,9 +1085,36 @@ class ChannelManager:
     """
     channels: dict[str, AudioChannel]
 
-    def __init__(self) -> None:
+    def __init__(self, output_config: Optional[OutputConfig] = None) -> None:
         """
         Initializes a new instance of ChannelManager.
+
+        :param output_config: Output configuration applied to every channel added to the manager.
+        :type output_config: Optional[OutputConfig]
+        """
+
+    @property
+    def output_config(self) -> Optional[OutputConfig]:
+        """
+        The output configuration applied to the manager's channels, or None if unset.
+
+        :rtype: Optional[OutputConfig]
+        """
+
+    def set_output_config(self, output_config: OutputConfig) -> None:
+        """
+        Apply an output configuration to every channel and the sinks in them.
+
+        :param output_config: The output configuration.
+        :type output_config: OutputConfig
+        """
+
+    def output_format(self) -> Dict[str, Any]:
+        """
+        The format the output device stream for the manager's configuration was opened with.
+        See `AudioSink.output_format`.
+
+        :rtype: dict
         """
 
     def add_channel(self, name: str, channel: AudioChannel) -> None:
@@ -662,3 +1152,233 @@ class ChannelManager:
         """
         Stops auto-consuming audio on all channels.
         """
+
+
+class SyncGroup:
+    """
+    Controls several AudioSinks and AudioChannels as one. Play, pause, seek and speed changes
+    are scheduled on the same output sample for every member, so stems stay locked together.
+    All members must share the same output configuration.
+
+        Example:
+
+        .. code-block:: python
+
+            drums = AudioSink().load_audio("drums.wav")
+            bass = AudioSink().load_audio("bass.wav")
+            vocals = AudioChannel()
+            vocals.push(AudioSink().load_audio("vocals.wav"))
+
+            group = SyncGroup([drums, bass, vocals])
+            group.play()
+            group.try_seek(30.0)
+            group.set_speed(0.9)
+            group.pause()
+
+    """
+
+    def __init__(self, members: Optional[List[Union[AudioSink, AudioChannel]]] = None) -> None:
+        """
+        :param members: Sinks and channels to start with.
+        :type members: Optional[List[Union[AudioSink, AudioChannel]]]
+        """
+
+    def add(self, member: Union[AudioSink, AudioChannel]) -> None:
+        """
+        Adds a sink or channel to the group. Adding a member twice has no effect.
+        """
+
+    def remove(self, member: Union[AudioSink, AudioChannel]) -> None:
+        """
+        Removes a sink or channel from the group.
+
+        :raises ValueError: If the member is not part of the group.
+        """
+
+    @property
+    def members(self) -> List[Union[AudioSink, AudioChannel]]:
+        """
+        The sinks and channels in the group.
+        """
+
+    @property
+    def is_playing(self) -> bool:
+        """
+        True if any member is playing.
+        """
+
+    def play(self) -> None:
+        """
+        Starts every member on the same output sample. Channels without current audio start
+        the next sink in their queue, and auto consume is enabled on them.
+
+        :raises RuntimeError: If a sink has no audio loaded.
+        :raises ValueError: If the members use different output configurations.
+        """
+
+    def play_at(self, timestamp: float) -> ScheduledEvent:
+        """
+        Starts every member on the same output sample at an absolute time on the
+        `time.monotonic()` clock.
+
+        :rtype: ScheduledEvent
+        """
+
+    def stop_at(self, timestamp: float) -> ScheduledEvent:
+        """
+        Stops every member on the same output sample at an absolute time on the
+        `time.monotonic()` clock.
+
+        :rtype: ScheduledEvent
+        """
+
+    def pause(self) -> None:
+        """
+        Pauses every member on the same output sample.
+        """
+
+    def stop(self) -> None:
+        """
+        Stops every member and disables auto consume on member channels.
+        """
+
+    def try_seek(self, position: float) -> None:
+        """
+        Seeks every member to the same position, in seconds, on the same output sample.
+
+        :param position: The position to seek to.
+        :type position: float
+        :raises ValueError: If the position is negative.
+        """
+
+    def set_speed(self, speed: float) -> None:
+        """
+        Changes the speed of every member on the same output sample.
+
+        :param speed: The playback speed. Must be greater than 0.
+        :type speed: float
+        :raises ValueError: If the speed is not greater than 0.
+        :raises EffectConflictException: If a member's speed is being changed by an effect.
+        """
+
+    def get_pos(self) -> float:
+        """
+        Position of the first member in seconds.
+
+        :raises RuntimeError: If no member has audio loaded.
+        """
+
+
+class EffectState:
+    """
+    Where an effect is in its lifetime.
+
+    - **Pending**: waiting for the audio to load or for its start position.
+    - **Active**: running.
+    - **Paused**: paused with `EffectHandle.pause`.
+    - **Done**: finished, cancelled, or its sink was unloaded.
+    """
+    Pending: "EffectState"
+    Active: "EffectState"
+    Paused: "EffectState"
+    Done: "EffectState"
+
+
+class EffectHandle:
+    """
+    A handle to one effect applied with `AudioSink.apply_effects`. The handle follows the
+    effect each time its sink loads the audio, so it also tracks the effect after `replay`.
+
+    :ivar id: Identifier of the effect, unique within the process.
+    :vartype id: int
+    :ivar effect: The effect the handle tracks.
+    """
+    id: int
+    effect: Any
+
+    @property
+    def state(self) -> EffectState:
+        """
+        The effect's current state.
+        """
+
+    @property
+    def progress(self) -> float:
+        """
+        How far the effect has run, from 0.0 to 1.0. Effects without a duration jump to 1.0
+        when they start.
+        """
+
+    @property
+    def value(self) -> float:
+        """
+        The effect's current value, such as the volume of a fade or the speed of a
+        `ChangeSpeed`.
+        """
+
+    def cancel(self) -> bool:
+        """
+        Removes the effect from its sink. Parameters it changed keep their current value.
+
+        :return: True if the effect was cancelled, False if it is not on the sink.
+        :rtype: bool
+        """
+
+    def pause(self) -> bool:
+        """
+        Freezes the effect's progress while the audio keeps playing. Processor effects such
+        as `Reverb` keep processing the audio.
+
+        :return: True if the effect was paused, False if it is not on the sink.
+        :rtype: bool
+        """
+
+    def resume(self) -> bool:
+        """
+        Continues a paused effect from where it was paused.
+
+        :return: True if the effect was resumed, False if it is not on the sink.
+        :rtype: bool
+        """
+
+    def extend(self, seconds: float) -> bool:
+        """
+        Lengthens the effect by `seconds`, or shortens it if negative. A running ramp
+        continues from its current value and reaches its end value `seconds` later. An
+        `Envelope` is not rescaled: it holds its last value for longer, and shortening cuts off
+        its last breakpoints.
+
+        :param seconds: Seconds to add to the effect's duration.
+        :return: True if the effect was extended, False if it is not on the sink or has
+        already finished.
+        :rtype: bool
+        :raises ValueError: If seconds is not finite or is more negative than the time left.
+        """
+
+
+class ScheduledEvent:
+    """
+    A play or stop scheduled with `AudioSink.play_at`, `AudioSink.stop_at`,
+    `AudioChannel.start_at`, `SyncGroup.play_at` or `SyncGroup.stop_at`.
+
+    :ivar timestamp: The time the event was scheduled for, on the `time.monotonic()` clock.
+    :vartype timestamp: float
+    :ivar action: ``"play"`` or ``"stop"``.
+    :vartype action: str
+    """
+    timestamp: float
+    action: str
+
+    @property
+    def pending(self) -> bool:
+        """
+        True until the event fires or is cancelled.
+        """
+
+    def cancel(self) -> bool:
+        """
+        Cancels the event.
+
+        :return: True if the event was cancelled, False if it already fired.
+        :rtype: bool
+        """
@@ -1,3 +1,120 @@
+import array
+from typing import Any, Callable, Dict, Optional, Sequence, Union
+
+
+class Curve:
+    """
+    Preset ramp shapes for effects.
+
+    - **Linear**: constant rate of change.
+    - **Exponential**: slow start, fast finish, over a 60 dB range. Suits fade-ins.
+    - **Logarithmic**: fast start, slow finish, the inverse of Exponential.
+    - **EqualPower**: quarter sine when rising and quarter cosine when falling, so a FadeOut
+      and a FadeIn of the same length cross at constant power.
+    - **SCurve**: slow at both ends and fastest in the middle.
+    """
+    Linear: "Curve"
+    Exponential: "Curve"
+    Logarithmic: "Curve"
+    EqualPower: "Curve"
+    SCurve: "Curve"
+
+
+class CubicBezier:
+    """
+    A custom ramp shape defined like CSS `cubic-bezier()`, with control points (x1, y1) and
+    (x2, y2) between the fixed end points (0, 0) and (1, 1).
+
+    :param x1: x of the first control point, between 0.0 and 1.0.
+    :param y1: y of the first control point.
+    :param x2: x of the second control point, between 0.0 and 1.0.
+    :param y2: y of the second control point.
+    :raises ValueError: If x1 or x2 is outside 0.0 to 1.0.
+
+    Example:
+
+    .. code-block:: python
+
+        ease_in_out = CubicBezier(0.42, 0.0, 0.58, 1.0)
+        change_speed = ChangeSpeed(duration=2.0, end_val=1.2, curve=ease_in_out)
+    """
+    x1: float
+    y1: float
+    x2: float
+    y2: float
+
+    def __init__(self, x1: float, y1: float, x2: float, y2: float) -> None:
+        pass
+
+
+
+class AnchorKind:
+    """
+    The kinds of `Anchor`.
+    """
+    Now: "AnchorKind"
+    Absolute: "AnchorKind"
+    BeforeEnd: "AnchorKind"
+    NextPlay: "AnchorKind"
+    Seek: "AnchorKind"
+
+
+class Anchor:
+    """
+    The point an effect's `apply_after` is measured from. Every effect takes an ``anchor``.
+    Without one, `apply_after` counts from the position when the sink receives the effect,
+    except `FadeOut`, which ends with the audio when `apply_after` is None.
+
+    Example:
+
+    .. code-block:: python
+
+        sink.apply_effects([
+            FadeIn(duration=1.0, anchor=Anchor.on_next_play()),
+            Reverb(mix=0.5, anchor=Anchor.absolute(30.0)),
+            Filter(cutoff=400.0, start_cutoff=8000.0, duration=5.0, anchor=Anchor.before_end()),
+            FadeIn(duration=0.3, anchor=Anchor.on_seek()),
+        ])
+    """
+    kind: AnchorKind
+    seconds: Optional[float]
+
+    @staticmethod
+    def now() -> "Anchor":
+        """
+        The position when the sink receives the effect.
+        """
+
+    @staticmethod
+    def absolute(position: float) -> "Anchor":
+        """
+        A position in the audio, in seconds. An effect anchored in the past starts part way
+        through.
+
+        :raises ValueError: If position is negative.
+        """
+
+    @staticmethod
+    def before_end(seconds: Optional[float] = None) -> "Anchor":
+        """
+        `seconds` before the end of the audio. If None, the effect's duration, so the effect
+        finishes with the audio.
+
+        :raises ValueError: If seconds is negative.
+        """
+
+    @staticmethod
+    def on_next_play() -> "Anchor":
+        """
+        The position the next time the sink starts playing, including the first `play`.
+        """
+
+    @staticmethod
+    def on_seek() -> "Anchor":
+        """
+        The position the sink seeks to next.
+        """
+
 
 class FadeIn:
     """
@@ -7,6 +124,10 @@ class FadeIn:
     :param start_val: Starting volume value. Defaults to None.
     :param end_val: Ending volume value. Defaults to 1.0.
     :param apply_after: Time in seconds after which to apply the effect. Defaults to None.
+    :param anchor: The `Anchor` apply_after is measured from. Defaults to None.
+    :param curve: Shape of the ramp, a `Curve` or a `CubicBezier`. Defaults to Curve.Linear.
+    :raises ValueError: If duration or apply_after is negative, or a volume is outside 0.0 to
+        1.0. Setting an attribute to an invalid value raises the same errors.
 
     Example:
 
@@ -14,11 +135,22 @@ class FadeIn:
 
         fade_in = FadeIn(duration=3.0, start_val=0.2, end_val=1.0)
         # Applies a fade-in effect over 3 seconds, starting from 0.2 volume to full volume (1.0)
+
+        fade_in = FadeIn(duration=3.0, curve=Curve.EqualPower)
+        # Rises along a quarter sine, for crossfading against an equal power FadeOut
     """
 
-    def __init__(self, duration=5.0, start_val=None, end_val=1.0, apply_after=None):
+    def __init__(self, duration=5.0, start_val=None, end_val=1.0, apply_after=None, anchor=None, curve=Curve.Linear):
         pass
 
+    @staticmethod
+    def schema() -> Dict[str, Dict[str, Any]]:
+        """
+        The declared parameters keyed by name, for building effect editors. Each entry has
+        ``type``, ``default``, ``min``, ``max``, ``exclusive_min``, ``exclusive_max`` and
+        ``description`` keys.
+        """
+
 
 class FadeOut:
     """
@@ -28,6 +160,10 @@ class FadeOut:
     :param start_val: Starting volume value. Defaults to 1.0.
     :param end_val: Ending volume value. Defaults to None.
     :param apply_after: Time in seconds after which to apply the effect. Defaults to None.
+    :param anchor: The `Anchor` apply_after is measured from. Defaults to None.
+    :param curve: Shape of the ramp, a `Curve` or a `CubicBezier`. Defaults to Curve.Linear.
+    :raises ValueError: If duration or apply_after is negative, or a volume is outside 0.0 to
+        1.0. Setting an attribute to an invalid value raises the same errors.
 
     Example:
 
@@ -37,9 +173,17 @@ class FadeOut:
         # Applies a fade-out effect over 4 seconds, fading from full volume (1.0) to silence (0.0)
     """
 
-    def __init__(self, duration=5.0, start_val=1.0, end_val=None, apply_after=None):
+    def __init__(self, duration=5.0, start_val=1.0, end_val=None, apply_after=None, anchor=None, curve=Curve.Linear):
         pass
 
+    @staticmethod
+    def schema() -> Dict[str, Dict[str, Any]]:
+        """
+        The declared parameters keyed by name, for building effect editors. Each entry has
+        ``type``, ``default``, ``min``, ``max``, ``exclusive_min``, ``exclusive_max`` and
+        ``description`` keys.
+        """
+
 
 class ChangeSpeed:
     """
@@ -49,6 +193,10 @@ class ChangeSpeed:
     :param start_val: Starting speed value. Defaults to 1.0.
     :param end_val: Ending speed value. Defaults to 1.5.
     :param apply_after: Time in seconds after which to apply the effect. Defaults to None.
+    :param anchor: The `Anchor` apply_after is measured from. Defaults to None.
+    :param curve: Shape of the ramp, a `Curve` or a `CubicBezier`. Defaults to Curve.Linear.
+    :raises ValueError: If duration or apply_after is negative, or a speed is not greater than
+        0. Setting an attribute to an invalid value raises the same errors.
 
     Example:
 
@@ -58,5 +206,993 @@ class ChangeSpeed:
         # Changes audio speed over 2 seconds from normal speed (1.0) to faster (1.2)
     """
 
-    def __init__(self, duration=0.0, start_val=1.0, end_val=1.5, apply_after=None):
+    def __init__(self, duration=0.0, start_val=1.0, end_val=1.5, apply_after=None, anchor=None, curve=Curve.Linear):
         pass
+
+    @staticmethod
+    def schema() -> Dict[str, Dict[str, Any]]:
+        """
+        The declared parameters keyed by name, for building effect editors. Each entry has
+        ``type``, ``default``, ``min``, ``max``, ``exclusive_min``, ``exclusive_max`` and
+        ``description`` keys.
+        """
+
+
+class AutomationTarget:
+    """
+    Parameters an `Envelope` can automate.
+
+    - **Volume**: values between 0.0 and 1.0.
+    - **Speed**: values greater than 0.
+    - **Pan**: values between -1.0 (left) and 1.0 (right). Mono audio is spread over two
+      channels so it can be panned.
+    - **FilterCutoff**: cutoff in Hz of the sink's `Filter` effects, or of a low pass filter
+      added for the envelope if there are none. Values at or above half the output sample
+      rate leave the audio unfiltered.
+
+    Pan and filter cutoff keep the last envelope value after the envelope ends.
+    """
+    Volume: "AutomationTarget"
+    Speed: "AutomationTarget"
+    Pan: "AutomationTarget"
+    FilterCutoff: "AutomationTarget"
+
+
+class ConflictPolicy:
+    """
+    How manual volume and speed changes and overlapping effects on the same parameter
+    combine, set per sink with `AudioSink.conflict_policy`. Pan and filter cutoff always
+    follow the newest effect.
+
+    - **Reject**: `set_volume` and `set_speed` raise `EffectConflictException` while an
+      effect drives the parameter. When effects overlap, the newest one that has started is
+      heard. This is the default.
+    - **Override**: manual changes cancel the running effects on the parameter, and a newly
+      started effect cancels the older ones.
+    - **Multiply**: the manual value is scaled by every running effect, so a `FadeIn` fades
+      in to the volume set with `set_volume`. Manual changes never raise.
+    - **Queue**: manual changes are applied once the running effects finish, and an effect
+      that starts while another drives the parameter waits for it to finish.
+    """
+    Reject: "ConflictPolicy"
+    Override: "ConflictPolicy"
+    Multiply: "ConflictPolicy"
+    Queue: "ConflictPolicy"
+
+
+class Envelope:
+    """
+    Automates a parameter through a list of breakpoints. Each breakpoint is a
+    ``(time, value)`` or ``(time, value, curve)`` tuple, where time is in seconds from the
+    start of the envelope and curve shapes the ramp from that breakpoint to the next one.
+    The value holds at the first breakpoint before its time.
+
+    :param points: The breakpoints, in increasing time order.
+    :param target: The parameter to automate. Defaults to AutomationTarget.Volume.
+    :param apply_after: Time in seconds after which to start the envelope. Defaults to None.
+    :param anchor: The `Anchor` apply_after is measured from. Defaults to None.
+    :raises ValueError: If there are no breakpoints, times are not increasing, a value is
+        out of range for the target, or apply_after is negative. Setting apply_after to a
+        negative value raises the same error.
+
+    Example:
+
+    .. code-block:: python
+
+        duck = Envelope([
+            (0.0, 1.0),
+            (0.5, 0.3, Curve.SCurve),
+            (4.0, 0.3),
+            (5.0, 1.0),
+        ])
+        sweep = Envelope([(0.0, 200.0, Curve.Exponential), (8.0, 18000.0)], target=AutomationTarget.FilterCutoff)
+        sink.apply_effects([duck, sweep])
+    """
+    points: list[tuple[float, float, Union[Curve, CubicBezier]]]
+    target: AutomationTarget
+    apply_after: Optional[float]
+    anchor: Optional[Anchor]
+    duration: float
+
+    def __init__(self, points, target=AutomationTarget.Volume, apply_after=None, anchor=None):
+        pass
+
+    @staticmethod
+    def schema() -> Dict[str, Dict[str, Any]]:
+        """
+        The declared parameters keyed by name, as for `FadeIn.schema`.
+        """
+
+    def as_dict(self) -> dict:
+        """
+        The envelope as a dictionary.
+        """
+
+
+class Automation:
+    """
+    Drives a parameter from a Python function of time. The function is called with the
+    seconds since the automation started and returns the parameter value. It runs on a
+    background thread at ``control_rate`` calls per second, so keep it cheap. If it raises
+    or returns a value out of range for the target, the error is printed and the parameter
+    keeps its last value.
+
+    :param function: A callable taking the elapsed time in seconds and returning a float.
+    :param target: The parameter to automate. Defaults to AutomationTarget.Volume.
+    :param duration: Seconds to run the function for. Defaults to None, running until the
+        audio ends.
+    :param control_rate: Calls per second, up to 1000. Defaults to 100.0.
+    :param apply_after: Time in seconds after which to start the automation. Defaults to None.
+    :param anchor: The `Anchor` apply_after is measured from. Defaults to None.
+    :raises TypeError: If function is not callable.
+    :raises ValueError: If duration or control_rate is out of range, or apply_after is
+        negative. Setting an attribute to an invalid value raises the same errors.
+
+    Example:
+
+    .. code-block:: python
+
+        import math
+
+        wobble = Automation(lambda t: 0.5 + 0.4 * math.sin(t * 2.0), duration=10.0)
+        autopan = Automation(lambda t: math.sin(t), target=AutomationTarget.Pan)
+        sink.apply_effects([wobble, autopan])
+    """
+    function: Optional[Callable[[float], float]]
+    target: AutomationTarget
+    control_rate: float
+    duration: Optional[float]
+    apply_after: Optional[float]
+    anchor: Optional[Anchor]
+
+    def __init__(self, function: Callable[[float], float], target=AutomationTarget.Volume, duration=None, control_rate=100.0, apply_after=None, anchor=None):
+        pass
+
+    @staticmethod
+    def schema() -> Dict[str, Dict[str, Any]]:
+        """
+        The declared parameters keyed by name, as for `FadeIn.schema`.
+        """
+
+    def as_dict(self) -> dict:
+        """
+        The automation as a dictionary, without the function.
+        """
+
+
+class FilterType:
+    """
+    Filter responses available to `Filter`.
+
+    - **LowPass** / **HighPass**: attenuate frequencies above / below the cutoff.
+    - **BandPass**: keep a band around the cutoff, narrower as Q increases.
+    - **Notch**: remove a band around the cutoff.
+    - **LowShelf** / **HighShelf**: boost or cut below / above the cutoff by `gain` dB.
+    - **Peak**: boost or cut a band around the cutoff by `gain` dB.
+    """
+    LowPass: "FilterType"
+    HighPass: "FilterType"
+    BandPass: "FilterType"
+    Notch: "FilterType"
+    LowShelf: "FilterType"
+    HighShelf: "FilterType"
+    Peak: "FilterType"
+
+
+class Filter:
+    """
+    A biquad filter applied to the sink. The filter stays active after any sweep ends.
+
+    :param filter_type: The filter response. Defaults to FilterType.LowPass.
+    :param cutoff: The cutoff or center frequency in Hz. Defaults to 1000.0.
+    :param q: The resonance of the filter. Defaults to 0.707 (Butterworth).
+    :param gain: Boost or cut in dB for shelf and peak filters. Defaults to 0.0.
+    :param start_cutoff: Cutoff in Hz to sweep from. Defaults to None, which means no sweep.
+    :param duration: Duration in seconds of the cutoff sweep. Defaults to None.
+    :param apply_after: Time in seconds after which to apply the filter. Defaults to None.
+    :param anchor: The `Anchor` apply_after is measured from. Defaults to None.
+    :param curve: The shape of the cutoff sweep. Defaults to Curve.Linear.
+    :raises ValueError: If cutoff, start_cutoff or q is not greater than 0, or duration or
+        apply_after is negative. Setting an attribute to an invalid value raises the same
+        errors.
+
+    Example:
+
+    .. code-block:: python
+
+        underwater = Filter(FilterType.LowPass, cutoff=400.0, start_cutoff=12000.0, duration=2.0, curve=Curve.Exponential)
+        rumble_cut = Filter(FilterType.HighPass, cutoff=80.0)
+        sink.apply_effects([underwater, rumble_cut])
+    """
+    filter_type: FilterType
+    cutoff: float
+    q: float
+    gain: float
+    start_cutoff: Optional[float]
+    duration: Optional[float]
+    apply_after: Optional[float]
+    anchor: Optional[Anchor]
+    curve: Union[Curve, CubicBezier]
+
+    def __init__(self, filter_type=FilterType.LowPass, cutoff=1000.0, q=0.707, gain=0.0,
+                 start_cutoff=None, duration=None, apply_after=None, anchor=None, curve=Curve.Linear):
+        pass
+
+    @staticmethod
+    def schema() -> Dict[str, Dict[str, Any]]:
+        """
+        The declared parameters keyed by name, as for `FadeIn.schema`.
+        """
+
+    def as_dict(self) -> dict:
+        """
+        The filter as a dictionary.
+        """
+
+
+class EQBand:
+    """
+    One band of a `ParametricEQ`.
+
+    :param band_type: The band response. Defaults to FilterType.Peak.
+    :param frequency: The center or cutoff frequency in Hz. Defaults to 1000.0.
+    :param gain: Boost or cut in dB for peak and shelf bands. Defaults to 0.0.
+    :param q: The width of the band. Defaults to 0.707.
+    :raises ValueError: If frequency or q is not greater than 0, or gain is not finite.
+    """
+    band_type: FilterType
+    frequency: float
+    gain: float
+    q: float
+
+    def __init__(self, band_type=FilterType.Peak, frequency=1000.0, gain=0.0, q=0.707):
+        pass
+
+    @staticmethod
+    def schema() -> Dict[str, Dict[str, Any]]:
+        """
+        The declared parameters keyed by name, as for `FadeIn.schema`.
+        """
+
+
+class ParametricEQ:
+    """
+    An equalizer with any number of bands, applied in order. Band settings are shared by
+    every sink the equalizer is applied to, so `set_band` changes are heard during playback.
+    Changes glide over about 20 ms to avoid clicks.
+
+    :param bands: The bands of the equalizer. Defaults to no bands.
+    :param apply_after: Time in seconds after which to apply the equalizer. Defaults to None.
+    :param anchor: The `Anchor` apply_after is measured from. Defaults to None.
+    :raises ValueError: If apply_after is negative. Setting apply_after to a negative value
+        raises the same error.
+
+    Example:
+
+    .. code-block:: python
+
+        eq = ParametricEQ([
+            EQBand(FilterType.HighPass, frequency=40.0),
+            EQBand(FilterType.LowShelf, frequency=120.0, gain=3.0),
+            EQBand(FilterType.Peak, frequency=2500.0, gain=-4.0, q=1.4),
+            EQBand(FilterType.HighShelf, frequency=9000.0, gain=2.0),
+        ])
+        channel.set_effects_chain([eq])
+
+        # Later, while playing
+        eq.set_band(2, gain=0.0)
+
+        with open("vocal_eq.json", "w") as f:
+            json.dump(eq.as_dict(), f)
+    """
+    bands: list[EQBand]
+    apply_after: Optional[float]
+    anchor: Optional[Anchor]
+
+    def __init__(self, bands=[], apply_after=None, anchor=None):
+        pass
+
+    def set_band(self, index: int, frequency: Optional[float] = None, gain: Optional[float] = None,
+                 q: Optional[float] = None) -> None:
+        """
+        Changes the settings of a band. Settings left as None are unchanged.
+
+        :raises IndexError: If there is no band at index.
+        :raises ValueError: If frequency or q is not greater than 0, or gain is not finite.
+        """
+        pass
+
+    def __len__(self) -> int:
+        pass
+
+    @staticmethod
+    def schema() -> Dict[str, Dict[str, Any]]:
+        """
+        The declared parameters keyed by name, as for `FadeIn.schema`.
+        """
+
+    def as_dict(self) -> dict:
+        """
+        The equalizer as a dictionary.
+        """
+
+
+class Reverb:
+    """
+    An algorithmic room reverb. Once applied, the reverb tail keeps playing after the audio
+    ends, and the sink's callback fires when the tail has died away. Room size, damping and mix
+    are shared by every sink the reverb is applied to, so setting them is heard during playback;
+    the pre-delay is fixed once applied.
+
+    :param room_size: Size of the room, from 0.0 (small) to 1.0 (large). Larger rooms have
+        longer tails. Defaults to 0.5.
+    :param damping: High frequency absorption, from 0.0 (bright) to 1.0 (dark). Defaults to 0.5.
+    :param mix: Wet/dry balance, from 0.0 (dry) to 1.0 (only reverb). Defaults to 0.3.
+    :param pre_delay: Delay in seconds before the reverb starts, up to 1.0. Defaults to 0.0.
+    :param apply_after: Time in seconds after which to apply the reverb. Defaults to None.
+    :param anchor: The `Anchor` apply_after is measured from. Defaults to None.
+    :raises ValueError: If a parameter is out of range. Setting an attribute to an invalid
+        value raises the same errors.
+
+    Example:
+
+    .. code-block:: python
+
+        hall = Reverb(room_size=0.85, damping=0.3, mix=0.35, pre_delay=0.03)
+        channel.set_effects_chain([hall])
+    """
+    room_size: float
+    damping: float
+    mix: float
+    pre_delay: float
+    apply_after: Optional[float]
+    anchor: Optional[Anchor]
+
+    def __init__(self, room_size=0.5, damping=0.5, mix=0.3, pre_delay=0.0, apply_after=None, anchor=None):
+        pass
+
+    @staticmethod
+    def schema() -> Dict[str, Dict[str, Any]]:
+        """
+        The declared parameters keyed by name, as for `FadeIn.schema`.
+        """
+
+    def as_dict(self) -> dict:
+        """
+        The reverb as a dictionary.
+        """
+
+
+class NoteValue:
+    """
+    Note lengths for tempo synced effects, relative to a quarter note beat.
+    """
+    Whole: "NoteValue"
+    Half: "NoteValue"
+    Quarter: "NoteValue"
+    Eighth: "NoteValue"
+    Sixteenth: "NoteValue"
+    ThirtySecond: "NoteValue"
+    DottedHalf: "NoteValue"
+    DottedQuarter: "NoteValue"
+    DottedEighth: "NoteValue"
+    DottedSixteenth: "NoteValue"
+    HalfTriplet: "NoteValue"
+    QuarterTriplet: "NoteValue"
+    EighthTriplet: "NoteValue"
+    SixteenthTriplet: "NoteValue"
+
+
+class Delay:
+    """
+    An echo effect. The delay time is given in seconds, or as a note value at a tempo. Once
+    applied, the echoes keep playing after the audio ends. Feedback and mix are shared by every
+    sink the delay is applied to, so setting them is heard during playback; the time and
+    ping-pong routing are fixed once applied.
+
+    :param time: Delay time in seconds, up to 4.0. Ignored when note and bpm are given.
+        Setting time later clears note and bpm. Defaults to 0.25.
+    :param feedback: Amount of each echo fed back into the delay, from 0.0 up to but not
+        including 1.0. Defaults to 0.35.
+    :param mix: Wet/dry balance, from 0.0 (dry) to 1.0 (only echoes). Defaults to 0.3.
+    :param ping_pong: Bounce echoes between the left and right channels. Defaults to False.
+    :param note: Note value of the delay time, used with bpm. Defaults to None.
+    :param bpm: Tempo in beats per minute, used with note. Defaults to None.
+    :param apply_after: Time in seconds after which to apply the delay. Defaults to None.
+    :param anchor: The `Anchor` apply_after is measured from. Defaults to None.
+    :raises ValueError: If a parameter is out of range, or only one of note and bpm is given.
+        Setting an attribute to an invalid value raises the same errors.
+
+    Example:
+
+    .. code-block:: python
+
+        slapback = Delay(time=0.12, feedback=0.1, mix=0.25)
+        dotted = Delay(note=NoteValue.DottedEighth, bpm=120, feedback=0.45, ping_pong=True)
+        sink.apply_effects([dotted])
+        print(dotted.time)  # 0.375
+    """
+    time: float
+    feedback: float
+    mix: float
+    ping_pong: bool
+    note: Optional[NoteValue]
+    bpm: Optional[float]
+    apply_after: Optional[float]
+    anchor: Optional[Anchor]
+
+    def __init__(self, time=0.25, feedback=0.35, mix=0.3, ping_pong=False, note=None, bpm=None,
+                 apply_after=None, anchor=None):
+        pass
+
+    @staticmethod
+    def schema() -> Dict[str, Dict[str, Any]]:
+        """
+        The declared parameters keyed by name, as for `FadeIn.schema`.
+        """
+
+    def as_dict(self) -> dict:
+        """
+        The delay as a dictionary.
+        """
+
+
+class Compressor:
+    """
+    Reduces the level of audio above a threshold. Stereo channels are compressed together.
+    Settings are shared by every sink the compressor is applied to, so setting them is heard
+    during playback.
+
+    :param threshold: Level in dB above which the audio is compressed. Defaults to -18.0.
+    :param ratio: Input to output ratio above the threshold, at least 1.0. Defaults to 4.0.
+    :param attack: Time in seconds to react to a rise in level, up to 1.0. Defaults to 0.01.
+    :param release: Time in seconds to recover after the level falls, up to 5.0. Defaults to 0.1.
+    :param knee: Width in dB of the soft knee around the threshold. Defaults to 6.0.
+    :param makeup: Gain in dB added after compression. Defaults to 0.0.
+    :param apply_after: Time in seconds after which to apply the compressor. Defaults to None.
+    :param anchor: The `Anchor` apply_after is measured from. Defaults to None.
+    :raises ValueError: If a parameter is out of range. Setting an attribute to an invalid
+        value raises the same errors.
+
+    Example:
+
+    .. code-block:: python
+
+        voice = Compressor(threshold=-24.0, ratio=3.0, attack=0.005, release=0.15, makeup=6.0)
+        voice_channel.set_effects_chain([voice])
+        print(voice.gain_reduction)
+    """
+    threshold: float
+    ratio: float
+    attack: float
+    release: float
+    knee: float
+    makeup: float
+    apply_after: Optional[float]
+    anchor: Optional[Anchor]
+    gain_reduction: float
+    """Current gain reduction in dB, from the most recent sink the compressor ran on."""
+
+    def __init__(self, threshold=-18.0, ratio=4.0, attack=0.01, release=0.1, knee=6.0, makeup=0.0,
+                 apply_after=None, anchor=None):
+        pass
+
+    @staticmethod
+    def schema() -> Dict[str, Dict[str, Any]]:
+        """
+        The declared parameters keyed by name, as for `FadeIn.schema`.
+        """
+
+    def as_dict(self) -> dict:
+        """
+        The compressor as a dictionary.
+        """
+
+
+class Limiter:
+    """
+    Keeps peaks from going over a threshold, reacting instantly to rises in level. Settings are
+    shared by every sink the limiter is applied to, so setting them is heard during playback.
+
+    :param threshold: The ceiling in dB. Defaults to -1.0.
+    :param release: Time in seconds to recover after a peak, up to 5.0. Defaults to 0.05.
+    :param apply_after: Time in seconds after which to apply the limiter. Defaults to None.
+    :param anchor: The `Anchor` apply_after is measured from. Defaults to None.
+    :raises ValueError: If a parameter is out of range. Setting an attribute to an invalid
+        value raises the same errors.
+    """
+    threshold: float
+    release: float
+    apply_after: Optional[float]
+    anchor: Optional[Anchor]
+    gain_reduction: float
+    """Current gain reduction in dB, from the most recent sink the limiter ran on."""
+
+    def __init__(self, threshold=-1.0, release=0.05, apply_after=None, anchor=None):
+        pass
+
+    @staticmethod
+    def schema() -> Dict[str, Dict[str, Any]]:
+        """
+        The declared parameters keyed by name, as for `FadeIn.schema`.
+        """
+
+    def as_dict(self) -> dict:
+        """
+        The limiter as a dictionary.
+        """
+
+
+class NoiseGate:
+    """
+    Mutes audio while its level is below a threshold, such as background noise between words.
+    Settings are shared by every sink the gate is applied to, so setting them is heard during
+    playback.
+
+    :param threshold: Level in dB below which the gate closes. Defaults to -50.0.
+    :param attack: Time in seconds for the gate to open, up to 1.0. Defaults to 0.002.
+    :param release: Time in seconds for the gate to close, up to 5.0. Defaults to 0.1.
+    :param range: Attenuation in dB while the gate is closed. Defaults to 80.0.
+    :param apply_after: Time in seconds after which to apply the gate. Defaults to None.
+    :param anchor: The `Anchor` apply_after is measured from. Defaults to None.
+    :raises ValueError: If a parameter is out of range. Setting an attribute to an invalid
+        value raises the same errors.
+    """
+    threshold: float
+    attack: float
+    release: float
+    range: float
+    apply_after: Optional[float]
+    anchor: Optional[Anchor]
+    gain_reduction: float
+    """Current gain reduction in dB, from the most recent sink the gate ran on."""
+
+    def __init__(self, threshold=-50.0, attack=0.002, release=0.1, range=80.0, apply_after=None, anchor=None):
+        pass
+
+    @staticmethod
+    def schema() -> Dict[str, Dict[str, Any]]:
+        """
+        The declared parameters keyed by name, as for `FadeIn.schema`.
+        """
+
+    def as_dict(self) -> dict:
+        """
+        The noise gate as a dictionary.
+        """
+
+
+class DistortionType:
+    """
+    Waveshapers available to `Distortion`.
+
+    - **SoftClip**: smooth saturation.
+    - **HardClip**: flat topped clipping, harsher and brighter.
+    - **Tube**: asymmetric saturation with even harmonics.
+    """
+    SoftClip: "DistortionType"
+    HardClip: "DistortionType"
+    Tube: "DistortionType"
+
+
+class Distortion:
+    """
+    Drives the audio into a waveshaper. The amount blends the distorted signal with the dry
+    one and can be swept from start_amount like the other timed effects. Drive and amount are
+    shared by every sink the distortion is applied to, so setting them is heard during playback,
+    after any sweep has finished; the waveshaper is fixed once applied.
+
+    :param distortion_type: The waveshaper. Defaults to DistortionType.SoftClip.
+    :param drive: Gain in dB into the waveshaper, from 0.0 to 48.0. Defaults to 12.0.
+    :param amount: Blend of the distorted signal, from 0.0 (dry) to 1.0. Defaults to 1.0.
+    :param start_amount: Amount to sweep from. Defaults to None, which means no sweep.
+    :param duration: Duration in seconds of the amount sweep. Defaults to None.
+    :param apply_after: Time in seconds after which to apply the distortion. Defaults to None.
+    :param anchor: The `Anchor` apply_after is measured from. Defaults to None.
+    :param curve: The shape of the amount sweep. Defaults to Curve.Linear.
+    :raises ValueError: If drive or an amount is out of range, or duration or apply_after is
+        negative. Setting an attribute to an invalid value raises the same errors.
+
+    Example:
+
+    .. code-block:: python
+
+        overdrive = Distortion(DistortionType.Tube, drive=18.0, start_amount=0.0, amount=0.8, duration=4.0)
+        sink.apply_effects([overdrive])
+    """
+    distortion_type: DistortionType
+    drive: float
+    amount: float
+    start_amount: Optional[float]
+    duration: Optional[float]
+    apply_after: Optional[float]
+    anchor: Optional[Anchor]
+    curve: Union[Curve, CubicBezier]
+
+    def __init__(self, distortion_type=DistortionType.SoftClip, drive=12.0, amount=1.0,
+                 start_amount=None, duration=None, apply_after=None, anchor=None, curve=Curve.Linear):
+        pass
+
+    @staticmethod
+    def schema() -> Dict[str, Dict[str, Any]]:
+        """
+        The declared parameters keyed by name, as for `FadeIn.schema`.
+        """
+
+    def as_dict(self) -> dict:
+        """
+        The distortion as a dictionary.
+        """
+
+
+class Bitcrusher:
+    """
+    Lowers the bit depth and sample rate of the audio for a lo-fi, retro sound. The amount
+    blends the crushed signal with the dry one and can be swept from start_amount like the
+    other timed effects. Bits, sample rate and amount are shared by every sink the bitcrusher is
+    applied to, so setting them is heard during playback, after any sweep has finished.
+
+    :param bits: Bit depth, from 1 to 24. Defaults to 8.
+    :param sample_rate: Sample rate in Hz to imitate. Defaults to 11025.
+    :param amount: Blend of the crushed signal, from 0.0 (dry) to 1.0. Defaults to 1.0.
+    :param start_amount: Amount to sweep from. Defaults to None, which means no sweep.
+    :param duration: Duration in seconds of the amount sweep. Defaults to None.
+    :param apply_after: Time in seconds after which to apply the bitcrusher. Defaults to None.
+    :param anchor: The `Anchor` apply_after is measured from. Defaults to None.
+    :param curve: The shape of the amount sweep. Defaults to Curve.Linear.
+    :raises ValueError: If bits, sample_rate or an amount is out of range, or duration or
+        apply_after is negative. Setting an attribute to an invalid value raises the same
+        errors.
+
+    Example:
+
+    .. code-block:: python
+
+        chiptune = Bitcrusher(bits=4, sample_rate=8000)
+        sink.apply_effects([chiptune])
+    """
+    bits: float
+    sample_rate: float
+    amount: float
+    start_amount: Optional[float]
+    duration: Optional[float]
+    apply_after: Optional[float]
+    anchor: Optional[Anchor]
+    curve: Union[Curve, CubicBezier]
+
+    def __init__(self, bits=8, sample_rate=11025.0, amount=1.0, start_amount=None, duration=None,
+                 apply_after=None, anchor=None, curve=Curve.Linear):
+        pass
+
+    @staticmethod
+    def schema() -> Dict[str, Dict[str, Any]]:
+        """
+        The declared parameters keyed by name, as for `FadeIn.schema`.
+        """
+
+    def as_dict(self) -> dict:
+        """
+        The bitcrusher as a dictionary.
+        """
+
+
+class Chorus:
+    """
+    Thickens the sound with copies delayed by 20 to 28 ms, swept by an LFO. Stereo
+    channels are swept out of phase. Settings are shared by every sink the chorus is applied
+    to, so setting them is heard during playback.
+
+    :param rate: LFO rate in Hz, up to 20.0. Defaults to 0.8.
+    :param depth: How far the LFO sweeps, from 0.0 to 1.0. Defaults to 0.5.
+    :param feedback: Amount of output fed back into the effect, from -0.95 to 0.95. Defaults to 0.0.
+    :param mix: Wet/dry balance, from 0.0 (dry) to 1.0 (only the effect). Defaults to 0.5.
+    :param apply_after: Time in seconds after which to apply the effect. Defaults to None.
+    :param anchor: The `Anchor` apply_after is measured from. Defaults to None.
+    :raises ValueError: If a parameter is out of range. Setting an attribute to an invalid
+        value raises the same errors.
+
+    Example:
+
+    .. code-block:: python
+
+        pad = Chorus(rate=0.6, depth=0.8, mix=0.4)
+        flange = Flanger(rate=0.1, depth=1.0, feedback=-0.7)
+        swirl = Phaser(rate=0.3, stages=8)
+        sink.apply_effects([pad, swirl])
+    """
+    rate: float
+    depth: float
+    feedback: float
+    mix: float
+    apply_after: Optional[float]
+    anchor: Optional[Anchor]
+
+    def __init__(self, rate=0.8, depth=0.5, feedback=0.0, mix=0.5, apply_after=None, anchor=None):
+        pass
+
+    @staticmethod
+    def schema() -> Dict[str, Dict[str, Any]]:
+        """
+        The declared parameters keyed by name, as for `FadeIn.schema`.
+        """
+
+    def as_dict(self) -> dict:
+        """
+        The chorus as a dictionary.
+        """
+
+
+class Flanger:
+    """
+    A jet-like sweep from a copy delayed by 1 to 5 ms, swept by an LFO. Negative
+    feedback gives a hollower sound. Settings are shared by every sink the flanger is applied
+    to, so setting them is heard during playback.
+
+    :param rate: LFO rate in Hz, up to 20.0. Defaults to 0.25.
+    :param depth: How far the LFO sweeps, from 0.0 to 1.0. Defaults to 0.7.
+    :param feedback: Amount of output fed back into the effect, from -0.95 to 0.95. Defaults to 0.5.
+    :param mix: Wet/dry balance, from 0.0 (dry) to 1.0 (only the effect). Defaults to 0.5.
+    :param apply_after: Time in seconds after which to apply the effect. Defaults to None.
+    :param anchor: The `Anchor` apply_after is measured from. Defaults to None.
+    :raises ValueError: If a parameter is out of range. Setting an attribute to an invalid
+        value raises the same errors.
+    """
+    rate: float
+    depth: float
+    feedback: float
+    mix: float
+    apply_after: Optional[float]
+    anchor: Optional[Anchor]
+
+    def __init__(self, rate=0.25, depth=0.7, feedback=0.5, mix=0.5, apply_after=None, anchor=None):
+        pass
+
+    @staticmethod
+    def schema() -> Dict[str, Dict[str, Any]]:
+        """
+        The declared parameters keyed by name, as for `FadeIn.schema`.
+        """
+
+    def as_dict(self) -> dict:
+        """
+        The flanger as a dictionary.
+        """
+
+
+class Phaser:
+    """
+    Moving notches from a chain of allpass stages swept between 200 Hz and 3.2 kHz
+    by an LFO. Rate, depth, feedback and mix are shared by every sink the phaser is applied
+    to, so setting them is heard during playback; the number of stages is fixed once applied.
+
+    :param rate: LFO rate in Hz, up to 20.0. Defaults to 0.5.
+    :param depth: How far the LFO sweeps, from 0.0 to 1.0. Defaults to 0.7.
+    :param feedback: Amount of output fed back into the effect, from -0.95 to 0.95. Defaults to 0.3.
+    :param mix: Wet/dry balance, from 0.0 (dry) to 1.0 (only the effect). Defaults to 0.5.
+    :param stages: Number of allpass stages, from 2 to 12. Each pair adds a notch. Defaults to 4.
+    :param apply_after: Time in seconds after which to apply the effect. Defaults to None.
+    :param anchor: The `Anchor` apply_after is measured from. Defaults to None.
+    :raises ValueError: If a parameter is out of range. Setting an attribute to an invalid
+        value raises the same errors.
+    """
+    rate: float
+    depth: float
+    feedback: float
+    mix: float
+    stages: int
+    apply_after: Optional[float]
+    anchor: Optional[Anchor]
+
+    def __init__(self, rate=0.5, depth=0.7, feedback=0.3, mix=0.5, stages=4, apply_after=None, anchor=None):
+        pass
+
+    @staticmethod
+    def schema() -> Dict[str, Dict[str, Any]]:
+        """
+        The declared parameters keyed by name, as for `FadeIn.schema`.
+        """
+
+    def as_dict(self) -> dict:
+        """
+        The phaser as a dictionary.
+        """
+
+
+class Waveform:
+    """
+    LFO shapes for `Tremolo` and `Vibrato`. Random holds a new random level each cycle.
+    """
+    Sine: "Waveform"
+    Triangle: "Waveform"
+    Square: "Waveform"
+    Random: "Waveform"
+
+
+class Tremolo:
+    """
+    Modulates the volume with an LFO. Runs from apply_after until the end of the audio, or
+    for duration seconds if given. Rate and depth are shared by every sink the tremolo is applied
+    to, so setting them is heard during playback; the waveform is fixed once applied.
+
+    :param rate: LFO rate in Hz, up to 20.0. Defaults to 5.0.
+    :param depth: Volume dip at the bottom of each cycle, from 0.0 to 1.0 (silent). Defaults to 0.5.
+    :param waveform: Shape of the LFO. Defaults to Waveform.Sine.
+    :param duration: Time in seconds the tremolo runs for. Defaults to None.
+    :param apply_after: Time in seconds after which to start the tremolo. Defaults to None.
+    :param anchor: The `Anchor` apply_after is measured from. Defaults to None.
+    :raises ValueError: If a parameter is out of range. Setting an attribute to an invalid
+        value raises the same errors.
+
+    Example:
+
+    .. code-block:: python
+
+        # A pulsing warning sound for one second, then a wobble on the music
+        warning.apply_effects([Tremolo(rate=8.0, depth=1.0, waveform=Waveform.Square, duration=1.0)])
+        music.apply_effects([Vibrato(rate=5.5, depth=0.4, apply_after=10.0, duration=4.0)])
+    """
+    rate: float
+    depth: float
+    waveform: Waveform
+    duration: Optional[float]
+    apply_after: Optional[float]
+    anchor: Optional[Anchor]
+
+    def __init__(self, rate=5.0, depth=0.5, waveform=Waveform.Sine, duration=None, apply_after=None, anchor=None):
+        pass
+
+    @staticmethod
+    def schema() -> Dict[str, Dict[str, Any]]:
+        """
+        The declared parameters keyed by name, as for `FadeIn.schema`.
+        """
+
+    def as_dict(self) -> dict:
+        """
+        The tremolo as a dictionary.
+        """
+
+
+class Vibrato:
+    """
+    Modulates the pitch with an LFO. Runs from apply_after until the end of the audio, or
+    for duration seconds if given. Rate and depth are shared by every sink the vibrato is applied
+    to, so setting them is heard during playback; the waveform is fixed once applied.
+
+    :param rate: LFO rate in Hz, up to 20.0. Defaults to 5.0.
+    :param depth: Pitch sweep, from 0.0 to 1.0. At 1.0 and 5 Hz the pitch moves by about a
+        semitone. Defaults to 0.3.
+    :param waveform: Shape of the LFO. Defaults to Waveform.Sine.
+    :param duration: Time in seconds the vibrato runs for. Defaults to None.
+    :param apply_after: Time in seconds after which to start the vibrato. Defaults to None.
+    :param anchor: The `Anchor` apply_after is measured from. Defaults to None.
+    :raises ValueError: If a parameter is out of range. Setting an attribute to an invalid
+        value raises the same errors.
+    """
+    rate: float
+    depth: float
+    waveform: Waveform
+    duration: Optional[float]
+    apply_after: Optional[float]
+    anchor: Optional[Anchor]
+
+    def __init__(self, rate=5.0, depth=0.3, waveform=Waveform.Sine, duration=None, apply_after=None, anchor=None):
+        pass
+
+    @staticmethod
+    def schema() -> Dict[str, Dict[str, Any]]:
+        """
+        The declared parameters keyed by name, as for `FadeIn.schema`.
+        """
+
+    def as_dict(self) -> dict:
+        """
+        The vibrato as a dictionary.
+        """
+
+
+class PythonEffect:
+    """
+    An effect written in Python. The callback receives blocks of interleaved float samples
+    as an ``array.array('f')``, with the sample rate and channel count, and either changes
+    the array in place and returns None, or returns a sequence of the same length.
+
+    Callbacks run on a worker thread shared by all Python effects, which takes the GIL once
+    for all the blocks waiting at the time. Audio through the effect is delayed by two
+    blocks. If a callback raises or returns the wrong number of samples, the error is
+    printed and the block plays unchanged. Python effects are not saved by `as_dict`.
+
+    :param callback: Called as ``callback(block, sample_rate, channels)``.
+    :param block_size: Frames per block, from 16 to 65536. Larger blocks mean fewer calls
+        but more latency. Defaults to 1024.
+    :param apply_after: Time in seconds after which to apply the effect. Defaults to None.
+    :param anchor: The `Anchor` apply_after is measured from. Defaults to None.
+    :raises TypeError: If callback is not callable.
+    :raises ValueError: If block_size is out of range or apply_after is negative. Setting
+        apply_after to a negative value raises the same error.
+
+    Example:
+
+    .. code-block:: python
+
+        import numpy as np
+
+        def soft_clip(block, sample_rate, channels):
+            samples = np.frombuffer(block, dtype=np.float32)
+            samples[:] = np.tanh(samples * 4.0) / 4.0
+
+        sink.apply_effects([PythonEffect(soft_clip, block_size=512)])
+    """
+    callback: Callable[[array.array, int, int], Optional[Sequence[float]]]
+    block_size: int
+    apply_after: Optional[float]
+    anchor: Optional[Anchor]
+
+    def __init__(self, callback, block_size=1024, apply_after=None, anchor=None):
+        pass
+
+    @staticmethod
+    def schema() -> Dict[str, Dict[str, Any]]:
+        """
+        The declared parameters keyed by name, as for `FadeIn.schema`.
+        """
+
+    def as_dict(self) -> dict:
+        """
+        The effect settings as a dictionary, without the callback.
+        """
+
+
+def dumps_preset(effect_list: list, name: Optional[str] = None) -> str:
+    """
+    Serializes an effects chain to a preset JSON string. A preset has a ``version``, an
+    optional ``name`` and an ``effects`` list holding each effect's settings under its
+    ``type``.
+
+    Example:
+
+    .. code-block:: python
+
+        json = dumps_preset([FadeIn(duration=2.0), Reverb(mix=0.4)], name="Hall")
+        # {"version": 1, "name": "Hall", "effects": [{"type": "FadeIn", ...}, ...]}
+
+    :param effect_list: The effects to store.
+    :param name: A name for the preset. Defaults to None.
+    :return: The preset as JSON.
+    :rtype: str
+    :raises ValueError: If a `PythonEffect` or `Automation` is in the list, since their
+        Python functions cannot be stored.
+    """
+
+
+def loads_preset(json: str) -> list:
+    """
+    Creates the effects stored in a preset JSON string.
+
+    :param json: The preset, as written by `dumps_preset`.
+    :return: New effect objects, ready for `AudioSink.apply_effects` or
+        `AudioChannel.set_effects_chain`.
+    :rtype: list
+    :raises ValueError: If the JSON is not a valid preset, its version is newer than this
+        version of rpaudio supports, it contains a `PythonEffect` or `Automation`, or an effect
+        has a parameter its constructor would reject.
+    """
+
+
+def save_preset(effect_list: list, path: str, name: Optional[str] = None) -> None:
+    """
+    Writes an effects chain to a preset file. See `dumps_preset`.
+
+    :raises ValueError: If a `PythonEffect` or `Automation` is in the list.
+    :raises RuntimeError: If the file cannot be written.
+    """
+
+
+def load_preset(path: str) -> list:
+    """
+    Creates the effects stored in a preset file. See `loads_preset`.
+
+    :raises ValueError: If the file is not a valid preset or an effect has a parameter its
+        constructor would reject.
+    :raises RuntimeError: If the file cannot be read.
+    """
@@ -1,8 +1,13 @@
-use crate::timesync::{ActionType, ExtractableEffect};
+use crate::output::OutputConfig;
+use crate::loudness::{ReplayGainMode, ReplayGainSettings};
+use crate::engine::{Detached, Wait};
+use crate::preset;
+use crate::timesync::{ActionType, ExtractableEffect, ScheduledEvent};
 use crate::AudioSink;
 use pyo3::exceptions::PyRuntimeError;
 use pyo3::prelude::*;
 use pyo3::types::{IntoPyDict, PyList};
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use std::{fmt, thread};
@@ -15,11 +20,14 @@ pub struct AudioChannel {
     currently_playing: Arc<Mutex<Option<AudioSink>>>,
     effects_chain: Arc<Mutex<Vec<ActionType>>>,
     channel_volume: Arc<Mutex<f32>>,
+    normalization: Arc<Mutex<Option<f32>>>,
+    replay_gain: Arc<Mutex<Option<ReplayGainSettings>>>,
+    output_config: Arc<Mutex<Option<OutputConfig>>>,
 }
 
 impl fmt::Debug for AudioSink {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "AudioSink {{ is_playing: {:?} }}", self.is_playing)
+        write!(f, "AudioSink {{ is_playing: {:?} }}", self.is_playing())
     }
 }
 
@@ -32,11 +40,11 @@ impl AudioChannel {
         }
     }
 
-    pub fn consume(&mut self) {
-        if let Some(mut sink) = self.pop() {
-            let volume = self.channel_volume.lock().unwrap();
-            let _ = sink.set_volume(*volume);
-            let _ = sink.play();
+    pub fn consume(&mut self, wait: impl Wait) {
+        if let Some(sink) = self.pop() {
+            let volume = *self.channel_volume.lock().unwrap();
+            let _ = sink.set_volume_with(wait, volume);
+            let _ = sink.play_with(wait);
         }
     }
 
@@ -45,6 +53,56 @@ impl AudioChannel {
             *volume_guard = volume;
         }
     }
+
+    pub fn same_channel(&self, other: &AudioChannel) -> bool {
+        Arc::ptr_eq(&self.queue, &other.queue)
+    }
+
+    fn prepare(&self, wait: impl Wait, sink: &AudioSink) {
+        let volume = *self.channel_volume.lock().unwrap();
+        let _ = sink.set_volume_with(wait, volume);
+
+        let effects = self.effects_chain.lock().unwrap().clone();
+        if !effects.is_empty() {
+            if let Err(e) = sink.send_channel_effects(wait, effects) {
+                eprintln!("Failed to send effect: {}", e);
+            }
+        }
+    }
+
+    /// Makes the next queued sink current without starting it, so it can be started together
+    /// with other sinks.
+    pub fn cue(&self, py: Python) -> Option<AudioSink> {
+        let cued = {
+            let mut playing_guard = self.currently_playing.lock().unwrap();
+            if playing_guard.is_some() {
+                return playing_guard.clone();
+            }
+            let mut queue_guard = self.queue.lock().unwrap();
+            if queue_guard.is_empty() {
+                return None;
+            }
+            let sink = queue_guard.remove(0);
+            *playing_guard = Some(sink.clone());
+            sink
+        };
+        // The locks are released first: other Python threads may take them while this one
+        // waits for the audio thread without the GIL.
+        self.prepare(py, &cued);
+        Some(cued)
+    }
+
+    /// The sink `cue` would return, left where it is.
+    pub fn next_audio(&self) -> Option<AudioSink> {
+        self.current_audio()
+            .or_else(|| self.queue.lock().unwrap().first().cloned())
+    }
+
+    fn sinks(&self) -> Vec<AudioSink> {
+        let mut sinks = self.queue.lock().unwrap().clone();
+        sinks.extend(self.current_audio());
+        sinks
+    }
 }
 
 #[pymethods]
@@ -57,6 +115,9 @@ impl AudioChannel {
             currently_playing: Arc::new(Mutex::new(None)),
             effects_chain: Arc::new(Mutex::new(Vec::new())),
             channel_volume: Arc::new(Mutex::new(1.0)),
+            normalization: Arc::new(Mutex::new(None)),
+            replay_gain: Arc::new(Mutex::new(None)),
+            output_config: Arc::new(Mutex::new(None)),
         };
 
         let channel_arc = Arc::new(Mutex::new(channel));
@@ -81,46 +142,23 @@ impl AudioChannel {
                     (channel.currently_playing.lock(), channel.queue.lock())
                 {
                     if playing_guard.is_none() && !queue_guard.is_empty() {
-                        let mut next_sink = queue_guard.remove(0);
-                        let volume = channel.channel_volume.lock().unwrap();
-                        let _ = next_sink.set_volume(*volume);
-
+                        let next_sink = queue_guard.remove(0);
                         drop(queue_guard);
 
-                        if let Err(e) = next_sink.play() {
+                        channel.prepare(Detached, &next_sink);
+                        if let Err(e) = next_sink.play_with(Detached) {
                             eprintln!("Failed to play sink: {}", e);
                             continue;
                         }
 
                         *playing_guard = Some(next_sink);
-
-                        let effects_guard = match channel.effects_chain.lock() {
-                            Ok(guard) => guard,
-                            Err(_) => {
-                                eprintln!("Failed to acquire lock on effects_chain");
-                                continue;
-                            }
-                        };
-
-                        if let Some(sender) = playing_guard.as_mut().unwrap().action_sender.take() {
-                            for effect in effects_guard.iter() {
-                                if let Err(e) = sender.send(effect.clone()) {
-                                    eprintln!("Failed to send effect: {}", e);
-                                }
-                            }
-                        }
                     }
                 }
 
                 if let Ok(mut playing_guard) = channel.currently_playing.lock() {
-                    if let Some(ref mut sink) = *playing_guard {
-                        let is_sink_empty = {
-                            let sink_guard = sink.sink.as_ref().unwrap().lock().unwrap();
-                            sink_guard.empty()
-                        };
-
-                        if !sink.is_playing() && is_sink_empty {
-                            if let Err(e) = sink.stop() {
+                    if let Some(ref sink) = *playing_guard {
+                        if !sink.is_playing() && sink.empty() {
+                            if let Err(e) = sink.stop_with(Detached) {
                                 eprintln!("Failed to stop sink: {}", e);
                             }
                             *playing_guard = None;
@@ -136,10 +174,78 @@ impl AudioChannel {
         x
     }
 
-    pub fn push(&mut self, sink: AudioSink) {
+    pub fn push(&mut self, py: Python, sink: AudioSink) -> PyResult<()> {
+        let normalization = *self.normalization.lock().unwrap();
+        if normalization.is_some() {
+            sink.set_normalization(py, normalization)?;
+        }
+        if let Some(settings) = *self.replay_gain.lock().unwrap() {
+            let (mode, preamp) = (Some(settings.mode), settings.preamp);
+            sink.set_replay_gain(py, mode, preamp, settings.prevent_clipping)?;
+        }
+        if let Some(output_config) = *self.output_config.lock().unwrap() {
+            sink.set_output_config(py, output_config)?;
+        }
+
         if let Ok(mut queue_guard) = self.queue.lock() {
             queue_guard.push(sink);
         }
+        Ok(())
+    }
+
+    #[getter]
+    pub fn normalization(&self) -> Option<f32> {
+        *self.normalization.lock().unwrap()
+    }
+
+    #[setter]
+    pub fn set_normalization(&mut self, py: Python, target_lufs: Option<f32>) -> PyResult<()> {
+        *self.normalization.lock().unwrap() = target_lufs;
+
+        // Measuring releases the GIL, so the queue is not held locked meanwhile.
+        for sink in self.sinks() {
+            sink.set_normalization(py, target_lufs)?;
+        }
+        Ok(())
+    }
+
+    #[getter]
+    pub fn replay_gain(&self) -> Option<ReplayGainMode> {
+        self.replay_gain.lock().unwrap().map(|settings| settings.mode)
+    }
+
+    #[pyo3(signature = (mode=None, preamp=0.0, prevent_clipping=true))]
+    pub fn set_replay_gain(
+        &mut self,
+        py: Python,
+        mode: Option<ReplayGainMode>,
+        preamp: f32,
+        prevent_clipping: bool,
+    ) -> PyResult<()> {
+        *self.replay_gain.lock().unwrap() = mode.map(|mode| ReplayGainSettings {
+            mode,
+            preamp,
+            prevent_clipping,
+        });
+
+        for sink in self.sinks() {
+            sink.set_replay_gain(py, mode, preamp, prevent_clipping)?;
+        }
+        Ok(())
+    }
+
+    #[getter]
+    pub fn output_config(&self) -> Option<OutputConfig> {
+        *self.output_config.lock().unwrap()
+    }
+
+    pub fn set_output_config(&mut self, py: Python, output_config: OutputConfig) -> PyResult<()> {
+        *self.output_config.lock().unwrap() = Some(output_config);
+
+        for sink in self.sinks() {
+            sink.set_output_config(py, output_config)?;
+        }
+        Ok(())
     }
 
     #[setter]
@@ -150,15 +256,22 @@ impl AudioChannel {
     }
 
     #[setter]
-    pub fn  channel_volume(&mut self, volume: f32) {
+    pub fn  channel_volume(&mut self, py: Python, volume: f32) {
         if let Ok(mut volume_guard) = self.channel_volume.lock() {
             *volume_guard = volume;
-            if let Ok(mut currently_playing) = self.currently_playing.lock() {
-                if let Some(ref mut sink) = *currently_playing {
-                    let _ = sink.set_volume(volume);
-                }
-            }
         }
+        if let Some(sink) = self.current_audio() {
+            let _ = sink.set_volume(py, volume);
+        }
+    }
+
+    pub fn start_at(&self, py: Python, timestamp: f64) -> PyResult<ScheduledEvent> {
+        let sink = self
+            .cue(py)
+            .ok_or_else(|| PyRuntimeError::new_err("No audio in the channel to start."))?;
+        let event = sink.play_at(py, timestamp)?;
+        *self.auto_consume.lock().unwrap() = true;
+        Ok(event)
     }
 
     #[getter]
@@ -179,11 +292,14 @@ impl AudioChannel {
         }
     }
 
-    pub fn drop_current_audio(&mut self) {
-        if let Ok(mut currently_playing_guard) = self.currently_playing.lock() {
-            if let Some(mut sink) = currently_playing_guard.take() {
-                let _ = sink.stop();
-            }
+    pub fn drop_current_audio(&mut self, py: Python) {
+        let current = self
+            .currently_playing
+            .lock()
+            .ok()
+            .and_then(|mut currently_playing_guard| currently_playing_guard.take());
+        if let Some(sink) = current {
+            let _ = sink.stop(py);
         }
     }
 
@@ -217,13 +333,7 @@ impl AudioChannel {
 
         let effects_list: Vec<PyObject> = effects_guard
             .iter()
-            .map(|effect| match effect {
-                ActionType::FadeIn(fade_in) => Py::new(py, fade_in.clone()).unwrap().into_py(py),
-                ActionType::FadeOut(fade_out) => Py::new(py, fade_out.clone()).unwrap().into_py(py),
-                ActionType::ChangeSpeed(change_speed) => {
-                    Py::new(py, change_speed.clone()).unwrap().into_py(py)
-                }
-            })
+            .map(|effect| effect.to_object(py))
             .collect();
         let py_list = PyList::new_bound(py, effects_list);
 
@@ -254,23 +364,23 @@ impl AudioChannel {
         })
     }
 
+    pub fn load_effects_preset(&mut self, path: PathBuf) -> PyResult<()> {
+        *self.effects_chain.lock().unwrap() = preset::read(&path)?;
+        Ok(())
+    }
+
     pub fn current_audio_data(&self) -> PyResult<PyObject> {
         Python::with_gil(|py| {
             if let Ok(playing_guard) = self.currently_playing.lock() {
                 if let Some(ref sink) = *playing_guard {
-                    let metadata = sink.metadata.clone();
+                    let metadata = sink.current_metadata();
 
                     let dict = metadata.into_py_dict_bound(py);
 
                     let effects_list = PyList::new_bound(py, &Vec::<PyObject>::new());
 
                     for effect in self.effects_chain.try_lock().unwrap().iter() {
-                        let effect_dict = match effect {
-                            ActionType::FadeIn(fi) => fi.into_py_dict_bound(py),
-                            ActionType::FadeOut(fo) => fo.into_py_dict_bound(py),
-                            ActionType::ChangeSpeed(cs) => cs.into_py_dict_bound(py),
-                        };
-                        effects_list.append(effect_dict)?;
+                        effects_list.append(effect.to_dict(py))?;
                     }
                     dict.set_item("effects", effects_list)?;
                     return Ok(dict.into());
@@ -1,20 +1,23 @@
-use crate::exceptions::EffectConflictException;
+use crate::engine::{
+    self, Callback, EffectStatus, EngineCommand, LoadRequest, ScheduledAction, TrackedEffect,
+    VoiceId, VoiceStatus, Wait,
+};
+use crate::output::{OutputConfig, OutputFormat};
+use crate::loudness::{self, GainSource, LoudnessStats, ReplayGainMode, ReplayGainSettings, SharedGain};
 use crate::timesync::ExtractableEffect;
-use crate::timesync::{ActionType, EffectResult, EffectSync};
+use crate::timesync::{ActionType, ConflictPolicy, EffectHandle, EffectSync, ScheduledEvent};
+use crate::utils::{json_to_py, py_to_json};
 use crate::{exmetadata, MetaData};
-use ::std::sync::mpsc::{Receiver, Sender};
 use pyo3::exceptions::{PyRuntimeError, PyValueError};
 use pyo3::prelude::*;
-use pyo3::types::{IntoPyDict, PyList};
-use rodio::{Decoder, OutputStream, Sink};
-use serde::Serialize;
+use pyo3::types::{IntoPyDict, PyList, PyString};
+use rodio::{Decoder, Source};
+use serde::{Deserialize, Serialize};
 use std::fs::File;
 use std::io::BufReader;
-use std::sync::{mpsc, Arc, Mutex, RwLock};
-use std::thread;
-use std::time::{Duration, Instant};
-
-unsafe impl Send for AudioSink {}
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
 
 #[pyclass]
 #[derive(Serialize)]
@@ -29,213 +32,358 @@ struct AudioInfo {
     volume: f32,
 }
 
-impl AudioSink {
-    pub fn handle_action_and_effects(&mut self, sink: Arc<Mutex<Sink>>) {
-        if let Some(receiver) = &self.action_receiver {
-            if let Ok(action) = receiver.try_recv() {
-                let mut effects_guard = self.effects.lock().unwrap();
-                let effect_sync = Arc::new(EffectSync::new(
-                    action.clone(),
-                    sink.lock().unwrap().get_pos().as_secs_f32(),
-                    Some(self.metadata.duration.unwrap() as f32),
-                ));
-                match action {
-                    ActionType::FadeIn(_fade_in) => {
-                        effects_guard.push(effect_sync);
-                    }
-                    ActionType::FadeOut(_fade_out) => {
-                        effects_guard.push(effect_sync);
-                    }
-                    ActionType::ChangeSpeed(_) => {
-                        effects_guard.push(effect_sync);
-                    }
-                }
-            } else {
-                if self.initial_play {
-                    self.initial_volume(&sink);
-
-                    self.initial_play = false;
-                }
-            }
+const SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct ActiveEffectSnapshot {
+    #[serde(flatten)]
+    effect: EffectSync,
+    #[serde(default)]
+    progress: f32,
+    /// The status the effect's handles watch, reused when the sink restores its own snapshot.
+    #[serde(skip)]
+    status: Option<Arc<EffectStatus>>,
+}
 
-            let mut effects_guard = self.effects.lock().unwrap();
-            let current_position = self.get_pos().unwrap() as f32;
-            effects_guard.retain(|effect| {
-                let keep_effect = match effect.action {
-                    ActionType::FadeIn(_fade_in) => match effect.update(current_position) {
-                        EffectResult::Value(val) => {
-                            let mut lock = self.vol_manipulation_lock.write().unwrap();
-                            sink.lock().unwrap().set_volume(val);
-                            *lock = true;
-                            true
-                        }
-                        EffectResult::Ignored => true,
-                        EffectResult::Completed(val) => {
-                            let mut lock = self.vol_manipulation_lock.write().unwrap();
-                            sink.lock().unwrap().set_volume(val);
-                            *lock = false;
-                            false
-                        }
-                    },
-                    ActionType::FadeOut(_fade_out) => match effect.update(current_position) {
-                        EffectResult::Value(val) => {
-                            let mut lock = self.vol_manipulation_lock.write().unwrap();
-                            sink.lock().unwrap().set_volume(val);
-                            *lock = true;
-                            true
-                        }
-                        EffectResult::Ignored => true,
-                        EffectResult::Completed(val) => {
-                            let mut lock = self.vol_manipulation_lock.write().unwrap();
-                            sink.lock().unwrap().set_volume(val);
-                            *lock = false;
-                            false
-                        }
-                    },
-                    ActionType::ChangeSpeed(_change_speed) => {
-                        match effect.update(current_position) {
-                            EffectResult::Value(val) => {
-                                let mut lock = self.speed_manipulation_lock.write().unwrap();
-                                sink.lock().unwrap().set_speed(val);
-                                *lock = true;
-                                true
-                            }
-                            EffectResult::Ignored => true,
-                            EffectResult::Completed(val) => {
-                                sink.lock().unwrap().set_speed(val);
-                                let mut lock = self.speed_manipulation_lock.write().unwrap();
-                                *lock = false;
-                                false
-                            }
-                        }
-                    }
-                };
-
-                if sink.lock().unwrap().is_paused() && self.resume {
-                    sink.lock().unwrap().play();
-                    self.resume = false;
-                }
-                keep_effect
-            });
-        }
+#[derive(Serialize, Deserialize)]
+struct SinkSnapshot {
+    version: u32,
+    file_path: String,
+    position: f64,
+    volume: f32,
+    speed: f32,
+    is_playing: bool,
+    #[serde(default)]
+    effects_chain: Vec<ActionType>,
+    /// Statuses of the effects chain, as for `ActiveEffectSnapshot::status`.
+    #[serde(skip)]
+    effect_statuses: Vec<Arc<EffectStatus>>,
+    #[serde(default)]
+    active_effects: Vec<ActiveEffectSnapshot>,
+    #[serde(default)]
+    normalization: Option<f32>,
+    #[serde(default)]
+    replay_gain: Option<ReplayGainSettings>,
+    #[serde(default)]
+    output_config: OutputConfig,
+    #[serde(default)]
+    conflict_policy: ConflictPolicy,
+}
+
+impl SinkSnapshot {
+    fn effects(&self) -> impl Iterator<Item = &ActionType> {
+        let active = self.active_effects.iter().map(|active| &active.effect.action);
+        self.effects_chain.iter().chain(active)
     }
-    pub fn initial_volume(&self, sink: &Arc<Mutex<Sink>>) {
-        let has_fade_in = self
-            .effects_chain
-            .iter()
-            .any(|effect| matches!(effect, ActionType::FadeIn(_)));
 
-        let apply_after_values: Vec<Option<f32>> = self
-            .effects_chain
-            .iter()
-            .filter_map(|effect| {
-                if let ActionType::FadeIn(fade_in) = effect {
-                    Some(fade_in.apply_after)
-                } else {
-                    None
-                }
-            })
-            .collect();
-        if has_fade_in {
-            let current_volume = sink.lock().unwrap().volume();
-            if apply_after_values
-                .iter()
-                .all(|val| val.is_none() || *val == Some(0.0))
-            {
-                if current_volume != 0.0 {
-                    sink.lock().unwrap().set_volume(0.0);
-                }
-            } else {
-                if current_volume == 0.0 {
-                    sink.lock().unwrap().set_volume(1.0);
-                }
-            }
-        } else {
-            if let Some(sink) = &self.sink {
-                let current_volume = sink.lock().unwrap().volume();
-                if current_volume == 0.0 {
-                    sink.lock().unwrap().set_volume(1.0);
-                }
-            }
+    /// Rejects effects whose Python callables would be lost when the snapshot is written out.
+    fn check_saveable(&self) -> PyResult<()> {
+        self.effects().try_for_each(|effect| effect.check_saveable("a snapshot"))
+    }
+
+    /// Rejects values the sink's own setters and the effect constructors would reject.
+    fn validate(&self) -> PyResult<()> {
+        if !(0.0..=1.0).contains(&self.volume) {
+            return Err(PyValueError::new_err("Volume must be between 0.0 and 1.0."));
         }
+        self.check_saveable()?;
+        self.effects().try_for_each(ActionType::validate)
     }
 }
 
+/// Handle to a voice playing on the audio thread. Clones share the same voice and
+/// settings, and every control call is a message to the audio thread.
 #[derive(Clone)]
 #[pyclass]
 pub struct AudioSink {
-    pub is_playing: Arc<RwLock<bool>>,
-    callback: Arc<Option<Py<PyAny>>>,
-    cancel_callback: Arc<RwLock<bool>>,
-    pub sink: Option<Arc<Mutex<Sink>>>,
-    stream: Option<Arc<OutputStream>>,
-    pub metadata: MetaData,
-    volume: f32,
-    start_time: Option<Instant>,
-    position: Duration,
-    pub action_sender: Option<Sender<ActionType>>,
-    pub action_receiver: Option<Arc<Receiver<ActionType>>>,
-    initial_play: bool,
-    effects: Arc<Mutex<Vec<Arc<EffectSync>>>>,
-    effects_chain: Vec<ActionType>,
-    resume: bool,
-    vol_manipulation_lock: Arc<RwLock<bool>>,
-    speed_manipulation_lock: Arc<RwLock<bool>>,
+    id: VoiceId,
+    status: Arc<VoiceStatus>,
+    callback: Callback,
+    cancel_callback: Arc<AtomicBool>,
+    metadata: Arc<RwLock<MetaData>>,
+    file_path: Arc<RwLock<Option<String>>>,
+    volume: Arc<RwLock<f32>>,
+    speed: Arc<RwLock<f32>>,
+    effects_chain: Arc<Mutex<Vec<ActionType>>>,
+    effect_statuses: Arc<Mutex<Vec<Arc<EffectStatus>>>>,
+    effects_sent: Arc<AtomicBool>,
+    gain: SharedGain,
+    normalization: Arc<RwLock<Option<f32>>>,
+    replay_gain: Arc<RwLock<Option<ReplayGainSettings>>>,
+    output_config: Arc<RwLock<OutputConfig>>,
+    conflict_policy: Arc<RwLock<ConflictPolicy>>,
 }
 
 impl AudioSink {
-    fn invoke_callback(callback: &Option<Py<PyAny>>) {
-        Python::with_gil(|py| {
-            if let Some(callback) = callback {
-                if let Err(e) = callback.call0(py) {
-                    eprintln!("Failed to invoke callback: {}", e);
-                }
-            }
-        });
+    pub fn voice_id(&self) -> VoiceId {
+        self.id
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        self.status.loaded()
+    }
+
+    /// Records a speed set on the audio thread, which snapshots save instead of the speed an
+    /// effect may be driving.
+    pub fn remember_speed(&self, speed: f32) {
+        *self.speed.write().unwrap() = speed;
+    }
+
+    pub fn current_metadata(&self) -> MetaData {
+        self.metadata.read().unwrap().clone()
+    }
+
+    fn ensure_loaded(&self, message: &'static str) -> PyResult<()> {
+        if self.status.loaded() {
+            Ok(())
+        } else {
+            Err(PyRuntimeError::new_err(message))
+        }
+    }
+
+    /// Sets the effects chain, tracking each effect for the handles `apply_effects` returns.
+    fn set_chain(&self, effects: Vec<ActionType>) -> Vec<Arc<EffectStatus>> {
+        let statuses = effects.iter().map(|_| EffectStatus::new()).collect();
+        self.keep_chain(effects, statuses)
+    }
+
+    /// Sets the effects chain with statuses its handles may already hold.
+    fn keep_chain(
+        &self,
+        effects: Vec<ActionType>,
+        statuses: Vec<Arc<EffectStatus>>,
+    ) -> Vec<Arc<EffectStatus>> {
+        *self.effects_chain.lock().unwrap() = effects;
+        *self.effect_statuses.lock().unwrap() = statuses.clone();
+        statuses
+    }
+
+    fn send_chain(&self, py: Python) -> PyResult<()> {
+        let effects = self.effects_chain.lock().unwrap().clone();
+        let statuses = self.effect_statuses.lock().unwrap().clone();
+        self.send_effects(py, effects.into_iter().zip(statuses).collect())
+    }
+
+    fn send_effects(
+        &self,
+        wait: impl Wait,
+        effects: Vec<(ActionType, Arc<EffectStatus>)>,
+    ) -> PyResult<()> {
+        let id = self.id;
+        let duration = self.metadata.read().unwrap().duration.map(|d| d as f32);
+        engine::request(wait, |reply| EngineCommand::PushEffects {
+            id,
+            effects,
+            duration,
+            reply,
+        })
+    }
+
+    /// Sends a channel's effects chain unless effects were already applied to this sink.
+    pub fn send_channel_effects(&self, wait: impl Wait, effects: Vec<ActionType>) -> PyResult<()> {
+        if self.effects_sent.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+        self.send_effects(
+            wait,
+            effects
+                .into_iter()
+                .map(|effect| (effect, EffectStatus::new()))
+                .collect(),
+        )
+    }
+
+    fn active_effects(&self, py: Python) -> PyResult<Vec<TrackedEffect>> {
+        let id = self.id;
+        engine::request(py, |reply| EngineCommand::Effects { id, reply })
+    }
+
+    fn measure_loudness(&self, py: Python) -> PyResult<LoudnessStats> {
+        if let Some(stats) = self.metadata.read().unwrap().loudness() {
+            return Ok(stats);
+        }
+
+        let file_path = self
+            .file_path
+            .read()
+            .unwrap()
+            .clone()
+            .ok_or_else(|| PyRuntimeError::new_err("No audio loaded. Load audio first."))?;
+        // Decoding the whole file takes seconds for a long track, so other threads keep running.
+        let stats = py.allow_threads(|| loudness::analyze_file(Path::new(&file_path)))?;
+        self.metadata.write().unwrap().set_loudness(&stats);
+        Ok(stats)
+    }
+
+    fn to_snapshot(&self, py: Python) -> PyResult<SinkSnapshot> {
+        let file_path = match (self.file_path.read().unwrap().clone(), self.status.loaded()) {
+            (Some(file_path), true) => file_path,
+            _ => return Err(PyRuntimeError::new_err("No audio loaded. Load audio first.")),
+        };
+
+        let position = self.status.position();
+        let active_effects = self
+            .active_effects(py)?
+            .into_iter()
+            .map(|(effect, status)| ActiveEffectSnapshot {
+                progress: effect.progress(position as f32),
+                effect,
+                status: Some(status),
+            })
+            .collect();
+
+        Ok(SinkSnapshot {
+            version: SNAPSHOT_VERSION,
+            file_path,
+            position,
+            volume: *self.volume.read().unwrap(),
+            speed: *self.speed.read().unwrap(),
+            is_playing: self.is_playing(),
+            effects_chain: self.effects_chain.lock().unwrap().clone(),
+            effect_statuses: self.effect_statuses.lock().unwrap().clone(),
+            active_effects,
+            normalization: *self.normalization.read().unwrap(),
+            replay_gain: *self.replay_gain.read().unwrap(),
+            output_config: *self.output_config.read().unwrap(),
+            conflict_policy: *self.conflict_policy.read().unwrap(),
+        })
+    }
+
+    /// Loads a snapshot into this sink. Effects taken from the sink itself keep their statuses, so
+    /// the handles `apply_effects` returned still control them; others get new ones.
+    fn restore(&self, py: Python, snapshot: SinkSnapshot) -> PyResult<()> {
+        *self.volume.write().unwrap() = snapshot.volume;
+        *self.normalization.write().unwrap() = snapshot.normalization;
+        *self.replay_gain.write().unwrap() = snapshot.replay_gain;
+        *self.output_config.write().unwrap() = snapshot.output_config;
+        *self.conflict_policy.write().unwrap() = snapshot.conflict_policy;
+        self.set_chain(Vec::new());
+        self.load_audio(py, snapshot.file_path)?;
+
+        self.set_speed(py, snapshot.speed)?;
+        if snapshot.position > 0.0 {
+            self.try_seek(py, snapshot.position as f32)?;
+        }
+
+        if !snapshot.effects_chain.is_empty() {
+            let mut statuses = snapshot.effect_statuses;
+            statuses.resize_with(snapshot.effects_chain.len(), EffectStatus::new);
+            self.keep_chain(snapshot.effects_chain, statuses);
+            self.effects_sent.store(true, Ordering::SeqCst);
+        }
+
+        let id = self.id;
+        let effects = snapshot
+            .active_effects
+            .into_iter()
+            .map(|active| {
+                let status = active.status.unwrap_or_else(EffectStatus::new);
+                (active.effect, active.progress, status)
+            })
+            .collect();
+        engine::request(py, |reply| EngineCommand::RestoreEffects { id, effects, reply })?;
+
+        if snapshot.is_playing {
+            self.play(py)?;
+        }
+        Ok(())
+    }
+
+    /// `play` for callers that choose how to wait for the audio thread.
+    pub fn play_with(&self, wait: impl Wait) -> PyResult<()> {
+        self.ensure_loaded("No sink available to play. Load audio first.")?;
+        let id = self.id;
+        engine::request(wait, |reply| EngineCommand::Play {
+            ids: vec![id],
+            reply,
+        })
+    }
+
+    /// `stop` for callers that choose how to wait for the audio thread.
+    pub fn stop_with(&self, wait: impl Wait) -> PyResult<()> {
+        self.ensure_loaded("No sink available to stop. Load audio first.")?;
+        let id = self.id;
+        engine::request(wait, |reply| EngineCommand::Stop {
+            ids: vec![id],
+            reply,
+        })
+    }
+
+    /// `set_volume` for callers that choose how to wait for the audio thread.
+    pub fn set_volume_with(&self, wait: impl Wait, volume: f32) -> PyResult<()> {
+        if !(0.0..=1.0).contains(&volume) {
+            return Err(PyValueError::new_err("Volume must be between 0.0 and 1.0."));
+        }
+
+        self.ensure_loaded("No sink available to set volume. Load audio first.")?;
+        let id = self.id;
+        engine::request(wait, |reply| EngineCommand::SetVolume { id, volume, reply })?;
+        *self.volume.write().unwrap() = volume;
+        Ok(())
+    }
+
+    fn update_gain(&self, py: Python) -> PyResult<()> {
+        if self.file_path.read().unwrap().is_none() {
+            return Ok(());
+        }
+
+        let replay_gain = *self.replay_gain.read().unwrap();
+        let tagged_gain = replay_gain
+            .and_then(|settings| settings.gain(&self.metadata.read().unwrap()));
+        if let Some(gain) = tagged_gain {
+            self.gain.set(gain);
+            return Ok(());
+        }
+
+        let target = *self.normalization.read().unwrap();
+        let gain = match target {
+            Some(target_lufs) => self.measure_loudness(py)?.normalization_gain(target_lufs),
+            None => 1.0,
+        };
+        self.gain.set(gain);
+        Ok(())
     }
 }
 
 #[pymethods]
 impl AudioSink {
     #[new]
-    #[pyo3(signature = (callback=None))]
-    pub fn new(callback: Option<Py<PyAny>>) -> Self {
-        let (action_sender, action_receiver) = mpsc::channel();
+    #[pyo3(signature = (callback=None, output_config=None))]
+    pub fn new(callback: Option<Py<PyAny>>, output_config: Option<OutputConfig>) -> Self {
         AudioSink {
-            is_playing: Arc::new(RwLock::new(false)),
+            id: engine::next_voice_id(),
+            status: Arc::new(VoiceStatus::default()),
             callback: Arc::new(callback),
-            cancel_callback: Arc::new(RwLock::new(false)),
-            sink: None,
-            stream: None,
-            metadata: MetaData::default(),
-            volume: 1.0,
-            start_time: None,
-            position: Duration::from_secs(0),
-            action_sender: Some(action_sender),
-            action_receiver: Some(Arc::new(action_receiver)),
-            initial_play: true,
-            effects: Arc::new(Mutex::new(Vec::new())),
-            effects_chain: Vec::new(),
-            resume: false,
-            vol_manipulation_lock: Arc::new(RwLock::new(false)),
-            speed_manipulation_lock: Arc::new(RwLock::new(false)),
+            cancel_callback: Arc::new(AtomicBool::new(false)),
+            metadata: Arc::new(RwLock::new(MetaData::default())),
+            file_path: Arc::new(RwLock::new(None)),
+            volume: Arc::new(RwLock::new(1.0)),
+            speed: Arc::new(RwLock::new(1.0)),
+            effects_chain: Arc::new(Mutex::new(Vec::new())),
+            effect_statuses: Arc::new(Mutex::new(Vec::new())),
+            effects_sent: Arc::new(AtomicBool::new(false)),
+            gain: SharedGain::default(),
+            normalization: Arc::new(RwLock::new(None)),
+            replay_gain: Arc::new(RwLock::new(None)),
+            output_config: Arc::new(RwLock::new(output_config.unwrap_or_default())),
+            conflict_policy: Arc::new(RwLock::new(ConflictPolicy::default())),
         }
     }
 
     #[getter]
     pub fn metadata(&self, py: Python) -> PyResult<Py<PyAny>> {
-        Ok(self.metadata.clone().into_py(py))
+        Ok(self.current_metadata().into_py(py))
     }
 
     #[getter]
     pub fn metadata_dict(&self, py: Python) -> PyResult<PyObject> {
-        let metadata = self.metadata.clone();
+        let metadata = self.current_metadata();
         Ok(metadata.into_py_dict_bound(py).into())
     }
 
     #[getter]
     pub fn is_playing(&self) -> bool {
-        *self.is_playing.read().unwrap()
+        self.status.is_playing()
     }
 
     #[getter]
@@ -243,312 +391,342 @@ impl AudioSink {
         (*self.callback).clone()
     }
 
-    pub fn load_audio(&mut self, file_path: String) -> PyResult<Self> {
-        if self.sink.is_some() {
-            return Err(PyRuntimeError::new_err(
-                "Audio is already loaded. Please stop the current audio before loading a new one.",
-            ));
+    pub fn load_audio(&self, py: Python, file_path: String) -> PyResult<Self> {
+        if self.status.loaded() {
+            if !self.status.empty() {
+                return Err(PyRuntimeError::new_err(
+                    "Audio is already loaded. Please stop the current audio before loading a new one.",
+                ));
+            }
+            self.unload(py)?;
         }
 
-        let (new_stream, stream_handle) = OutputStream::try_default().unwrap();
-        let sink = Arc::new(Mutex::new(Sink::try_new(&stream_handle).map_err(|e| {
-            PyRuntimeError::new_err(format!("Failed to create sink: {}", e))
-        })?));
-
-        let file_path_clone = file_path.clone();
-        let file = File::open(file_path_clone).unwrap();
-        let source = Decoder::new(BufReader::new(file))
-            .map_err(|e| format!("Failed to decode audio file: {}", e))
-            .unwrap();
+        let file = File::open(&file_path)
+            .map_err(|e| PyRuntimeError::new_err(format!("Failed to open audio file: {}", e)))?;
+        let source = Decoder::new(BufReader::new(file)).map_err(|e| {
+            PyRuntimeError::new_err(format!("Failed to decode audio file: {}", e))
+        })?;
 
-        self.metadata = exmetadata::extract_metadata(std::path::Path::new(&file_path))
+        *self.metadata.write().unwrap() = exmetadata::extract_metadata(Path::new(&file_path))
             .map_err(|_| PyRuntimeError::new_err("Failed to extract metadata"))?;
-
-        {
-            sink.lock().unwrap().append(source);
-            sink.lock().unwrap().pause();
-        }
-
-        self.stream = Some(Arc::new(new_stream));
-        sink.lock().unwrap().set_volume(0.0);
-        self.sink = Some(sink.clone());
-
-        if let Some(sink) = &self.sink {
-            sink.lock().unwrap().pause();
+        *self.file_path.write().unwrap() = Some(file_path);
+        self.update_gain(py)?;
+
+        let source = GainSource::new(source.convert_samples::<f32>(), self.gain.clone());
+        let id = self.id;
+        let request = LoadRequest {
+            status: self.status.clone(),
+            callback: self.callback.clone(),
+            cancel_callback: self.cancel_callback.clone(),
+            source: Box::new(source),
+            volume: *self.volume.read().unwrap(),
+            policy: *self.conflict_policy.read().unwrap(),
+            output: *self.output_config.read().unwrap(),
+        };
+        engine::request(py, |reply| EngineCommand::Load { id, request, reply })?;
+        *self.speed.write().unwrap() = 1.0;
+
+        let effects_chain = self.effects_chain.lock().unwrap().clone();
+        if effects_chain.is_empty() {
+            self.effects_sent.store(false, Ordering::SeqCst);
+        } else {
+            let effects = effects_chain;
+            engine::request(py, |reply| EngineCommand::SetEffectsChain { id, effects, reply })?;
+            self.effects_sent.store(true, Ordering::SeqCst);
+            self.send_chain(py)?;
         }
-        let self_clone = self.clone();
-
-        let is_playing_clone = Arc::clone(&self.is_playing);
-        let callback = self.callback.clone();
-        let cancel_callback_clone = self.cancel_callback.clone();
-
-        thread::spawn({
-            let sink = Arc::clone(&sink);
-
-            move || {
-                let mut self_clone = self_clone;
-
-                loop {
-                    let sink_guard = sink.lock().unwrap();
 
-                    if sink_guard.empty() {
-                        let mut is_playing_guard = is_playing_clone.write().unwrap();
-                        *is_playing_guard = false;
-
-                        if !*cancel_callback_clone.read().unwrap() {
-                            Self::invoke_callback(&*callback);
-                        }
-                        drop(self_clone);
-                        break;
-                    }
+        Ok(self.clone())
+    }
 
-                    drop(sink_guard);
-                    self_clone.handle_action_and_effects(Arc::clone(&sink));
+    pub fn unload(&self, py: Python) -> PyResult<()> {
+        let id = self.id;
+        engine::request(py, |reply| EngineCommand::Unload { id, reply })?;
 
-                    thread::sleep(Duration::from_millis(3));
-                }
-            }
-        });
+        self.effects_sent.store(false, Ordering::SeqCst);
+        *self.metadata.write().unwrap() = MetaData::default();
+        *self.file_path.write().unwrap() = None;
+        Ok(())
+    }
 
-        Ok(self.clone())
+    pub fn replay(&self, py: Python) -> PyResult<()> {
+        let file_path = self
+            .file_path
+            .read()
+            .unwrap()
+            .clone()
+            .ok_or_else(|| PyRuntimeError::new_err("No audio loaded. Load audio first."))?;
+
+        self.unload(py)?;
+        self.load_audio(py, file_path)?;
+        self.play(py)
     }
 
-    pub fn play(&mut self) -> PyResult<()> {
-        if let Some(sink) = &self.sink {
-            *self.is_playing.write().unwrap() = true;
-            if self.initial_play {
-                sink.lock().unwrap().play();
-            } else {
-                self.resume = true;
-                sink.lock().unwrap().play();
+    pub fn play(&self, py: Python) -> PyResult<()> {
+        self.play_with(py)
+    }
 
-                self.handle_action_and_effects(sink.clone());
-            }
-            Ok(())
-        } else {
-            Err(PyRuntimeError::new_err(
-                "No sink available to play. Load audio first.",
-            ))
-        }
+    pub fn pause(&self, py: Python) -> PyResult<()> {
+        let id = self.id;
+        engine::request(py, |reply| EngineCommand::Pause {
+            ids: vec![id],
+            reply,
+        })
     }
 
-    pub fn pause(&mut self) -> PyResult<()> {
-        if let Some(sink) = &self.sink {
-            *self.is_playing.write().unwrap() = false;
-            sink.lock().unwrap().pause();
-            Ok(())
-        } else {
-            Err(PyRuntimeError::new_err(
-                "No sink available. Load audio first.",
-            ))
-        }
+    pub fn stop(&self, py: Python) -> PyResult<()> {
+        self.stop_with(py)
     }
 
-    pub fn stop(&mut self) -> PyResult<()> {
-        if let Some(sink) = &self.sink {
-            sink.lock().unwrap().stop();
-            *self.is_playing.write().unwrap() = false;
+    pub fn play_at(&self, py: Python, timestamp: f64) -> PyResult<ScheduledEvent> {
+        self.ensure_loaded("No sink available to play. Load audio first.")?;
+        ScheduledEvent::schedule(py, vec![self.id], timestamp, ScheduledAction::Play)
+    }
 
-            Ok(())
-        } else {
-            Err(PyRuntimeError::new_err(
-                "No sink available to stop. Load audio first.",
-            ))
-        }
+    pub fn stop_at(&self, py: Python, timestamp: f64) -> PyResult<ScheduledEvent> {
+        self.ensure_loaded("No sink available to stop. Load audio first.")?;
+        ScheduledEvent::schedule(py, vec![self.id], timestamp, ScheduledAction::Stop)
     }
 
     pub fn get_volume(&self) -> PyResult<f32> {
-        if let Some(sink) = &self.sink {
-            Ok(sink.lock().unwrap().volume())
-        } else {
-            Err(PyRuntimeError::new_err(
-                "No sink available. Load audio first.",
-            ))
-        }
+        self.ensure_loaded("No sink available. Load audio first.")?;
+        Ok(self.status.volume())
     }
 
-    pub fn set_volume(&mut self, volume: f32) -> PyResult<()> {
-        if volume < 0.0 || volume > 1.0 {
-            return Err(PyValueError::new_err("Volume must be between 0.0 and 1.0."));
-        }
-
-        if let Some(sink) = &self.sink {
-            let lock = self.vol_manipulation_lock.read().unwrap();
-
-            if *lock {
-                return Err(EffectConflictException::with_context("Volume"));
-            } else {
-                sink.lock().unwrap().set_volume(volume);
-                self.volume = volume;
-                Ok(())
-            }
-        } else {
-            Err(PyRuntimeError::new_err(
-                "No sink available to set volume. Load audio first.",
-            ))
-        }
+    pub fn set_volume(&self, py: Python, volume: f32) -> PyResult<()> {
+        self.set_volume_with(py, volume)
     }
 
     pub fn get_pos(&self) -> PyResult<f64> {
-        if let Some(sink) = &self.sink {
-            let duration = sink.lock().unwrap().get_pos();
-            let position_seconds = duration.as_secs_f64();
-            Ok((position_seconds * 100.0).round() / 100.0)
-        } else {
-            Err(PyRuntimeError::new_err(
-                "No sink available. Load audio first.",
-            ))
-        }
+        self.ensure_loaded("No sink available. Load audio first.")?;
+        let position_seconds = self.status.position();
+        Ok((position_seconds * 100.0).round() / 100.0)
     }
 
-    pub fn set_duration(&mut self, duration: f32) -> PyResult<()> {
-        let duration = Duration::from_secs_f32(duration);
-        self.metadata.duration = Some(duration.as_secs_f64());
+    pub fn set_duration(&self, duration: f32) -> PyResult<()> {
+        self.metadata.write().unwrap().duration = Some(duration as f64);
         Ok(())
     }
 
     pub fn get_remaining_time(&self) -> PyResult<f64> {
-        if let Some(sink) = &self.sink {
-            let sink_lock = sink.lock().unwrap();
-            let position = sink_lock.get_pos();
-
-            if let Some(duration) = self.metadata.duration {
-                let remaining = duration - position.as_secs_f64();
-                Ok((remaining * 100.0).round() / 100.0)
-            } else {
-                Err(PyRuntimeError::new_err("Audio duration is not available."))
-            }
+        self.ensure_loaded("No sink available. Load audio first.")?;
+        if let Some(duration) = self.metadata.read().unwrap().duration {
+            let remaining = duration - self.status.position();
+            Ok((remaining * 100.0).round() / 100.0)
         } else {
-            Err(PyRuntimeError::new_err(
-                "No sink available. Load audio first.",
-            ))
+            Err(PyRuntimeError::new_err("Audio duration is not available."))
         }
     }
 
     pub fn get_speed(&self) -> f32 {
-        if let Some(sink) = &self.sink {
-            sink.lock().unwrap().speed()
+        if self.status.loaded() {
+            self.status.speed()
         } else {
             1.0
         }
     }
 
-    pub fn set_speed(&mut self, speed: f32) -> PyResult<()> {
+    pub fn set_speed(&self, py: Python, speed: f32) -> PyResult<()> {
         if speed <= 0.0 {
             return Err(PyValueError::new_err("Speed must be greater than 0."));
         }
 
-        if let Some(sink) = &self.sink {
-            let lock = self.speed_manipulation_lock.read().unwrap();
-
-            if *lock {
-                return Err(EffectConflictException::with_context("Speed"));
-            } else {
-                sink.lock().unwrap().set_speed(speed);
-                Ok(())
-            }
-        } else {
-            Err(PyRuntimeError::new_err(
-                "No sink available. Load audio first.",
-            ))
-        }
+        let id = self.id;
+        engine::request(py, |reply| EngineCommand::SetSpeed {
+            ids: vec![id],
+            speed,
+            reply,
+        })?;
+        self.remember_speed(speed);
+        Ok(())
     }
 
-    pub fn try_seek(&mut self, position: f32) -> PyResult<()> {
-        if position <= 0.0 {
+    pub fn try_seek(&self, py: Python, position: f32) -> PyResult<()> {
+        if position < 0.0 {
             return Err(PyValueError::new_err("Position must be non-negative."));
         }
 
-        if let Some(sink) = &self.sink {
-            let duration = Duration::from_secs_f32(position);
-
-            let result = sink.lock().unwrap().try_seek(duration);
-            match result {
-                Ok(_) => {
-                    self.position = Duration::from_secs_f64(self.get_pos().unwrap());
-                    self.start_time = Some(Instant::now());
-                    Ok(())
-                }
-                Err(e) => Err(PyRuntimeError::new_err(format!("Seek failed: {:?}", e))),
-            }
-        } else {
-            Err(PyRuntimeError::new_err(
-                "No audio sink available. Load audio first.",
-            ))
-        }
+        self.ensure_loaded("No audio sink available. Load audio first.")?;
+        let id = self.id;
+        engine::request(py, |reply| EngineCommand::Seek {
+            ids: vec![id],
+            position,
+            reply,
+        })
     }
 
-    pub fn cancel_callback(&mut self) {
-        let mut cancel_guard = self.cancel_callback.write().unwrap();
-        *cancel_guard = true;
+    pub fn cancel_callback(&self) {
+        self.cancel_callback.store(true, Ordering::SeqCst);
     }
 
     pub fn empty(&self) -> bool {
-        if let Some(sink) = &self.sink {
-            sink.lock().unwrap().empty()
-        } else {
-            false
+        self.status.loaded() && self.status.empty()
+    }
+
+    pub fn apply_effects(&self, py: Python, effect_list: Py<PyList>) -> PyResult<Vec<EffectHandle>> {
+        let _effect_list: Vec<Py<PyAny>> = effect_list.extract(py)?;
+        let effects = _effect_list
+            .into_iter()
+            .map(|effect| effect.downcast_bound::<PyAny>(py).unwrap().extract_action())
+            .collect::<PyResult<Vec<ActionType>>>()?;
+
+        let statuses = self.set_chain(effects.clone());
+        let handles = effects
+            .iter()
+            .zip(&statuses)
+            .map(|(effect, status)| EffectHandle::new(self.id, status.clone(), effect.clone()))
+            .collect();
+        if !self.status.loaded() {
+            return Ok(handles);
+        }
+
+        // The voice mutes itself here when a fade-in starts right away.
+        let id = self.id;
+        let chain = effects.clone();
+        engine::request(py, |reply| EngineCommand::SetEffectsChain {
+            id,
+            effects: chain,
+            reply,
+        })?;
+
+        if !self.effects_sent.swap(true, Ordering::SeqCst) {
+            self.send_chain(py)?;
         }
+
+        Ok(handles)
     }
 
-    pub fn apply_effects(&mut self, effect_list: Py<PyList>) -> PyResult<()> {
-        Python::with_gil(|py| -> PyResult<()> {
-            let effects_guard = &mut self.effects_chain;
+    pub fn analyze_loudness(&self, py: Python) -> PyResult<PyObject> {
+        let stats = self.measure_loudness(py)?;
+        Ok(stats.into_py_dict_bound(py).into())
+    }
+
+    #[getter]
+    pub fn normalization(&self) -> Option<f32> {
+        *self.normalization.read().unwrap()
+    }
 
-            let _effect_list: Vec<Py<PyAny>> = effect_list.extract(py)?;
+    #[setter]
+    pub fn set_normalization(&self, py: Python, target_lufs: Option<f32>) -> PyResult<()> {
+        *self.normalization.write().unwrap() = target_lufs;
+        self.update_gain(py)
+    }
 
-            let rust_effect_list: Result<Vec<ActionType>, PyErr> = _effect_list
-                .into_iter()
-                .map(|effect| {
-                    let effect = effect.downcast_bound::<PyAny>(py).unwrap();
-                    effect.extract_action()
-                })
-                .collect();
+    #[getter]
+    pub fn replay_gain(&self) -> Option<ReplayGainMode> {
+        self.replay_gain.read().unwrap().map(|settings| settings.mode)
+    }
 
-            *effects_guard = rust_effect_list?;
+    #[pyo3(signature = (mode=None, preamp=0.0, prevent_clipping=true))]
+    pub fn set_replay_gain(
+        &self,
+        py: Python,
+        mode: Option<ReplayGainMode>,
+        preamp: f32,
+        prevent_clipping: bool,
+    ) -> PyResult<()> {
+        *self.replay_gain.write().unwrap() = mode.map(|mode| ReplayGainSettings {
+            mode,
+            preamp,
+            prevent_clipping,
+        });
+        self.update_gain(py)
+    }
 
-            Ok(())
-        })?;
+    pub fn snapshot(&self, py: Python) -> PyResult<PyObject> {
+        let snapshot = self.to_snapshot(py)?;
+        snapshot.check_saveable()?;
+        let value = serde_json::to_value(snapshot)
+            .map_err(|e| PyRuntimeError::new_err(format!("Failed to serialize snapshot: {}", e)))?;
+        Ok(json_to_py(py, &value).into())
+    }
 
-        // If there is a FadeIn effect, check if it has an apply_after value, if its none or 0.0, but the current volume is not 0.0, set it to 0.0
-        self.initial_volume(&self.sink.clone().unwrap());
+    pub fn snapshot_json(&self, py: Python) -> PyResult<String> {
+        let snapshot = self.to_snapshot(py)?;
+        snapshot.check_saveable()?;
+        serde_json::to_string(&snapshot)
+            .map_err(|e| PyRuntimeError::new_err(format!("Failed to serialize snapshot: {}", e)))
+    }
 
-        if let Some(sender) = self.action_sender.take() {
-            let effects_guard = &self.effects_chain;
+    #[staticmethod]
+    #[pyo3(signature = (snapshot, callback=None))]
+    pub fn from_snapshot(
+        snapshot: &Bound<'_, PyAny>,
+        callback: Option<Py<PyAny>>,
+    ) -> PyResult<AudioSink> {
+        let py = snapshot.py();
+        let snapshot: SinkSnapshot = if let Ok(json) = snapshot.downcast::<PyString>() {
+            serde_json::from_str(&json.extract::<String>()?)
+        } else {
+            serde_json::from_value(py_to_json(snapshot)?)
+        }
+        .map_err(|e| PyValueError::new_err(format!("Invalid snapshot: {}", e)))?;
 
-            for effect in effects_guard.iter() {
-                sender
-                    .send(effect.clone())
-                    .map_err(|_| {
-                        eprintln!("Failed to send effect");
-                    })
-                    .ok();
-            }
+        if snapshot.version > SNAPSHOT_VERSION {
+            return Err(PyValueError::new_err(format!(
+                "Unsupported snapshot version {}",
+                snapshot.version
+            )));
         }
+        snapshot.validate()?;
 
-        Ok(())
+        let audio_sink = AudioSink::new(callback, None);
+        audio_sink.restore(py, snapshot)?;
+        Ok(audio_sink)
+    }
+
+    #[getter]
+    pub fn conflict_policy(&self) -> ConflictPolicy {
+        *self.conflict_policy.read().unwrap()
+    }
+
+    #[setter]
+    pub fn set_conflict_policy(&self, py: Python, policy: ConflictPolicy) -> PyResult<()> {
+        *self.conflict_policy.write().unwrap() = policy;
+        if !self.status.loaded() {
+            return Ok(());
+        }
+        let id = self.id;
+        engine::request(py, |reply| EngineCommand::SetConflictPolicy { id, policy, reply })
     }
 
-    pub fn playback_data(&self) -> PyResult<PyObject> {
-        let self_clone = self.clone();
-        Python::with_gil(|py| {
-            let metadata = self_clone.metadata.clone();
+    #[getter]
+    pub fn output_config(&self) -> OutputConfig {
+        *self.output_config.read().unwrap()
+    }
 
-            let dict = metadata.into_py_dict_bound(py);
+    pub fn set_output_config(&self, py: Python, output_config: OutputConfig) -> PyResult<()> {
+        if !self.status.loaded() || self.status.empty() {
+            *self.output_config.write().unwrap() = output_config;
+            return Ok(());
+        }
 
-            let effects_list = PyList::new_bound(py, &Vec::<PyObject>::new());
+        let mut snapshot = self.to_snapshot(py)?;
+        snapshot.output_config = output_config;
+        self.unload(py)?;
+        self.restore(py, snapshot)
+    }
 
-            for effect in self.effects.lock().unwrap().iter() {
-                let effect_dict = match &effect.action {
-                    ActionType::FadeIn(fi) => fi.into_py_dict_bound(py),
-                    ActionType::FadeOut(fo) => fo.into_py_dict_bound(py),
-                    ActionType::ChangeSpeed(cs) => cs.into_py_dict_bound(py),
-                };
-                effects_list.append(effect_dict)?;
-            }
-            dict.set_item("effects", effects_list)?;
-            dict.set_item("position", self_clone.get_pos().unwrap())?;
-            return Ok(dict.into());
-        })
+    pub fn output_format(&self, py: Python) -> PyResult<PyObject> {
+        let config = *self.output_config.read().unwrap();
+        let format: OutputFormat =
+            engine::request(py, |reply| EngineCommand::OutputFormat { config, reply })?;
+        Ok(format.into_py_dict_bound(py).into())
+    }
+
+    pub fn playback_data(&self, py: Python) -> PyResult<PyObject> {
+        let effects = self.active_effects(py)?;
+        let metadata = self.current_metadata();
+
+        let dict = metadata.into_py_dict_bound(py);
+
+        let effects_list = PyList::new_bound(py, &Vec::<PyObject>::new());
+
+        for (effect, _) in effects.iter() {
+            effects_list.append(effect.action.to_dict(py))?;
+        }
+        dict.set_item("effects", effects_list)?;
+        dict.set_item("position", self.get_pos()?)?;
+        Ok(dict.into())
     }
 }
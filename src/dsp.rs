@@ -0,0 +1,1577 @@
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+use std::f64::consts::PI;
+use std::fmt;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+pub const BUTTERWORTH_Q: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+/// A stateful stage in a voice's sample chain. Processors run on the output callback, so they
+/// must not block or allocate while processing.
+pub trait Processor: Send {
+    /// Processes one interleaved frame in place.
+    fn process(&mut self, frame: &mut [f32]);
+
+    /// Frames the processor keeps sounding after its input falls silent.
+    fn tail(&self) -> usize {
+        0
+    }
+}
+
+/// An effect setting shared by the Python object and the processors of every sink it is applied
+/// to, so a change made through its setter is heard during playback. Clones share the value.
+#[derive(Clone)]
+pub struct Setting(Arc<AtomicU32>);
+
+impl Setting {
+    pub fn new(value: f32) -> Self {
+        Setting(Arc::new(AtomicU32::new(value.to_bits())))
+    }
+
+    #[inline]
+    pub fn get(&self) -> f32 {
+        f32::from_bits(self.0.load(Ordering::Relaxed))
+    }
+
+    pub fn set(&self, value: f32) {
+        self.0.store(value.to_bits(), Ordering::Relaxed);
+    }
+}
+
+impl From<f32> for Setting {
+    fn from(value: f32) -> Self {
+        Setting::new(value)
+    }
+}
+
+impl PartialEq for Setting {
+    fn eq(&self, other: &Self) -> bool {
+        self.get() == other.get()
+    }
+}
+
+impl fmt::Debug for Setting {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.get(), f)
+    }
+}
+
+impl fmt::Display for Setting {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.get(), f)
+    }
+}
+
+impl Serialize for Setting {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.get().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Setting {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        f32::deserialize(deserializer).map(Setting::new)
+    }
+}
+
+impl IntoPy<PyObject> for Setting {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        self.get().into_py(py)
+    }
+}
+
+/// Effect parameters shared between the audio thread, which automates them, and the processor.
+#[derive(Debug)]
+pub struct Params(Vec<Setting>);
+
+impl Params {
+    pub fn new(values: &[f32]) -> Arc<Self> {
+        Arc::new(Params(values.iter().copied().map(Setting::new).collect()))
+    }
+
+    /// Parameters that include settings shared with an effect's Python object.
+    pub fn shared(settings: &[&Setting]) -> Arc<Self> {
+        Arc::new(Params(settings.iter().map(|setting| (*setting).clone()).collect()))
+    }
+
+    #[inline]
+    pub fn get(&self, index: usize) -> f32 {
+        self.0[index].get()
+    }
+
+    pub fn set(&self, index: usize, value: f32) {
+        self.0[index].set(value);
+    }
+}
+
+#[pyclass(eq, eq_int)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum FilterType {
+    #[default]
+    LowPass,
+    HighPass,
+    BandPass,
+    Notch,
+    LowShelf,
+    HighShelf,
+    Peak,
+}
+
+#[pyclass(eq, eq_int)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum DistortionType {
+    #[default]
+    SoftClip,
+    HardClip,
+    Tube,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Coefficients {
+    b0: f64,
+    b1: f64,
+    b2: f64,
+    a1: f64,
+    a2: f64,
+}
+
+impl Coefficients {
+    /// RBJ audio EQ cookbook coefficients. `gain` is in dB and only used by shelves and peaks.
+    fn new(filter_type: FilterType, sample_rate: u32, cutoff: f32, q: f32, gain: f32) -> Self {
+        let w0 = 2.0 * PI * cutoff as f64 / sample_rate as f64;
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin / (2.0 * q as f64);
+        let a = 10f64.powf(gain as f64 / 40.0);
+        let shelf = 2.0 * a.sqrt() * alpha;
+
+        let (b0, b1, b2, a0, a1, a2) = match filter_type {
+            FilterType::LowPass => (
+                (1.0 - cos) / 2.0,
+                1.0 - cos,
+                (1.0 - cos) / 2.0,
+                1.0 + alpha,
+                -2.0 * cos,
+                1.0 - alpha,
+            ),
+            FilterType::HighPass => (
+                (1.0 + cos) / 2.0,
+                -(1.0 + cos),
+                (1.0 + cos) / 2.0,
+                1.0 + alpha,
+                -2.0 * cos,
+                1.0 - alpha,
+            ),
+            FilterType::BandPass => (alpha, 0.0, -alpha, 1.0 + alpha, -2.0 * cos, 1.0 - alpha),
+            FilterType::Notch => (1.0, -2.0 * cos, 1.0, 1.0 + alpha, -2.0 * cos, 1.0 - alpha),
+            FilterType::Peak => (
+                1.0 + alpha * a,
+                -2.0 * cos,
+                1.0 - alpha * a,
+                1.0 + alpha / a,
+                -2.0 * cos,
+                1.0 - alpha / a,
+            ),
+            FilterType::LowShelf => (
+                a * ((a + 1.0) - (a - 1.0) * cos + shelf),
+                2.0 * a * ((a - 1.0) - (a + 1.0) * cos),
+                a * ((a + 1.0) - (a - 1.0) * cos - shelf),
+                (a + 1.0) + (a - 1.0) * cos + shelf,
+                -2.0 * ((a - 1.0) + (a + 1.0) * cos),
+                (a + 1.0) + (a - 1.0) * cos - shelf,
+            ),
+            FilterType::HighShelf => (
+                a * ((a + 1.0) + (a - 1.0) * cos + shelf),
+                -2.0 * a * ((a - 1.0) + (a + 1.0) * cos),
+                a * ((a + 1.0) + (a - 1.0) * cos - shelf),
+                (a + 1.0) - (a - 1.0) * cos + shelf,
+                2.0 * ((a - 1.0) - (a + 1.0) * cos),
+                (a + 1.0) - (a - 1.0) * cos - shelf,
+            ),
+        };
+
+        Coefficients {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+        }
+    }
+}
+
+/// A second order IIR filter with independent state per channel.
+#[derive(Clone, Debug)]
+pub struct Biquad {
+    /// None for a filter built from fixed coefficients.
+    filter_type: Option<FilterType>,
+    sample_rate: u32,
+    settings: (f32, f32, f32),
+    coefficients: Option<Coefficients>,
+    state: Vec<[f64; 2]>,
+}
+
+impl Biquad {
+    pub fn new(filter_type: FilterType, sample_rate: u32, channels: usize) -> Self {
+        Biquad {
+            filter_type: Some(filter_type),
+            sample_rate,
+            settings: (f32::NAN, f32::NAN, f32::NAN),
+            coefficients: None,
+            state: vec![[0.0; 2]; channels],
+        }
+    }
+
+    /// A filter with the given coefficients, normalized so that a0 is 1. `set` leaves it as is.
+    pub fn from_coefficients(b: [f64; 3], a: [f64; 2], channels: usize) -> Self {
+        Biquad {
+            filter_type: None,
+            sample_rate: 0,
+            settings: (f32::NAN, f32::NAN, f32::NAN),
+            coefficients: Some(Coefficients {
+                b0: b[0],
+                b1: b[1],
+                b2: b[2],
+                a1: a[0],
+                a2: a[1],
+            }),
+            state: vec![[0.0; 2]; channels],
+        }
+    }
+
+    /// Updates cutoff (Hz), Q and gain (dB). Cutoffs outside the audible band of the output
+    /// bypass the filter.
+    pub fn set(&mut self, cutoff: f32, q: f32, gain: f32) {
+        let Some(filter_type) = self.filter_type else {
+            return;
+        };
+        if self.settings == (cutoff, q, gain) {
+            return;
+        }
+        self.settings = (cutoff, q, gain);
+
+        let nyquist = self.sample_rate as f32 / 2.0;
+        self.coefficients = if cutoff > 0.0 && cutoff < nyquist && q > 0.0 {
+            Some(Coefficients::new(
+                filter_type,
+                self.sample_rate,
+                cutoff,
+                q,
+                gain,
+            ))
+        } else {
+            None
+        };
+    }
+
+    #[inline]
+    pub fn process_sample(&mut self, channel: usize, sample: f32) -> f32 {
+        self.process(channel, sample as f64) as f32
+    }
+
+    /// Filters one sample of `channel` at full precision.
+    #[inline]
+    pub fn process(&mut self, channel: usize, x: f64) -> f64 {
+        let Some(c) = self.coefficients else {
+            return x;
+        };
+        let state = &mut self.state[channel];
+        let y = c.b0 * x + state[0];
+        state[0] = c.b1 * x - c.a1 * y + state[1];
+        state[1] = c.b2 * x - c.a2 * y;
+        y
+    }
+}
+
+pub mod filter {
+    pub const ENABLED: usize = 0;
+    pub const CUTOFF: usize = 1;
+    pub const Q: usize = 2;
+    pub const GAIN: usize = 3;
+}
+
+pub struct FilterProcessor {
+    params: Arc<Params>,
+    biquad: Biquad,
+}
+
+impl FilterProcessor {
+    pub fn new(filter_type: FilterType, params: Arc<Params>, sample_rate: u32, channels: usize) -> Self {
+        FilterProcessor {
+            params,
+            biquad: Biquad::new(filter_type, sample_rate, channels),
+        }
+    }
+}
+
+impl Processor for FilterProcessor {
+    fn process(&mut self, frame: &mut [f32]) {
+        if self.params.get(filter::ENABLED) == 0.0 {
+            return;
+        }
+        self.biquad.set(
+            self.params.get(filter::CUTOFF),
+            self.params.get(filter::Q),
+            self.params.get(filter::GAIN),
+        );
+        for (channel, sample) in frame.iter_mut().enumerate() {
+            *sample = self.biquad.process_sample(channel, *sample);
+        }
+    }
+}
+
+pub mod eq {
+    pub const ENABLED: usize = 0;
+
+    pub const FREQUENCY: usize = 0;
+    pub const Q: usize = 1;
+    pub const GAIN: usize = 2;
+    pub const STRIDE: usize = 3;
+}
+
+/// Frames between coefficient updates while an equalizer band glides to new settings.
+const EQ_CONTROL_FRAMES: u32 = 32;
+/// Time constant of the glide, in seconds.
+const EQ_GLIDE_SECONDS: f32 = 0.02;
+
+/// A chain of biquads whose settings live in `bands`, shared with the Python object so they can
+/// be changed during playback. Changes glide over a few milliseconds to avoid zipper noise.
+pub struct EqProcessor {
+    bands: Arc<Params>,
+    enabled: Arc<Params>,
+    filters: Vec<Biquad>,
+    current: Vec<[f32; 3]>,
+    glide: f32,
+    countdown: u32,
+}
+
+impl EqProcessor {
+    pub fn new(
+        band_types: &[FilterType],
+        bands: Arc<Params>,
+        enabled: Arc<Params>,
+        sample_rate: u32,
+        channels: usize,
+    ) -> Self {
+        let current = (0..band_types.len())
+            .map(|band| {
+                let base = band * eq::STRIDE;
+                [
+                    bands.get(base + eq::FREQUENCY),
+                    bands.get(base + eq::Q),
+                    bands.get(base + eq::GAIN),
+                ]
+            })
+            .collect();
+        let filters = band_types
+            .iter()
+            .map(|filter_type| Biquad::new(*filter_type, sample_rate, channels))
+            .collect();
+        let glide = 1.0 - (-(EQ_CONTROL_FRAMES as f32) / (EQ_GLIDE_SECONDS * sample_rate as f32)).exp();
+
+        EqProcessor {
+            bands,
+            enabled,
+            filters,
+            current,
+            glide,
+            countdown: 0,
+        }
+    }
+
+    fn update(&mut self) {
+        for (band, (current, filter)) in self.current.iter_mut().zip(&mut self.filters).enumerate() {
+            let base = band * eq::STRIDE;
+            let frequency = self.bands.get(base + eq::FREQUENCY);
+            let q = self.bands.get(base + eq::Q);
+            let gain = self.bands.get(base + eq::GAIN);
+
+            // Frequency and Q glide geometrically, gain linearly in dB.
+            current[0] = glide_towards(current[0], frequency, self.glide, true);
+            current[1] = glide_towards(current[1], q, self.glide, true);
+            current[2] = glide_towards(current[2], gain, self.glide, false);
+            filter.set(current[0], current[1], current[2]);
+        }
+    }
+}
+
+fn glide_towards(current: f32, target: f32, amount: f32, geometric: bool) -> f32 {
+    let next = if geometric && current > 0.0 && target > 0.0 {
+        current * (target / current).powf(amount)
+    } else {
+        current + (target - current) * amount
+    };
+    if (next - target).abs() <= target.abs().max(1.0) * 1e-3 {
+        target
+    } else {
+        next
+    }
+}
+
+impl Processor for EqProcessor {
+    fn process(&mut self, frame: &mut [f32]) {
+        if self.enabled.get(eq::ENABLED) == 0.0 {
+            return;
+        }
+        if self.countdown == 0 {
+            self.update();
+            self.countdown = EQ_CONTROL_FRAMES;
+        }
+        self.countdown -= 1;
+
+        for filter in &mut self.filters {
+            for (channel, sample) in frame.iter_mut().enumerate() {
+                *sample = filter.process_sample(channel, *sample);
+            }
+        }
+    }
+}
+
+pub mod reverb {
+    pub const ENABLED: usize = 0;
+    pub const ROOM_SIZE: usize = 1;
+    pub const DAMPING: usize = 2;
+    pub const MIX: usize = 3;
+}
+
+// Freeverb tunings, in frames at 44.1 kHz.
+const COMB_TUNING: [usize; 8] = [1116, 1188, 1277, 1356, 1422, 1491, 1557, 1617];
+const ALLPASS_TUNING: [usize; 4] = [556, 441, 341, 225];
+const STEREO_SPREAD: usize = 23;
+const REVERB_INPUT_GAIN: f32 = 0.015;
+const REVERB_WET_SCALE: f32 = 3.0;
+
+struct Comb {
+    buffer: Vec<f32>,
+    index: usize,
+    store: f32,
+}
+
+impl Comb {
+    fn new(len: usize) -> Self {
+        Comb {
+            buffer: vec![0.0; len.max(1)],
+            index: 0,
+            store: 0.0,
+        }
+    }
+
+    #[inline]
+    fn process(&mut self, input: f32, feedback: f32, damping: f32) -> f32 {
+        let output = self.buffer[self.index];
+        self.store = output * (1.0 - damping) + self.store * damping;
+        self.buffer[self.index] = input + self.store * feedback;
+        self.index = (self.index + 1) % self.buffer.len();
+        output
+    }
+}
+
+struct Allpass {
+    buffer: Vec<f32>,
+    index: usize,
+}
+
+impl Allpass {
+    fn new(len: usize) -> Self {
+        Allpass {
+            buffer: vec![0.0; len.max(1)],
+            index: 0,
+        }
+    }
+
+    #[inline]
+    fn process(&mut self, input: f32) -> f32 {
+        let delayed = self.buffer[self.index];
+        self.buffer[self.index] = input + delayed * 0.5;
+        self.index = (self.index + 1) % self.buffer.len();
+        delayed - input
+    }
+}
+
+/// A Schroeder-Moorer reverb in the style of Freeverb, with a comb and allpass bank per channel
+/// fed from a pre-delayed mono sum of the input.
+pub struct ReverbProcessor {
+    params: Arc<Params>,
+    pre_delay: Vec<f32>,
+    pre_delay_index: usize,
+    combs: Vec<Vec<Comb>>,
+    allpasses: Vec<Vec<Allpass>>,
+    longest_comb: usize,
+    allpass_frames: usize,
+}
+
+impl ReverbProcessor {
+    pub fn new(params: Arc<Params>, pre_delay: f32, sample_rate: u32, channels: usize) -> Self {
+        let scale = |frames: usize| frames * sample_rate as usize / 44_100;
+        let combs = (0..channels)
+            .map(|channel| {
+                COMB_TUNING
+                    .iter()
+                    .map(|frames| Comb::new(scale(frames + channel * STEREO_SPREAD)))
+                    .collect()
+            })
+            .collect();
+        let allpasses = (0..channels)
+            .map(|channel| {
+                ALLPASS_TUNING
+                    .iter()
+                    .map(|frames| Allpass::new(scale(frames + channel * STEREO_SPREAD)))
+                    .collect()
+            })
+            .collect();
+        let pre_delay_frames = (pre_delay.max(0.0) * sample_rate as f32) as usize;
+
+        ReverbProcessor {
+            params,
+            pre_delay: vec![0.0; pre_delay_frames + 1],
+            pre_delay_index: 0,
+            combs,
+            allpasses,
+            longest_comb: scale(COMB_TUNING[7] + (channels - 1) * STEREO_SPREAD),
+            allpass_frames: scale(ALLPASS_TUNING.iter().sum()),
+        }
+    }
+
+    fn feedback(&self) -> f32 {
+        0.7 + 0.28 * self.params.get(reverb::ROOM_SIZE).clamp(0.0, 1.0)
+    }
+}
+
+impl Processor for ReverbProcessor {
+    fn process(&mut self, frame: &mut [f32]) {
+        if self.params.get(reverb::ENABLED) == 0.0 {
+            return;
+        }
+        let feedback = self.feedback();
+        let damping = 0.4 * self.params.get(reverb::DAMPING).clamp(0.0, 1.0);
+        let mix = self.params.get(reverb::MIX).clamp(0.0, 1.0);
+
+        let input = frame.iter().sum::<f32>() * REVERB_INPUT_GAIN;
+        self.pre_delay[self.pre_delay_index] = input;
+        self.pre_delay_index = (self.pre_delay_index + 1) % self.pre_delay.len();
+        let input = self.pre_delay[self.pre_delay_index];
+
+        for (channel, sample) in frame.iter_mut().enumerate() {
+            let mut wet: f32 = self.combs[channel]
+                .iter_mut()
+                .map(|comb| comb.process(input, feedback, damping))
+                .sum();
+            for allpass in self.allpasses[channel].iter_mut() {
+                wet = allpass.process(wet);
+            }
+            *sample = *sample * (1.0 - mix) + wet * mix * REVERB_WET_SCALE;
+        }
+    }
+
+    fn tail(&self) -> usize {
+        if self.params.get(reverb::ENABLED) == 0.0 {
+            return 0;
+        }
+        // Time for the longest comb to decay by 60 dB.
+        let loops = (0.001f32).ln() / self.feedback().ln();
+        self.pre_delay.len() + (loops * self.longest_comb as f32) as usize + self.allpass_frames
+    }
+}
+
+pub mod delay {
+    pub const ENABLED: usize = 0;
+    pub const FEEDBACK: usize = 1;
+    pub const MIX: usize = 2;
+}
+
+/// A feedback delay line per channel. In ping-pong mode a stereo pair bounces the echoes
+/// between left and right.
+pub struct DelayProcessor {
+    params: Arc<Params>,
+    buffers: Vec<Vec<f32>>,
+    index: usize,
+    ping_pong: bool,
+}
+
+impl DelayProcessor {
+    pub fn new(
+        params: Arc<Params>,
+        delay: f32,
+        ping_pong: bool,
+        sample_rate: u32,
+        channels: usize,
+    ) -> Self {
+        let frames = ((delay * sample_rate as f32) as usize).max(1);
+        DelayProcessor {
+            params,
+            buffers: vec![vec![0.0; frames]; channels],
+            index: 0,
+            ping_pong: ping_pong && channels == 2,
+        }
+    }
+}
+
+impl Processor for DelayProcessor {
+    fn process(&mut self, frame: &mut [f32]) {
+        if self.params.get(delay::ENABLED) == 0.0 {
+            return;
+        }
+        let feedback = self.params.get(delay::FEEDBACK);
+        let mix = self.params.get(delay::MIX);
+        let index = self.index;
+
+        if self.ping_pong {
+            let left = self.buffers[0][index];
+            let right = self.buffers[1][index];
+            self.buffers[0][index] = (frame[0] + frame[1]) * 0.5 + right * feedback;
+            self.buffers[1][index] = left * feedback;
+            frame[0] = frame[0] * (1.0 - mix) + left * mix;
+            frame[1] = frame[1] * (1.0 - mix) + right * mix;
+        } else {
+            for (sample, buffer) in frame.iter_mut().zip(&mut self.buffers) {
+                let delayed = buffer[index];
+                buffer[index] = *sample + delayed * feedback;
+                *sample = *sample * (1.0 - mix) + delayed * mix;
+            }
+        }
+        self.index = (index + 1) % self.buffers[0].len();
+    }
+
+    fn tail(&self) -> usize {
+        if self.params.get(delay::ENABLED) == 0.0 {
+            return 0;
+        }
+        // Echoes until they have decayed by 60 dB.
+        let feedback = self.params.get(delay::FEEDBACK);
+        let echoes = if feedback > 0.0 {
+            ((0.001f32).ln() / feedback.ln()).ceil() as usize
+        } else {
+            0
+        };
+        (echoes + 1 + self.ping_pong as usize) * self.buffers[0].len()
+    }
+}
+
+pub mod dynamics {
+    pub const ENABLED: usize = 0;
+    pub const THRESHOLD: usize = 1;
+    pub const RATIO: usize = 2;
+    pub const ATTACK: usize = 3;
+    pub const RELEASE: usize = 4;
+    pub const KNEE: usize = 5;
+    pub const MAKEUP: usize = 6;
+    pub const RANGE: usize = 7;
+}
+
+/// Peak level decay of the gate's detector, in seconds.
+const GATE_DETECTOR_SECONDS: f32 = 0.01;
+
+/// Gain reduction in dB last reported by a dynamics processor. Shared with the Python effect.
+#[derive(Clone, Debug)]
+pub struct Meter(Arc<AtomicU32>);
+
+impl Meter {
+    pub fn get(&self) -> f32 {
+        f32::from_bits(self.0.load(Ordering::Relaxed))
+    }
+
+    fn set(&self, value: f32) {
+        self.0.store(value.to_bits(), Ordering::Relaxed);
+    }
+}
+
+impl Default for Meter {
+    fn default() -> Self {
+        Meter(Arc::new(AtomicU32::new(0.0f32.to_bits())))
+    }
+}
+
+impl PartialEq for Meter {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DynamicsMode {
+    Compressor,
+    Limiter,
+    Gate,
+}
+
+fn time_coefficient(seconds: f32, sample_rate: u32) -> f32 {
+    if seconds <= 0.0 {
+        0.0
+    } else {
+        (-1.0 / (seconds * sample_rate as f32)).exp()
+    }
+}
+
+/// Compressor, limiter and gate sharing one stereo linked, log domain gain computer.
+pub struct DynamicsProcessor {
+    mode: DynamicsMode,
+    params: Arc<Params>,
+    meter: Meter,
+    sample_rate: u32,
+    times: (f32, f32),
+    attack: f32,
+    release: f32,
+    detector: f32,
+    detector_decay: f32,
+    reduction: f32,
+}
+
+impl DynamicsProcessor {
+    pub fn new(mode: DynamicsMode, params: Arc<Params>, meter: Meter, sample_rate: u32) -> Self {
+        DynamicsProcessor {
+            mode,
+            params,
+            meter,
+            sample_rate,
+            times: (f32::NAN, f32::NAN),
+            attack: 0.0,
+            release: 0.0,
+            detector: 0.0,
+            detector_decay: time_coefficient(GATE_DETECTOR_SECONDS, sample_rate),
+            reduction: 0.0,
+        }
+    }
+
+    /// Gain reduction in dB the gain computer asks for at `level` dB.
+    fn target(&self, level: f32) -> f32 {
+        let threshold = self.params.get(dynamics::THRESHOLD);
+        match self.mode {
+            DynamicsMode::Gate => {
+                if level < threshold {
+                    self.params.get(dynamics::RANGE)
+                } else {
+                    0.0
+                }
+            }
+            DynamicsMode::Limiter => (level - threshold).max(0.0),
+            DynamicsMode::Compressor => {
+                let slope = 1.0 - 1.0 / self.params.get(dynamics::RATIO);
+                let knee = self.params.get(dynamics::KNEE);
+                let over = level - threshold;
+                if 2.0 * over <= -knee {
+                    0.0
+                } else if 2.0 * over.abs() <= knee {
+                    slope * (over + knee / 2.0).powi(2) / (2.0 * knee)
+                } else {
+                    slope * over
+                }
+            }
+        }
+    }
+}
+
+impl Processor for DynamicsProcessor {
+    fn process(&mut self, frame: &mut [f32]) {
+        if self.params.get(dynamics::ENABLED) == 0.0 {
+            return;
+        }
+        let times = (
+            self.params.get(dynamics::ATTACK),
+            self.params.get(dynamics::RELEASE),
+        );
+        if times != self.times {
+            self.times = times;
+            self.attack = time_coefficient(times.0, self.sample_rate);
+            self.release = time_coefficient(times.1, self.sample_rate);
+        }
+
+        let peak = frame.iter().fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+        let peak = if self.mode == DynamicsMode::Gate {
+            self.detector = peak.max(self.detector * self.detector_decay);
+            self.detector
+        } else {
+            peak
+        };
+        let target = self.target(20.0 * peak.max(1e-9).log10());
+
+        // Compressors attack as reduction rises, gates as it falls and the gate opens.
+        let rising = target > self.reduction;
+        let coefficient = if rising == (self.mode == DynamicsMode::Gate) {
+            self.release
+        } else {
+            self.attack
+        };
+        self.reduction = target + (self.reduction - target) * coefficient;
+        self.meter.set(self.reduction);
+
+        let makeup = if self.mode == DynamicsMode::Compressor {
+            self.params.get(dynamics::MAKEUP)
+        } else {
+            0.0
+        };
+        let gain = 10f32.powf((makeup - self.reduction) / 20.0);
+        for sample in frame.iter_mut() {
+            *sample *= gain;
+        }
+    }
+}
+
+pub mod distortion {
+    pub const ENABLED: usize = 0;
+    pub const AMOUNT: usize = 1;
+    pub const DRIVE: usize = 2;
+}
+
+/// Bias of the tube shaper, which makes it asymmetric and adds even harmonics.
+const TUBE_BIAS: f32 = 0.3;
+/// Pole of the DC blocker after the tube shaper.
+const DC_BLOCKER_POLE: f32 = 0.995;
+
+/// A waveshaper driven by `distortion::DRIVE` dB, blended with the dry signal by `AMOUNT`.
+pub struct DistortionProcessor {
+    distortion_type: DistortionType,
+    params: Arc<Params>,
+    dc: Vec<(f32, f32)>,
+}
+
+impl DistortionProcessor {
+    pub fn new(distortion_type: DistortionType, params: Arc<Params>, channels: usize) -> Self {
+        DistortionProcessor {
+            distortion_type,
+            params,
+            dc: vec![(0.0, 0.0); channels],
+        }
+    }
+}
+
+impl Processor for DistortionProcessor {
+    fn process(&mut self, frame: &mut [f32]) {
+        if self.params.get(distortion::ENABLED) == 0.0 {
+            return;
+        }
+        let amount = self.params.get(distortion::AMOUNT).clamp(0.0, 1.0);
+        let drive = 10f32.powf(self.params.get(distortion::DRIVE) / 20.0);
+
+        for (sample, dc) in frame.iter_mut().zip(self.dc.iter_mut()) {
+            let driven = *sample * drive;
+            let shaped = match self.distortion_type {
+                DistortionType::SoftClip => driven.tanh(),
+                DistortionType::HardClip => driven.clamp(-1.0, 1.0),
+                DistortionType::Tube => {
+                    let shaped = (driven + TUBE_BIAS).tanh() - TUBE_BIAS.tanh();
+                    let (last_in, last_out) = *dc;
+                    let blocked = shaped - last_in + DC_BLOCKER_POLE * last_out;
+                    *dc = (shaped, blocked);
+                    blocked
+                }
+            };
+            *sample += (shaped - *sample) * amount;
+        }
+    }
+}
+
+pub mod bitcrusher {
+    pub const ENABLED: usize = 0;
+    pub const AMOUNT: usize = 1;
+    pub const BITS: usize = 2;
+    pub const SAMPLE_RATE: usize = 3;
+}
+
+/// Reduces bit depth and holds samples to imitate a lower sample rate, blended with the dry
+/// signal by `bitcrusher::AMOUNT`.
+pub struct BitcrusherProcessor {
+    params: Arc<Params>,
+    sample_rate: u32,
+    phase: f32,
+    held: Vec<f32>,
+}
+
+impl BitcrusherProcessor {
+    pub fn new(params: Arc<Params>, sample_rate: u32, channels: usize) -> Self {
+        BitcrusherProcessor {
+            params,
+            sample_rate,
+            phase: 1.0,
+            held: vec![0.0; channels],
+        }
+    }
+}
+
+impl Processor for BitcrusherProcessor {
+    fn process(&mut self, frame: &mut [f32]) {
+        if self.params.get(bitcrusher::ENABLED) == 0.0 {
+            return;
+        }
+        let amount = self.params.get(bitcrusher::AMOUNT).clamp(0.0, 1.0);
+        let levels = 2f32.powf(self.params.get(bitcrusher::BITS) - 1.0);
+
+        self.phase += self.params.get(bitcrusher::SAMPLE_RATE) / self.sample_rate as f32;
+        if self.phase >= 1.0 {
+            self.phase = self.phase.fract();
+            for (held, sample) in self.held.iter_mut().zip(frame.iter()) {
+                *held = (*sample * levels).round() / levels;
+            }
+        }
+        for (sample, held) in frame.iter_mut().zip(self.held.iter()) {
+            *sample += (held - *sample) * amount;
+        }
+    }
+}
+
+#[pyclass(eq, eq_int)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Waveform {
+    #[default]
+    Sine,
+    Triangle,
+    Square,
+    Random,
+}
+
+/// A low frequency oscillator. Channels read it at evenly spread phase offsets for stereo width.
+#[derive(Clone, Debug)]
+pub struct Lfo {
+    phase: f32,
+    held: f32,
+    seed: u32,
+}
+
+impl Default for Lfo {
+    fn default() -> Self {
+        Lfo {
+            phase: 0.0,
+            held: 0.0,
+            seed: 0x9E37_79B9,
+        }
+    }
+}
+
+impl Lfo {
+    pub fn advance(&mut self, rate: f32, sample_rate: u32) {
+        self.phase += rate / sample_rate as f32;
+        if self.phase >= 1.0 {
+            self.phase = self.phase.fract();
+            // xorshift32, a new random level each cycle.
+            self.seed ^= self.seed << 13;
+            self.seed ^= self.seed >> 17;
+            self.seed ^= self.seed << 5;
+            self.held = self.seed as f32 / u32::MAX as f32 * 2.0 - 1.0;
+        }
+    }
+
+    /// The oscillator between -1.0 and 1.0, `offset` cycles ahead of its phase.
+    pub fn value(&self, offset: f32) -> f32 {
+        ((self.phase + offset) * TAU).sin()
+    }
+
+    /// The oscillator between -1.0 and 1.0 with the given shape.
+    pub fn wave(&self, waveform: Waveform) -> f32 {
+        match waveform {
+            Waveform::Sine => self.value(0.0),
+            Waveform::Triangle => 1.0 - 4.0 * (self.phase - 0.5).abs(),
+            Waveform::Square => {
+                if self.phase < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+            Waveform::Random => self.held,
+        }
+    }
+}
+
+fn channel_offset(channel: usize, channels: usize) -> f32 {
+    channel as f32 / (2 * channels.max(1)) as f32
+}
+
+pub mod modulation {
+    pub const ENABLED: usize = 0;
+    pub const RATE: usize = 1;
+    pub const DEPTH: usize = 2;
+    pub const FEEDBACK: usize = 3;
+    pub const MIX: usize = 4;
+}
+
+/// Base delay and maximum sweep, in seconds, of a modulated delay line.
+#[derive(Clone, Copy, Debug)]
+pub struct Sweep {
+    pub base: f32,
+    pub depth: f32,
+}
+
+pub const CHORUS_SWEEP: Sweep = Sweep {
+    base: 0.02,
+    depth: 0.008,
+};
+pub const FLANGER_SWEEP: Sweep = Sweep {
+    base: 0.001,
+    depth: 0.004,
+};
+
+/// A delay line swept by an LFO, the basis of chorus and flanger.
+pub struct ModulatedDelayProcessor {
+    params: Arc<Params>,
+    sweep: Sweep,
+    sample_rate: u32,
+    lfo: Lfo,
+    buffers: Vec<Vec<f32>>,
+    index: usize,
+}
+
+impl ModulatedDelayProcessor {
+    pub fn new(params: Arc<Params>, sweep: Sweep, sample_rate: u32, channels: usize) -> Self {
+        let frames = ((sweep.base + sweep.depth) * sample_rate as f32) as usize + 2;
+        ModulatedDelayProcessor {
+            params,
+            sweep,
+            sample_rate,
+            lfo: Lfo::default(),
+            buffers: vec![vec![0.0; frames]; channels],
+            index: 0,
+        }
+    }
+}
+
+impl Processor for ModulatedDelayProcessor {
+    fn process(&mut self, frame: &mut [f32]) {
+        if self.params.get(modulation::ENABLED) == 0.0 {
+            return;
+        }
+        let depth = self.params.get(modulation::DEPTH).clamp(0.0, 1.0);
+        let feedback = self.params.get(modulation::FEEDBACK);
+        let mix = self.params.get(modulation::MIX);
+        let channels = frame.len();
+        let len = self.buffers[0].len();
+
+        for (channel, (sample, buffer)) in frame.iter_mut().zip(&mut self.buffers).enumerate() {
+            let modulation = 0.5 + 0.5 * self.lfo.value(channel_offset(channel, channels));
+            let delay = (self.sweep.base + self.sweep.depth * depth * modulation)
+                * self.sample_rate as f32;
+            let read = (self.index + len) as f32 - delay.max(1.0);
+            let whole = read.floor();
+            let frac = read - whole;
+            let a = buffer[whole as usize % len];
+            let b = buffer[(whole as usize + 1) % len];
+            let delayed = a + (b - a) * frac;
+
+            buffer[self.index] = *sample + delayed * feedback;
+            *sample = *sample * (1.0 - mix) + delayed * mix;
+        }
+        self.index = (self.index + 1) % len;
+        self.lfo
+            .advance(self.params.get(modulation::RATE), self.sample_rate);
+    }
+
+    fn tail(&self) -> usize {
+        if self.params.get(modulation::ENABLED) == 0.0 {
+            return 0;
+        }
+        let feedback = self.params.get(modulation::FEEDBACK).abs();
+        let echoes = if feedback > 0.0 {
+            ((0.001f32).ln() / feedback.ln()).ceil() as usize
+        } else {
+            0
+        };
+        (echoes + 1) * self.buffers[0].len()
+    }
+}
+
+/// Lowest notch frequency of the phaser, in Hz.
+const PHASER_MIN_FREQUENCY: f32 = 200.0;
+/// Octaves the phaser sweeps over at full depth.
+const PHASER_OCTAVES: f32 = 4.0;
+
+/// A chain of first order allpass stages swept by an LFO. Mixed with the dry signal, they
+/// carve moving notches into the spectrum.
+pub struct PhaserProcessor {
+    params: Arc<Params>,
+    sample_rate: u32,
+    lfo: Lfo,
+    stages: Vec<Vec<(f32, f32)>>,
+    last: Vec<f32>,
+}
+
+impl PhaserProcessor {
+    pub fn new(params: Arc<Params>, stages: usize, sample_rate: u32, channels: usize) -> Self {
+        PhaserProcessor {
+            params,
+            sample_rate,
+            lfo: Lfo::default(),
+            stages: vec![vec![(0.0, 0.0); stages]; channels],
+            last: vec![0.0; channels],
+        }
+    }
+}
+
+impl Processor for PhaserProcessor {
+    fn process(&mut self, frame: &mut [f32]) {
+        if self.params.get(modulation::ENABLED) == 0.0 {
+            return;
+        }
+        let depth = self.params.get(modulation::DEPTH).clamp(0.0, 1.0);
+        let feedback = self.params.get(modulation::FEEDBACK);
+        let mix = self.params.get(modulation::MIX);
+        let channels = frame.len();
+        let nyquist = self.sample_rate as f32 / 2.0;
+
+        for (channel, sample) in frame.iter_mut().enumerate() {
+            let modulation = 0.5 + 0.5 * self.lfo.value(channel_offset(channel, channels));
+            let frequency = (PHASER_MIN_FREQUENCY * 2f32.powf(PHASER_OCTAVES * depth * modulation))
+                .min(nyquist * 0.9);
+            let tan = (std::f32::consts::PI * frequency / self.sample_rate as f32).tan();
+            let coefficient = (tan - 1.0) / (tan + 1.0);
+
+            let mut wet = *sample + self.last[channel] * feedback;
+            for (last_in, last_out) in self.stages[channel].iter_mut() {
+                let out = coefficient * wet + *last_in - coefficient * *last_out;
+                *last_in = wet;
+                *last_out = out;
+                wet = out;
+            }
+            self.last[channel] = wet;
+            *sample = *sample * (1.0 - mix) + wet * mix;
+        }
+        self.lfo
+            .advance(self.params.get(modulation::RATE), self.sample_rate);
+    }
+}
+
+pub mod lfo {
+    pub const ENABLED: usize = 0;
+    pub const RATE: usize = 1;
+    pub const DEPTH: usize = 2;
+}
+
+/// Time constant smoothing LFO steps and depth changes, in seconds. Keeps square and random
+/// waveforms, and switching the effect on or off, from clicking.
+const LFO_SMOOTHING_SECONDS: f32 = 0.002;
+/// Delay swept by a vibrato at full depth, in seconds.
+const VIBRATO_SWEEP_SECONDS: f32 = 0.003;
+
+/// The smoothed LFO and depth shared by tremolo and vibrato. The depth glides to 0 while the
+/// effect is disabled, and `next` returns None once the effect can be bypassed.
+struct LfoState {
+    params: Arc<Params>,
+    waveform: Waveform,
+    sample_rate: u32,
+    lfo: Lfo,
+    smoothing: f32,
+    wave: f32,
+    depth: f32,
+}
+
+impl LfoState {
+    fn new(params: Arc<Params>, waveform: Waveform, sample_rate: u32) -> Self {
+        LfoState {
+            params,
+            waveform,
+            sample_rate,
+            lfo: Lfo::default(),
+            smoothing: 1.0 - (-1.0 / (LFO_SMOOTHING_SECONDS * sample_rate as f32)).exp(),
+            wave: 0.0,
+            depth: 0.0,
+        }
+    }
+
+    /// Smoothed depth and LFO position between 0.0 and 1.0.
+    fn next(&mut self) -> Option<(f32, f32)> {
+        let enabled = self.params.get(lfo::ENABLED) != 0.0;
+        let depth = if enabled {
+            self.params.get(lfo::DEPTH).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        if !enabled && self.depth < 1e-4 {
+            self.depth = 0.0;
+            return None;
+        }
+        self.depth += (depth - self.depth) * self.smoothing;
+        self.wave += (0.5 + 0.5 * self.lfo.wave(self.waveform) - self.wave) * self.smoothing;
+        self.lfo.advance(self.params.get(lfo::RATE), self.sample_rate);
+        Some((self.depth, self.wave))
+    }
+}
+
+/// Amplitude modulation.
+pub struct TremoloProcessor {
+    state: LfoState,
+}
+
+impl TremoloProcessor {
+    pub fn new(params: Arc<Params>, waveform: Waveform, sample_rate: u32) -> Self {
+        TremoloProcessor {
+            state: LfoState::new(params, waveform, sample_rate),
+        }
+    }
+}
+
+impl Processor for TremoloProcessor {
+    fn process(&mut self, frame: &mut [f32]) {
+        let Some((depth, wave)) = self.state.next() else {
+            return;
+        };
+        let gain = 1.0 - depth * wave;
+        for sample in frame.iter_mut() {
+            *sample *= gain;
+        }
+    }
+}
+
+/// Pitch modulation through a swept delay line with no dry signal.
+pub struct VibratoProcessor {
+    state: LfoState,
+    buffers: Vec<Vec<f32>>,
+    index: usize,
+}
+
+impl VibratoProcessor {
+    pub fn new(params: Arc<Params>, waveform: Waveform, sample_rate: u32, channels: usize) -> Self {
+        let frames = (VIBRATO_SWEEP_SECONDS * sample_rate as f32) as usize + 3;
+        VibratoProcessor {
+            state: LfoState::new(params, waveform, sample_rate),
+            buffers: vec![vec![0.0; frames]; channels],
+            index: 0,
+        }
+    }
+}
+
+impl Processor for VibratoProcessor {
+    fn process(&mut self, frame: &mut [f32]) {
+        let len = self.buffers[0].len();
+        let index = self.index;
+        self.index = (index + 1) % len;
+
+        // Switched off, it reads at the base delay so turning it on and off does not click.
+        let (depth, wave) = self.state.next().unwrap_or((0.0, 0.0));
+        let delay = 1.0 + depth * wave * VIBRATO_SWEEP_SECONDS * self.state.sample_rate as f32;
+        let read = (index + len) as f32 - delay;
+        let whole = read.floor();
+        let frac = read - whole;
+
+        for (sample, buffer) in frame.iter_mut().zip(&mut self.buffers) {
+            buffer[index] = *sample;
+            let a = buffer[whole as usize % len];
+            let b = buffer[(whole as usize + 1) % len];
+            *sample = a + (b - a) * frac;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 48_000;
+
+    fn sine(frequency: f32, frames: usize) -> Vec<f32> {
+        (0..frames)
+            .map(|n| (n as f32 * frequency * TAU / SAMPLE_RATE as f32).sin() * 0.5)
+            .collect()
+    }
+
+    /// Runs a mono signal through `processor` one frame at a time.
+    fn run(processor: &mut dyn Processor, input: &[f32]) -> Vec<f32> {
+        input
+            .iter()
+            .map(|&sample| {
+                let mut frame = [sample];
+                processor.process(&mut frame);
+                frame[0]
+            })
+            .collect()
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|sample| sample * sample).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    /// Level of a tone at `frequency` after `processor`, relative to the input, once the
+    /// processor has settled.
+    fn gain_at(processor: &mut dyn Processor, frequency: f32) -> f32 {
+        let input = sine(frequency, 9600);
+        let output = run(processor, &input);
+        rms(&output[4800..]) / rms(&input[4800..])
+    }
+
+    fn filter(filter_type: FilterType, cutoff: f32, gain: f32) -> FilterProcessor {
+        let params = Params::new(&[1.0, cutoff, 0.707, gain]);
+        FilterProcessor::new(filter_type, params, SAMPLE_RATE, 1)
+    }
+
+    #[test]
+    fn low_pass_attenuates_above_its_cutoff() {
+        assert!(gain_at(&mut filter(FilterType::LowPass, 500.0, 0.0), 100.0) > 0.95);
+        // Two octaves up, a second-order slope is down by about 24 dB.
+        assert!(gain_at(&mut filter(FilterType::LowPass, 500.0, 0.0), 2000.0) < 0.07);
+    }
+
+    #[test]
+    fn high_pass_attenuates_below_its_cutoff() {
+        assert!(gain_at(&mut filter(FilterType::HighPass, 2000.0, 0.0), 8000.0) > 0.95);
+        assert!(gain_at(&mut filter(FilterType::HighPass, 2000.0, 0.0), 500.0) < 0.07);
+    }
+
+    #[test]
+    fn notch_removes_its_center_and_peak_boosts_it() {
+        assert!(gain_at(&mut filter(FilterType::Notch, 1000.0, 0.0), 1000.0) < 0.01);
+        let boost = gain_at(&mut filter(FilterType::Peak, 1000.0, 6.0), 1000.0);
+        assert!((boost - 10f32.powf(6.0 / 20.0)).abs() < 0.02, "{boost}");
+    }
+
+    #[test]
+    fn disabled_filter_passes_audio_through() {
+        let params = Params::new(&[0.0, 200.0, 0.707, 0.0]);
+        let mut low_pass = FilterProcessor::new(FilterType::LowPass, params, SAMPLE_RATE, 1);
+        let input = sine(5000.0, 480);
+        assert_eq!(run(&mut low_pass, &input), input);
+    }
+
+    #[test]
+    fn eq_bands_follow_their_shared_settings() {
+        let bands = Params::new(&[1000.0, 1.0, 6.0, 100.0, 0.707, 0.0]);
+        let mut eq = EqProcessor::new(
+            &[FilterType::Peak, FilterType::HighPass],
+            bands.clone(),
+            Params::new(&[1.0]),
+            SAMPLE_RATE,
+            1,
+        );
+        assert!((gain_at(&mut eq, 1000.0) - 10f32.powf(6.0 / 20.0)).abs() < 0.05);
+        assert!(gain_at(&mut eq, 30.0) < 0.15);
+
+        bands.set(eq::GAIN, -6.0);
+        let cut = gain_at(&mut eq, 1000.0);
+        assert!((cut - 10f32.powf(-6.0 / 20.0)).abs() < 0.02, "{cut}");
+    }
+
+    #[test]
+    fn reverb_tail_starts_after_the_pre_delay_and_decays() {
+        let params = Params::new(&[1.0, 0.8, 0.5, 1.0]);
+        let mut reverb = ReverbProcessor::new(params, 0.02, SAMPLE_RATE, 1);
+        let tail = reverb.tail();
+        let mut input = vec![0.0; tail];
+        input[0] = 1.0;
+        let output = run(&mut reverb, &input);
+
+        // Nothing comes back before the pre-delay and the shortest comb have passed.
+        let first_echo = 960 + COMB_TUNING[0] * SAMPLE_RATE as usize / 44_100;
+        assert!(output[..first_echo].iter().all(|sample| *sample == 0.0));
+        assert!(output[first_echo] != 0.0);
+
+        let windows: Vec<f32> = output[first_echo..]
+            .chunks(SAMPLE_RATE as usize / 4)
+            .map(rms)
+            .collect();
+        assert!(windows.windows(2).all(|pair| pair[1] < pair[0]), "{windows:?}");
+        // The reported tail covers the decay to -60 dB.
+        assert!(windows.last().unwrap() / windows[0] < 0.002);
+    }
+
+    #[test]
+    fn delay_echoes_after_its_time_and_decays_by_its_feedback() {
+        let params = Params::new(&[1.0, 0.5, 0.5]);
+        let mut delay = DelayProcessor::new(params, 0.1, false, SAMPLE_RATE, 1);
+        let mut input = vec![0.0; 3 * 4800 + 1];
+        input[0] = 1.0;
+        let output = run(&mut delay, &input);
+
+        let echoes: Vec<(usize, f32)> = output
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|(_, sample)| *sample != 0.0)
+            .collect();
+        assert_eq!(echoes, [(0, 0.5), (4800, 0.5), (9600, 0.25), (14400, 0.125)]);
+    }
+
+    #[test]
+    fn ping_pong_delay_alternates_channels() {
+        let params = Params::new(&[1.0, 0.5, 1.0]);
+        let mut delay = DelayProcessor::new(params, 0.1, true, SAMPLE_RATE, 2);
+        let mut frames = vec![[0.0f32; 2]; 2 * 4800 + 1];
+        frames[0] = [1.0, 0.0];
+        for frame in frames.iter_mut() {
+            delay.process(frame);
+        }
+        assert_eq!(frames[4800], [0.5, 0.0]);
+        assert_eq!(frames[9600], [0.0, 0.25]);
+    }
+
+    fn to_db(level: f32) -> f32 {
+        20.0 * level.log10()
+    }
+
+    /// Output level in dB of a steady ±`amplitude` signal, with the reduction on the meter.
+    fn dynamics_level(mode: DynamicsMode, settings: [f32; 7], amplitude: f32) -> (f32, f32) {
+        let mut values = vec![1.0];
+        values.extend_from_slice(&settings);
+        let meter = Meter::default();
+        let params = Params::new(&values);
+        let mut dynamics = DynamicsProcessor::new(mode, params, meter.clone(), SAMPLE_RATE);
+        let input: Vec<f32> = (0..SAMPLE_RATE)
+            .map(|n| if n % 2 == 0 { amplitude } else { -amplitude })
+            .collect();
+        let output = run(&mut dynamics, &input);
+        (to_db(output.last().unwrap().abs()), meter.get())
+    }
+
+    #[test]
+    fn compressor_divides_the_level_above_threshold_by_its_ratio() {
+        // Threshold, ratio, attack, release, knee, makeup, range.
+        let settings = [-20.0, 4.0, 0.005, 0.1, 0.0, 0.0, 0.0];
+        let over = to_db(0.5) + 20.0;
+        let (level, reduction) = dynamics_level(DynamicsMode::Compressor, settings, 0.5);
+        assert!((level - (-20.0 + over / 4.0)).abs() < 0.01, "{level}");
+        assert!((reduction - over * 0.75).abs() < 0.01);
+
+        let (level, reduction) = dynamics_level(DynamicsMode::Compressor, settings, 0.05);
+        assert!((level - to_db(0.05)).abs() < 0.01);
+        assert_eq!(reduction, 0.0);
+
+        let makeup = [-20.0, 4.0, 0.005, 0.1, 0.0, 6.0, 0.0];
+        let (level, _) = dynamics_level(DynamicsMode::Compressor, makeup, 0.05);
+        assert!((level - (to_db(0.05) + 6.0)).abs() < 0.01);
+    }
+
+    #[test]
+    fn limiter_holds_the_threshold_and_gate_drops_by_its_range() {
+        let limiter = [-12.0, f32::INFINITY, 0.0, 0.1, 0.0, 0.0, 0.0];
+        let (level, _) = dynamics_level(DynamicsMode::Limiter, limiter, 0.9);
+        assert!((level + 12.0).abs() < 0.01, "{level}");
+
+        let gate = [-30.0, 1.0, 0.001, 0.05, 0.0, 0.0, 40.0];
+        let (level, reduction) = dynamics_level(DynamicsMode::Gate, gate, 0.01);
+        assert!((level - (-40.0 - 40.0)).abs() < 0.01, "{level}");
+        assert!((reduction - 40.0).abs() < 0.01);
+        let (level, _) = dynamics_level(DynamicsMode::Gate, gate, 0.5);
+        assert!((level - to_db(0.5)).abs() < 0.01);
+    }
+
+    #[test]
+    fn distortion_clips_and_blends_by_its_amount() {
+        let input = sine(440.0, 4800);
+        // Enabled, amount, drive in dB.
+        let params = Params::new(&[1.0, 1.0, 12.0]);
+        let mut hard = DistortionProcessor::new(DistortionType::HardClip, params.clone(), 1);
+        let clipped = run(&mut hard, &input);
+        assert_eq!(clipped.iter().copied().fold(f32::MIN, f32::max), 1.0);
+        assert_eq!(clipped.iter().copied().fold(f32::MAX, f32::min), -1.0);
+
+        params.set(distortion::AMOUNT, 0.5);
+        let blended = run(&mut hard, &input);
+        for ((blended, clipped), dry) in blended.iter().zip(&clipped).zip(&input) {
+            assert!((blended - (dry + clipped) / 2.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn tube_distortion_leaves_no_dc_offset() {
+        let params = Params::new(&[1.0, 1.0, 24.0]);
+        let mut tube = DistortionProcessor::new(DistortionType::Tube, params, 1);
+        let output = run(&mut tube, &sine(440.0, SAMPLE_RATE as usize));
+        let settled = &output[SAMPLE_RATE as usize / 2..];
+        let mean = settled.iter().sum::<f32>() / settled.len() as f32;
+        assert!(mean.abs() < 1e-3, "{mean}");
+    }
+
+    #[test]
+    fn bitcrusher_quantizes_to_its_bit_depth_and_holds_samples() {
+        // Enabled, amount, bits, sample rate.
+        let params = Params::new(&[1.0, 1.0, 3.0, 8000.0]);
+        let mut crusher = BitcrusherProcessor::new(params, SAMPLE_RATE, 1);
+        let ramp: Vec<f32> = (0..4800).map(|n| n as f32 / 2400.0 - 1.0).collect();
+        let output = run(&mut crusher, &ramp);
+
+        // Steps of 2 / 2^bits over -1.0..=1.0.
+        let mut levels: Vec<i32> = output.iter().map(|sample| (sample * 4.0) as i32).collect();
+        assert!(output.iter().all(|sample| (sample * 4.0).fract() == 0.0));
+        levels.dedup();
+        assert_eq!(levels, (-4..=4).collect::<Vec<_>>());
+
+        // At 16 bits every step of the ramp is distinct, so the runs show the hold time.
+        let params = Params::new(&[1.0, 1.0, 16.0, 8000.0]);
+        let mut crusher = BitcrusherProcessor::new(params, SAMPLE_RATE, 1);
+        let mut output = run(&mut crusher, &ramp);
+        output.dedup();
+        // 48 kHz held down to 8 kHz.
+        assert!((output.len() as i32 - 800).abs() <= 1, "{}", output.len());
+    }
+
+    fn impulses(every: usize, frames: usize) -> Vec<f32> {
+        (0..frames).map(|n| if n % every == 0 { 1.0 } else { 0.0 }).collect()
+    }
+
+    #[test]
+    fn flanger_feedback_repeats_at_its_base_delay() {
+        // Enabled, rate, depth, feedback, mix.
+        let params = Params::new(&[1.0, 0.5, 0.0, -0.5, 0.5]);
+        let mut flanger = ModulatedDelayProcessor::new(params, FLANGER_SWEEP, SAMPLE_RATE, 1);
+        let output = run(&mut flanger, &impulses(4800, 200));
+
+        let base = (FLANGER_SWEEP.base * SAMPLE_RATE as f32).round() as usize;
+        for (echo, expected) in [(0, 0.5), (base, 0.5), (2 * base, -0.25), (3 * base, 0.125)] {
+            assert!((output[echo] - expected).abs() < 1e-3, "{echo}: {}", output[echo]);
+        }
+    }
+
+    #[test]
+    fn chorus_sweeps_its_delay_within_its_depth() {
+        let params = Params::new(&[1.0, 1.0, 1.0, 0.0, 1.0]);
+        let mut chorus = ModulatedDelayProcessor::new(params, CHORUS_SWEEP, SAMPLE_RATE, 1);
+        let output = run(&mut chorus, &impulses(4800, SAMPLE_RATE as usize));
+
+        let shortest = (CHORUS_SWEEP.base * SAMPLE_RATE as f32) as usize;
+        let longest = ((CHORUS_SWEEP.base + CHORUS_SWEEP.depth) * SAMPLE_RATE as f32) as usize + 1;
+        let mut delays: Vec<usize> = output
+            .chunks(4800)
+            .map(|echo| {
+                let loudest = echo.iter().map(|sample| sample.abs()).fold(0.0, f32::max);
+                echo.iter().position(|sample| sample.abs() == loudest).unwrap()
+            })
+            .collect();
+        assert!(delays.iter().all(|delay| (shortest..=longest).contains(delay)), "{delays:?}");
+        delays.dedup();
+        assert!(delays.len() > 5, "{delays:?}");
+    }
+
+    #[test]
+    fn phaser_notches_where_its_stages_turn_half_a_cycle() {
+        // Four stages at 200 Hz turn a tone by half a cycle at 200 * tan(3/8 pi), about 483 Hz,
+        // and by a whole cycle at 200 Hz.
+        let params = Params::new(&[1.0, 0.5, 0.0, 0.0, 0.5]);
+        let notch = 200.0 * (3.0 * std::f32::consts::PI / 8.0).tan();
+        let mut phaser = PhaserProcessor::new(params.clone(), 4, SAMPLE_RATE, 1);
+        assert!(gain_at(&mut phaser, notch) < 0.05);
+        let mut phaser = PhaserProcessor::new(params, 4, SAMPLE_RATE, 1);
+        assert!(gain_at(&mut phaser, 200.0) > 0.95);
+    }
+
+    #[test]
+    fn tremolo_gain_follows_its_waveform_and_depth() {
+        // A 10 Hz square wave is high for the first 2400 frames of every cycle.
+        let params = Params::new(&[1.0, 10.0, 1.0]);
+        let mut tremolo = TremoloProcessor::new(params.clone(), Waveform::Square, SAMPLE_RATE);
+        let output = run(&mut tremolo, &[1.0; 4800]);
+        assert!(output[1200] < 1e-3 && output[3600] > 0.999);
+        // The depth and steps glide instead of jumping.
+        assert!(output.windows(2).all(|pair| (pair[1] - pair[0]).abs() < 0.02));
+
+        params.set(lfo::DEPTH, 0.5);
+        let output = run(&mut tremolo, &[1.0; 4800]);
+        assert!((output[1200] - 0.5).abs() < 1e-3 && output[3600] > 0.999);
+
+        params.set(lfo::ENABLED, 0.0);
+        let output = run(&mut tremolo, &[1.0; 4800]);
+        assert!(output[2400..].iter().all(|sample| *sample == 1.0));
+    }
+
+    #[test]
+    fn vibrato_keeps_its_base_delay_when_off() {
+        let params = Params::new(&[0.0, 5.0, 1.0]);
+        let mut vibrato = VibratoProcessor::new(params.clone(), Waveform::Sine, SAMPLE_RATE, 1);
+        let input = sine(440.0, 4800);
+
+        let off = run(&mut vibrato, &input[..2400]);
+        assert_eq!(off[0], 0.0);
+        assert_eq!(&off[1..], &input[..2399]);
+
+        params.set(lfo::ENABLED, 1.0);
+        let on = run(&mut vibrato, &input[2400..]);
+        let step = |a: f32, b: f32| (a - b).abs();
+        // The sweep starts from the base delay instead of jumping to it.
+        assert!(step(on[0], off[2399]) <= step(input[2399], input[2398]) * 1.01);
+        assert!(on.iter().zip(&input[2399..]).any(|(wet, dry)| (wet - dry).abs() > 1e-3));
+    }
+}
@@ -0,0 +1,1610 @@
+use std::fmt;
+use std::sync::Arc;
+
+use pyo3::exceptions::{PyIndexError, PyValueError};
+use pyo3::{prelude::*, types::{IntoPyDict, PyDict}};
+use serde::{Deserialize, Serialize};
+
+use crate::dsp::{eq, DistortionType, FilterType, Meter, Params, Setting, Waveform, BUTTERWORTH_Q};
+use crate::schema;
+use crate::timesync::{Anchor, Easing};
+use crate::utils::json_to_py;
+
+/// A biquad filter in the sample chain. The cutoff can sweep from `start_cutoff` to `cutoff`
+/// over `duration`; the filter stays in place after the sweep.
+#[derive(Clone, Debug, Copy, PartialEq, Serialize, Deserialize)]
+#[pyclass]
+pub struct Filter {
+    #[pyo3(get, set)]
+    pub filter_type: FilterType,
+    #[pyo3(get)]
+    pub cutoff: f32,
+    #[pyo3(get)]
+    pub q: f32,
+    #[pyo3(get)]
+    pub gain: f32,
+    #[pyo3(get)]
+    pub start_cutoff: Option<f32>,
+    #[pyo3(get)]
+    pub duration: Option<f32>,
+    #[pyo3(get)]
+    pub apply_after: Option<f32>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub anchor: Option<Anchor>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub curve: Easing,
+}
+
+#[pymethods]
+impl Filter {
+    #[new]
+    #[pyo3(signature = (filter_type=FilterType::LowPass, cutoff=1000.0, q=BUTTERWORTH_Q, gain=0.0, start_cutoff=None, duration=None, apply_after=None, anchor=None, curve=Easing::default()))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        filter_type: FilterType,
+        cutoff: f32,
+        q: f32,
+        gain: f32,
+        start_cutoff: Option<f32>,
+        duration: Option<f32>,
+        apply_after: Option<f32>,
+        anchor: Option<Anchor>,
+        curve: Easing,
+    ) -> PyResult<Self> {
+        let filter = Filter {
+            filter_type,
+            cutoff,
+            q,
+            gain,
+            start_cutoff,
+            duration,
+            apply_after,
+            anchor,
+            curve,
+        };
+        filter.validate()?;
+        Ok(filter)
+    }
+
+    #[setter]
+    fn set_cutoff(&mut self, cutoff: f32) -> PyResult<()> {
+        schema::update(&schema::FILTER, "cutoff", &mut self.cutoff, cutoff)
+    }
+
+    #[setter]
+    fn set_q(&mut self, q: f32) -> PyResult<()> {
+        schema::update(&schema::FILTER, "q", &mut self.q, q)
+    }
+
+    #[setter]
+    fn set_gain(&mut self, gain: f32) -> PyResult<()> {
+        schema::update(&schema::FILTER, "gain", &mut self.gain, gain)
+    }
+
+    #[setter]
+    fn set_start_cutoff(&mut self, start_cutoff: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::FILTER, "start_cutoff", &mut self.start_cutoff, start_cutoff)
+    }
+
+    #[setter]
+    fn set_duration(&mut self, duration: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::FILTER, "duration", &mut self.duration, duration)
+    }
+
+    #[setter]
+    fn set_apply_after(&mut self, apply_after: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::FILTER, "apply_after", &mut self.apply_after, apply_after)
+    }
+
+    #[staticmethod]
+    fn schema(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+        schema::to_dict(py, &schema::FILTER)
+    }
+
+    fn as_dict<'py>(&self, py: Python<'py>) -> Bound<'py, PyDict> {
+        self.into_py_dict_bound(py)
+    }
+}
+
+schema::validate!(Filter, schema::FILTER, [cutoff, q, gain, start_cutoff, duration, apply_after]);
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Filter {{ filter_type: {:?}, cutoff: {}, q: {}, gain: {}, start_cutoff: {:?}, duration: {:?}, apply_after: {:?}, curve: {:?} }}",
+            self.filter_type, self.cutoff, self.q, self.gain, self.start_cutoff, self.duration, self.apply_after, self.curve)
+    }
+}
+
+impl IntoPyDict for Filter {
+    fn into_py_dict_bound(self, py: Python<'_>) -> Bound<'_, PyDict> {
+        let value = serde_json::to_value(self).unwrap();
+        let dict: Bound<'_, PyDict> = json_to_py(py, &value).extract().unwrap();
+        dict.set_item("type", "Filter").unwrap();
+        dict
+    }
+}
+
+#[derive(Clone, Debug, Copy, PartialEq, Serialize, Deserialize)]
+#[pyclass]
+pub struct EQBand {
+    #[pyo3(get)]
+    pub band_type: FilterType,
+    #[pyo3(get)]
+    pub frequency: f32,
+    #[pyo3(get)]
+    pub gain: f32,
+    #[pyo3(get)]
+    pub q: f32,
+}
+
+#[pymethods]
+impl EQBand {
+    #[new]
+    #[pyo3(signature = (band_type=FilterType::Peak, frequency=1000.0, gain=0.0, q=BUTTERWORTH_Q))]
+    pub fn new(band_type: FilterType, frequency: f32, gain: f32, q: f32) -> PyResult<Self> {
+        let band = EQBand {
+            band_type,
+            frequency,
+            gain,
+            q,
+        };
+        band.validate()?;
+        Ok(band)
+    }
+
+    #[staticmethod]
+    fn schema(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+        schema::to_dict(py, &schema::EQ_BAND)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "EQBand(band_type={:?}, frequency={}, gain={}, q={})",
+            self.band_type, self.frequency, self.gain, self.q
+        )
+    }
+}
+
+schema::validate!(EQBand, schema::EQ_BAND, [frequency, gain, q]);
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct EqPreset {
+    bands: Vec<EQBand>,
+    apply_after: Option<f32>,
+    #[serde(default)]
+    anchor: Option<Anchor>,
+}
+
+/// A multi-band equalizer. The band settings are shared with every sink the equalizer is
+/// applied to, so changes made through `set_band` are heard during playback.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(from = "EqPreset", into = "EqPreset")]
+#[pyclass]
+pub struct ParametricEQ {
+    band_types: Vec<FilterType>,
+    pub params: Arc<Params>,
+    #[pyo3(get)]
+    pub apply_after: Option<f32>,
+    #[pyo3(get, set)]
+    pub anchor: Option<Anchor>,
+}
+
+impl ParametricEQ {
+    pub fn band_types(&self) -> &[FilterType] {
+        &self.band_types
+    }
+
+    /// Checks the bands and `apply_after` against `schema::EQ_BAND` and
+    /// `schema::PARAMETRIC_EQ`.
+    pub fn validate(&self) -> PyResult<()> {
+        for band in self.get_bands() {
+            band.validate()?;
+        }
+        schema::find(&schema::PARAMETRIC_EQ, "apply_after").check(self.apply_after)
+    }
+
+    fn band(&self, index: usize) -> EQBand {
+        let base = index * eq::STRIDE;
+        EQBand {
+            band_type: self.band_types[index],
+            frequency: self.params.get(base + eq::FREQUENCY),
+            gain: self.params.get(base + eq::GAIN),
+            q: self.params.get(base + eq::Q),
+        }
+    }
+}
+
+impl From<EqPreset> for ParametricEQ {
+    fn from(preset: EqPreset) -> Self {
+        let values: Vec<f32> = preset
+            .bands
+            .iter()
+            .flat_map(|band| [band.frequency, band.q, band.gain])
+            .collect();
+        ParametricEQ {
+            band_types: preset.bands.iter().map(|band| band.band_type).collect(),
+            params: Params::new(&values),
+            apply_after: preset.apply_after,
+            anchor: preset.anchor,
+        }
+    }
+}
+
+impl From<ParametricEQ> for EqPreset {
+    fn from(equalizer: ParametricEQ) -> Self {
+        EqPreset {
+            bands: equalizer.get_bands(),
+            apply_after: equalizer.apply_after,
+            anchor: equalizer.anchor,
+        }
+    }
+}
+
+impl PartialEq for ParametricEQ {
+    fn eq(&self, other: &Self) -> bool {
+        self.apply_after == other.apply_after
+            && self.anchor == other.anchor
+            && self.get_bands() == other.get_bands()
+    }
+}
+
+#[pymethods]
+impl ParametricEQ {
+    #[new]
+    #[pyo3(signature = (bands=Vec::new(), apply_after=None, anchor=None))]
+    pub fn new(bands: Vec<EQBand>, apply_after: Option<f32>, anchor: Option<Anchor>) -> PyResult<Self> {
+        let equalizer: ParametricEQ = EqPreset {
+            bands,
+            apply_after,
+            anchor,
+        }
+        .into();
+        equalizer.validate()?;
+        Ok(equalizer)
+    }
+
+    #[getter]
+    fn get_bands(&self) -> Vec<EQBand> {
+        (0..self.band_types.len()).map(|index| self.band(index)).collect()
+    }
+
+    #[pyo3(signature = (index, frequency=None, gain=None, q=None))]
+    fn set_band(
+        &self,
+        index: usize,
+        frequency: Option<f32>,
+        gain: Option<f32>,
+        q: Option<f32>,
+    ) -> PyResult<()> {
+        if index >= self.band_types.len() {
+            return Err(PyIndexError::new_err("Band index out of range."));
+        }
+        let band = self.band(index);
+        let frequency = frequency.unwrap_or(band.frequency);
+        let gain = gain.unwrap_or(band.gain);
+        let q = q.unwrap_or(band.q);
+        EQBand::new(band.band_type, frequency, gain, q)?;
+
+        let base = index * eq::STRIDE;
+        self.params.set(base + eq::FREQUENCY, frequency);
+        self.params.set(base + eq::GAIN, gain);
+        self.params.set(base + eq::Q, q);
+        Ok(())
+    }
+
+    fn __len__(&self) -> usize {
+        self.band_types.len()
+    }
+
+    #[setter]
+    fn set_apply_after(&mut self, apply_after: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::PARAMETRIC_EQ, "apply_after", &mut self.apply_after, apply_after)
+    }
+
+    #[staticmethod]
+    fn schema(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+        schema::to_dict(py, &schema::PARAMETRIC_EQ)
+    }
+
+    fn as_dict<'py>(&self, py: Python<'py>) -> Bound<'py, PyDict> {
+        self.clone().into_py_dict_bound(py)
+    }
+}
+
+impl fmt::Display for ParametricEQ {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ParametricEQ {{ bands: {:?}, apply_after: {:?} }}",
+            self.get_bands(), self.apply_after)
+    }
+}
+
+impl IntoPyDict for ParametricEQ {
+    fn into_py_dict_bound(self, py: Python<'_>) -> Bound<'_, PyDict> {
+        let value = serde_json::to_value(self).unwrap();
+        let dict: Bound<'_, PyDict> = json_to_py(py, &value).extract().unwrap();
+        dict.set_item("type", "ParametricEQ").unwrap();
+        dict
+    }
+}
+
+/// A Freeverb style reverb. Room size, damping and mix are shared with the sinks the reverb is
+/// applied to, so they can be changed during playback; the pre-delay is fixed once applied.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[pyclass]
+pub struct Reverb {
+    #[pyo3(get)]
+    pub room_size: Setting,
+    #[pyo3(get)]
+    pub damping: Setting,
+    #[pyo3(get)]
+    pub mix: Setting,
+    #[pyo3(get)]
+    pub pre_delay: f32,
+    #[pyo3(get)]
+    pub apply_after: Option<f32>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub anchor: Option<Anchor>,
+}
+
+#[pymethods]
+impl Reverb {
+    #[new]
+    #[pyo3(signature = (room_size=0.5, damping=0.5, mix=0.3, pre_delay=0.0, apply_after=None, anchor=None))]
+    pub fn new(
+        room_size: f32,
+        damping: f32,
+        mix: f32,
+        pre_delay: f32,
+        apply_after: Option<f32>,
+        anchor: Option<Anchor>,
+    ) -> PyResult<Self> {
+        let reverb = Reverb {
+            room_size: room_size.into(),
+            damping: damping.into(),
+            mix: mix.into(),
+            pre_delay,
+            apply_after,
+            anchor,
+        };
+        reverb.validate()?;
+        Ok(reverb)
+    }
+
+    #[setter]
+    fn set_room_size(&mut self, room_size: f32) -> PyResult<()> {
+        schema::update(&schema::REVERB, "room_size", &mut self.room_size, room_size)
+    }
+
+    #[setter]
+    fn set_damping(&mut self, damping: f32) -> PyResult<()> {
+        schema::update(&schema::REVERB, "damping", &mut self.damping, damping)
+    }
+
+    #[setter]
+    fn set_mix(&mut self, mix: f32) -> PyResult<()> {
+        schema::update(&schema::REVERB, "mix", &mut self.mix, mix)
+    }
+
+    #[setter]
+    fn set_pre_delay(&mut self, pre_delay: f32) -> PyResult<()> {
+        schema::update(&schema::REVERB, "pre_delay", &mut self.pre_delay, pre_delay)
+    }
+
+    #[setter]
+    fn set_apply_after(&mut self, apply_after: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::REVERB, "apply_after", &mut self.apply_after, apply_after)
+    }
+
+    #[staticmethod]
+    fn schema(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+        schema::to_dict(py, &schema::REVERB)
+    }
+
+    fn as_dict<'py>(&self, py: Python<'py>) -> Bound<'py, PyDict> {
+        self.clone().into_py_dict_bound(py)
+    }
+}
+
+schema::validate!(Reverb, schema::REVERB, [room_size, damping, mix, pre_delay, apply_after]);
+
+impl fmt::Display for Reverb {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Reverb {{ room_size: {}, damping: {}, mix: {}, pre_delay: {}, apply_after: {:?} }}",
+            self.room_size, self.damping, self.mix, self.pre_delay, self.apply_after)
+    }
+}
+
+impl IntoPyDict for Reverb {
+    fn into_py_dict_bound(self, py: Python<'_>) -> Bound<'_, PyDict> {
+        let value = serde_json::to_value(self).unwrap();
+        let dict: Bound<'_, PyDict> = json_to_py(py, &value).extract().unwrap();
+        dict.set_item("type", "Reverb").unwrap();
+        dict
+    }
+}
+
+#[pyclass(eq, eq_int)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum NoteValue {
+    Whole,
+    Half,
+    Quarter,
+    Eighth,
+    Sixteenth,
+    ThirtySecond,
+    DottedHalf,
+    DottedQuarter,
+    DottedEighth,
+    DottedSixteenth,
+    HalfTriplet,
+    QuarterTriplet,
+    EighthTriplet,
+    SixteenthTriplet,
+}
+
+impl NoteValue {
+    /// Length of the note in quarter note beats.
+    fn beats(&self) -> f32 {
+        match self {
+            NoteValue::Whole => 4.0,
+            NoteValue::Half => 2.0,
+            NoteValue::Quarter => 1.0,
+            NoteValue::Eighth => 0.5,
+            NoteValue::Sixteenth => 0.25,
+            NoteValue::ThirtySecond => 0.125,
+            NoteValue::DottedHalf => 3.0,
+            NoteValue::DottedQuarter => 1.5,
+            NoteValue::DottedEighth => 0.75,
+            NoteValue::DottedSixteenth => 0.375,
+            NoteValue::HalfTriplet => 4.0 / 3.0,
+            NoteValue::QuarterTriplet => 2.0 / 3.0,
+            NoteValue::EighthTriplet => 1.0 / 3.0,
+            NoteValue::SixteenthTriplet => 1.0 / 6.0,
+        }
+    }
+}
+
+/// A feedback delay. Feedback and mix are shared with the sinks the delay is applied to, so they
+/// can be changed during playback; the time and ping-pong routing are fixed once applied.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[pyclass]
+pub struct Delay {
+    #[pyo3(get)]
+    pub time: f32,
+    #[pyo3(get)]
+    pub feedback: Setting,
+    #[pyo3(get)]
+    pub mix: Setting,
+    #[pyo3(get, set)]
+    pub ping_pong: bool,
+    #[pyo3(get)]
+    pub note: Option<NoteValue>,
+    #[pyo3(get)]
+    pub bpm: Option<f32>,
+    #[pyo3(get)]
+    pub apply_after: Option<f32>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub anchor: Option<Anchor>,
+}
+
+#[pymethods]
+impl Delay {
+    #[new]
+    #[pyo3(signature = (time=0.25, feedback=0.35, mix=0.3, ping_pong=false, note=None, bpm=None, apply_after=None, anchor=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        time: f32,
+        feedback: f32,
+        mix: f32,
+        ping_pong: bool,
+        note: Option<NoteValue>,
+        bpm: Option<f32>,
+        apply_after: Option<f32>,
+        anchor: Option<Anchor>,
+    ) -> PyResult<Self> {
+        schema::find(&schema::DELAY, "bpm").check(bpm)?;
+        let time = match (note, bpm) {
+            (Some(note), Some(bpm)) => note.beats() * 60.0 / bpm,
+            (None, None) => time,
+            _ => return Err(PyValueError::new_err("Tempo sync needs both a note value and a BPM.")),
+        };
+        let delay = Delay {
+            time,
+            feedback: feedback.into(),
+            mix: mix.into(),
+            ping_pong,
+            note,
+            bpm,
+            apply_after,
+            anchor,
+        };
+        delay.validate()?;
+        Ok(delay)
+    }
+
+    /// Setting the time directly drops the tempo sync it may have come from.
+    #[setter]
+    fn set_time(&mut self, time: f32) -> PyResult<()> {
+        schema::update(&schema::DELAY, "time", &mut self.time, time)?;
+        self.note = None;
+        self.bpm = None;
+        Ok(())
+    }
+
+    #[setter]
+    fn set_feedback(&mut self, feedback: f32) -> PyResult<()> {
+        schema::update(&schema::DELAY, "feedback", &mut self.feedback, feedback)
+    }
+
+    #[setter]
+    fn set_mix(&mut self, mix: f32) -> PyResult<()> {
+        schema::update(&schema::DELAY, "mix", &mut self.mix, mix)
+    }
+
+    #[setter]
+    fn set_apply_after(&mut self, apply_after: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::DELAY, "apply_after", &mut self.apply_after, apply_after)
+    }
+
+    #[staticmethod]
+    fn schema(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+        schema::to_dict(py, &schema::DELAY)
+    }
+
+    fn as_dict<'py>(&self, py: Python<'py>) -> Bound<'py, PyDict> {
+        self.clone().into_py_dict_bound(py)
+    }
+}
+
+schema::validate!(Delay, schema::DELAY, [time, feedback, mix, bpm, apply_after]);
+
+impl fmt::Display for Delay {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Delay {{ time: {}, feedback: {}, mix: {}, ping_pong: {}, note: {:?}, bpm: {:?}, apply_after: {:?} }}",
+            self.time, self.feedback, self.mix, self.ping_pong, self.note, self.bpm, self.apply_after)
+    }
+}
+
+impl IntoPyDict for Delay {
+    fn into_py_dict_bound(self, py: Python<'_>) -> Bound<'_, PyDict> {
+        let value = serde_json::to_value(self).unwrap();
+        let dict: Bound<'_, PyDict> = json_to_py(py, &value).extract().unwrap();
+        dict.set_item("type", "Delay").unwrap();
+        dict
+    }
+}
+
+/// A compressor. Its settings are shared with the sinks it is applied to, so they can
+/// be changed during playback.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[pyclass]
+pub struct Compressor {
+    #[pyo3(get)]
+    pub threshold: Setting,
+    #[pyo3(get)]
+    pub ratio: Setting,
+    #[pyo3(get)]
+    pub attack: Setting,
+    #[pyo3(get)]
+    pub release: Setting,
+    #[pyo3(get)]
+    pub knee: Setting,
+    #[pyo3(get)]
+    pub makeup: Setting,
+    #[pyo3(get)]
+    pub apply_after: Option<f32>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub anchor: Option<Anchor>,
+    #[serde(skip)]
+    pub meter: Meter,
+}
+
+#[pymethods]
+impl Compressor {
+    #[new]
+    #[pyo3(signature = (threshold=-18.0, ratio=4.0, attack=0.01, release=0.1, knee=6.0, makeup=0.0, apply_after=None, anchor=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        threshold: f32,
+        ratio: f32,
+        attack: f32,
+        release: f32,
+        knee: f32,
+        makeup: f32,
+        apply_after: Option<f32>,
+        anchor: Option<Anchor>,
+    ) -> PyResult<Self> {
+        let compressor = Compressor {
+            threshold: threshold.into(),
+            ratio: ratio.into(),
+            attack: attack.into(),
+            release: release.into(),
+            knee: knee.into(),
+            makeup: makeup.into(),
+            apply_after,
+            anchor,
+            meter: Meter::default(),
+        };
+        compressor.validate()?;
+        Ok(compressor)
+    }
+
+    /// Current gain reduction in dB.
+    #[getter]
+    fn gain_reduction(&self) -> f32 {
+        self.meter.get()
+    }
+
+    #[setter]
+    fn set_threshold(&mut self, threshold: f32) -> PyResult<()> {
+        schema::update(&schema::COMPRESSOR, "threshold", &mut self.threshold, threshold)
+    }
+
+    #[setter]
+    fn set_ratio(&mut self, ratio: f32) -> PyResult<()> {
+        schema::update(&schema::COMPRESSOR, "ratio", &mut self.ratio, ratio)
+    }
+
+    #[setter]
+    fn set_attack(&mut self, attack: f32) -> PyResult<()> {
+        schema::update(&schema::COMPRESSOR, "attack", &mut self.attack, attack)
+    }
+
+    #[setter]
+    fn set_release(&mut self, release: f32) -> PyResult<()> {
+        schema::update(&schema::COMPRESSOR, "release", &mut self.release, release)
+    }
+
+    #[setter]
+    fn set_knee(&mut self, knee: f32) -> PyResult<()> {
+        schema::update(&schema::COMPRESSOR, "knee", &mut self.knee, knee)
+    }
+
+    #[setter]
+    fn set_makeup(&mut self, makeup: f32) -> PyResult<()> {
+        schema::update(&schema::COMPRESSOR, "makeup", &mut self.makeup, makeup)
+    }
+
+    #[setter]
+    fn set_apply_after(&mut self, apply_after: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::COMPRESSOR, "apply_after", &mut self.apply_after, apply_after)
+    }
+
+    #[staticmethod]
+    fn schema(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+        schema::to_dict(py, &schema::COMPRESSOR)
+    }
+
+    fn as_dict<'py>(&self, py: Python<'py>) -> Bound<'py, PyDict> {
+        self.clone().into_py_dict_bound(py)
+    }
+}
+
+schema::validate!(
+    Compressor,
+    schema::COMPRESSOR,
+    [threshold, ratio, attack, release, knee, makeup, apply_after]
+);
+
+impl fmt::Display for Compressor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Compressor {{ threshold: {}, ratio: {}, attack: {}, release: {}, knee: {}, makeup: {}, apply_after: {:?} }}",
+            self.threshold, self.ratio, self.attack, self.release, self.knee, self.makeup, self.apply_after)
+    }
+}
+
+impl IntoPyDict for Compressor {
+    fn into_py_dict_bound(self, py: Python<'_>) -> Bound<'_, PyDict> {
+        let value = serde_json::to_value(self).unwrap();
+        let dict: Bound<'_, PyDict> = json_to_py(py, &value).extract().unwrap();
+        dict.set_item("type", "Compressor").unwrap();
+        dict
+    }
+}
+
+/// A peak limiter. Its settings are shared with the sinks it is applied to, so they can be changed
+/// during playback.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[pyclass]
+pub struct Limiter {
+    #[pyo3(get)]
+    pub threshold: Setting,
+    #[pyo3(get)]
+    pub release: Setting,
+    #[pyo3(get)]
+    pub apply_after: Option<f32>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub anchor: Option<Anchor>,
+    #[serde(skip)]
+    pub meter: Meter,
+}
+
+#[pymethods]
+impl Limiter {
+    #[new]
+    #[pyo3(signature = (threshold=-1.0, release=0.05, apply_after=None, anchor=None))]
+    pub fn new(
+        threshold: f32,
+        release: f32,
+        apply_after: Option<f32>,
+        anchor: Option<Anchor>,
+    ) -> PyResult<Self> {
+        let limiter = Limiter {
+            threshold: threshold.into(),
+            release: release.into(),
+            apply_after,
+            anchor,
+            meter: Meter::default(),
+        };
+        limiter.validate()?;
+        Ok(limiter)
+    }
+
+    /// Current gain reduction in dB.
+    #[getter]
+    fn gain_reduction(&self) -> f32 {
+        self.meter.get()
+    }
+
+    #[setter]
+    fn set_threshold(&mut self, threshold: f32) -> PyResult<()> {
+        schema::update(&schema::LIMITER, "threshold", &mut self.threshold, threshold)
+    }
+
+    #[setter]
+    fn set_release(&mut self, release: f32) -> PyResult<()> {
+        schema::update(&schema::LIMITER, "release", &mut self.release, release)
+    }
+
+    #[setter]
+    fn set_apply_after(&mut self, apply_after: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::LIMITER, "apply_after", &mut self.apply_after, apply_after)
+    }
+
+    #[staticmethod]
+    fn schema(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+        schema::to_dict(py, &schema::LIMITER)
+    }
+
+    fn as_dict<'py>(&self, py: Python<'py>) -> Bound<'py, PyDict> {
+        self.clone().into_py_dict_bound(py)
+    }
+}
+
+schema::validate!(Limiter, schema::LIMITER, [threshold, release, apply_after]);
+
+impl fmt::Display for Limiter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Limiter {{ threshold: {}, release: {}, apply_after: {:?} }}",
+            self.threshold, self.release, self.apply_after)
+    }
+}
+
+impl IntoPyDict for Limiter {
+    fn into_py_dict_bound(self, py: Python<'_>) -> Bound<'_, PyDict> {
+        let value = serde_json::to_value(self).unwrap();
+        let dict: Bound<'_, PyDict> = json_to_py(py, &value).extract().unwrap();
+        dict.set_item("type", "Limiter").unwrap();
+        dict
+    }
+}
+
+/// A noise gate. Its settings are shared with the sinks it is applied to, so they can be changed
+/// during playback.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[pyclass]
+pub struct NoiseGate {
+    #[pyo3(get)]
+    pub threshold: Setting,
+    #[pyo3(get)]
+    pub attack: Setting,
+    #[pyo3(get)]
+    pub release: Setting,
+    #[pyo3(get)]
+    pub range: Setting,
+    #[pyo3(get)]
+    pub apply_after: Option<f32>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub anchor: Option<Anchor>,
+    #[serde(skip)]
+    pub meter: Meter,
+}
+
+#[pymethods]
+impl NoiseGate {
+    #[new]
+    #[pyo3(signature = (threshold=-50.0, attack=0.002, release=0.1, range=80.0, apply_after=None, anchor=None))]
+    pub fn new(
+        threshold: f32,
+        attack: f32,
+        release: f32,
+        range: f32,
+        apply_after: Option<f32>,
+        anchor: Option<Anchor>,
+    ) -> PyResult<Self> {
+        let gate = NoiseGate {
+            threshold: threshold.into(),
+            attack: attack.into(),
+            release: release.into(),
+            range: range.into(),
+            apply_after,
+            anchor,
+            meter: Meter::default(),
+        };
+        gate.validate()?;
+        Ok(gate)
+    }
+
+    /// Current gain reduction in dB.
+    #[getter]
+    fn gain_reduction(&self) -> f32 {
+        self.meter.get()
+    }
+
+    #[setter]
+    fn set_threshold(&mut self, threshold: f32) -> PyResult<()> {
+        schema::update(&schema::NOISE_GATE, "threshold", &mut self.threshold, threshold)
+    }
+
+    #[setter]
+    fn set_attack(&mut self, attack: f32) -> PyResult<()> {
+        schema::update(&schema::NOISE_GATE, "attack", &mut self.attack, attack)
+    }
+
+    #[setter]
+    fn set_release(&mut self, release: f32) -> PyResult<()> {
+        schema::update(&schema::NOISE_GATE, "release", &mut self.release, release)
+    }
+
+    #[setter]
+    fn set_range(&mut self, range: f32) -> PyResult<()> {
+        schema::update(&schema::NOISE_GATE, "range", &mut self.range, range)
+    }
+
+    #[setter]
+    fn set_apply_after(&mut self, apply_after: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::NOISE_GATE, "apply_after", &mut self.apply_after, apply_after)
+    }
+
+    #[staticmethod]
+    fn schema(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+        schema::to_dict(py, &schema::NOISE_GATE)
+    }
+
+    fn as_dict<'py>(&self, py: Python<'py>) -> Bound<'py, PyDict> {
+        self.clone().into_py_dict_bound(py)
+    }
+}
+
+schema::validate!(NoiseGate, schema::NOISE_GATE, [threshold, attack, release, range, apply_after]);
+
+impl fmt::Display for NoiseGate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "NoiseGate {{ threshold: {}, attack: {}, release: {}, range: {}, apply_after: {:?} }}",
+            self.threshold, self.attack, self.release, self.range, self.apply_after)
+    }
+}
+
+impl IntoPyDict for NoiseGate {
+    fn into_py_dict_bound(self, py: Python<'_>) -> Bound<'_, PyDict> {
+        let value = serde_json::to_value(self).unwrap();
+        let dict: Bound<'_, PyDict> = json_to_py(py, &value).extract().unwrap();
+        dict.set_item("type", "NoiseGate").unwrap();
+        dict
+    }
+}
+
+/// A waveshaping distortion. Drive and amount are shared with the sinks the distortion is applied
+/// to, so they can be changed during playback; an amount sweep runs to completion before a changed
+/// amount is heard. The waveshaper is fixed once applied.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[pyclass]
+pub struct Distortion {
+    #[pyo3(get, set)]
+    pub distortion_type: DistortionType,
+    #[pyo3(get)]
+    pub drive: Setting,
+    #[pyo3(get)]
+    pub amount: Setting,
+    #[pyo3(get)]
+    pub start_amount: Option<f32>,
+    #[pyo3(get)]
+    pub duration: Option<f32>,
+    #[pyo3(get)]
+    pub apply_after: Option<f32>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub anchor: Option<Anchor>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub curve: Easing,
+}
+
+#[pymethods]
+impl Distortion {
+    #[new]
+    #[pyo3(signature = (distortion_type=DistortionType::SoftClip, drive=12.0, amount=1.0, start_amount=None, duration=None, apply_after=None, anchor=None, curve=Easing::default()))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        distortion_type: DistortionType,
+        drive: f32,
+        amount: f32,
+        start_amount: Option<f32>,
+        duration: Option<f32>,
+        apply_after: Option<f32>,
+        anchor: Option<Anchor>,
+        curve: Easing,
+    ) -> PyResult<Self> {
+        let distortion = Distortion {
+            distortion_type,
+            drive: drive.into(),
+            amount: amount.into(),
+            start_amount,
+            duration,
+            apply_after,
+            anchor,
+            curve,
+        };
+        distortion.validate()?;
+        Ok(distortion)
+    }
+
+    #[setter]
+    fn set_drive(&mut self, drive: f32) -> PyResult<()> {
+        schema::update(&schema::DISTORTION, "drive", &mut self.drive, drive)
+    }
+
+    #[setter]
+    fn set_amount(&mut self, amount: f32) -> PyResult<()> {
+        schema::update(&schema::DISTORTION, "amount", &mut self.amount, amount)
+    }
+
+    #[setter]
+    fn set_start_amount(&mut self, start_amount: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::DISTORTION, "start_amount", &mut self.start_amount, start_amount)
+    }
+
+    #[setter]
+    fn set_duration(&mut self, duration: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::DISTORTION, "duration", &mut self.duration, duration)
+    }
+
+    #[setter]
+    fn set_apply_after(&mut self, apply_after: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::DISTORTION, "apply_after", &mut self.apply_after, apply_after)
+    }
+
+    #[staticmethod]
+    fn schema(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+        schema::to_dict(py, &schema::DISTORTION)
+    }
+
+    fn as_dict<'py>(&self, py: Python<'py>) -> Bound<'py, PyDict> {
+        self.clone().into_py_dict_bound(py)
+    }
+}
+
+schema::validate!(
+    Distortion,
+    schema::DISTORTION,
+    [drive, amount, start_amount, duration, apply_after]
+);
+
+impl fmt::Display for Distortion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Distortion {{ distortion_type: {:?}, drive: {}, amount: {}, start_amount: {:?}, duration: {:?}, apply_after: {:?}, curve: {:?} }}",
+            self.distortion_type, self.drive, self.amount, self.start_amount, self.duration, self.apply_after, self.curve)
+    }
+}
+
+impl IntoPyDict for Distortion {
+    fn into_py_dict_bound(self, py: Python<'_>) -> Bound<'_, PyDict> {
+        let value = serde_json::to_value(self).unwrap();
+        let dict: Bound<'_, PyDict> = json_to_py(py, &value).extract().unwrap();
+        dict.set_item("type", "Distortion").unwrap();
+        dict
+    }
+}
+
+/// Bit depth and sample rate reduction. Its settings are shared with the sinks the bitcrusher is
+/// applied to, so they can be changed during playback; an amount sweep runs to completion before a
+/// changed amount is heard.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[pyclass]
+pub struct Bitcrusher {
+    #[pyo3(get)]
+    pub bits: Setting,
+    #[pyo3(get)]
+    pub sample_rate: Setting,
+    #[pyo3(get)]
+    pub amount: Setting,
+    #[pyo3(get)]
+    pub start_amount: Option<f32>,
+    #[pyo3(get)]
+    pub duration: Option<f32>,
+    #[pyo3(get)]
+    pub apply_after: Option<f32>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub anchor: Option<Anchor>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub curve: Easing,
+}
+
+#[pymethods]
+impl Bitcrusher {
+    #[new]
+    #[pyo3(signature = (bits=8.0, sample_rate=11025.0, amount=1.0, start_amount=None, duration=None, apply_after=None, anchor=None, curve=Easing::default()))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        bits: f32,
+        sample_rate: f32,
+        amount: f32,
+        start_amount: Option<f32>,
+        duration: Option<f32>,
+        apply_after: Option<f32>,
+        anchor: Option<Anchor>,
+        curve: Easing,
+    ) -> PyResult<Self> {
+        let bitcrusher = Bitcrusher {
+            bits: bits.into(),
+            sample_rate: sample_rate.into(),
+            amount: amount.into(),
+            start_amount,
+            duration,
+            apply_after,
+            anchor,
+            curve,
+        };
+        bitcrusher.validate()?;
+        Ok(bitcrusher)
+    }
+
+    #[setter]
+    fn set_bits(&mut self, bits: f32) -> PyResult<()> {
+        schema::update(&schema::BITCRUSHER, "bits", &mut self.bits, bits)
+    }
+
+    #[setter]
+    fn set_sample_rate(&mut self, sample_rate: f32) -> PyResult<()> {
+        schema::update(&schema::BITCRUSHER, "sample_rate", &mut self.sample_rate, sample_rate)
+    }
+
+    #[setter]
+    fn set_amount(&mut self, amount: f32) -> PyResult<()> {
+        schema::update(&schema::BITCRUSHER, "amount", &mut self.amount, amount)
+    }
+
+    #[setter]
+    fn set_start_amount(&mut self, start_amount: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::BITCRUSHER, "start_amount", &mut self.start_amount, start_amount)
+    }
+
+    #[setter]
+    fn set_duration(&mut self, duration: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::BITCRUSHER, "duration", &mut self.duration, duration)
+    }
+
+    #[setter]
+    fn set_apply_after(&mut self, apply_after: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::BITCRUSHER, "apply_after", &mut self.apply_after, apply_after)
+    }
+
+    #[staticmethod]
+    fn schema(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+        schema::to_dict(py, &schema::BITCRUSHER)
+    }
+
+    fn as_dict<'py>(&self, py: Python<'py>) -> Bound<'py, PyDict> {
+        self.clone().into_py_dict_bound(py)
+    }
+}
+
+schema::validate!(
+    Bitcrusher,
+    schema::BITCRUSHER,
+    [bits, sample_rate, amount, start_amount, duration, apply_after]
+);
+
+impl fmt::Display for Bitcrusher {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Bitcrusher {{ bits: {}, sample_rate: {}, amount: {}, start_amount: {:?}, duration: {:?}, apply_after: {:?}, curve: {:?} }}",
+            self.bits, self.sample_rate, self.amount, self.start_amount, self.duration, self.apply_after, self.curve)
+    }
+}
+
+impl IntoPyDict for Bitcrusher {
+    fn into_py_dict_bound(self, py: Python<'_>) -> Bound<'_, PyDict> {
+        let value = serde_json::to_value(self).unwrap();
+        let dict: Bound<'_, PyDict> = json_to_py(py, &value).extract().unwrap();
+        dict.set_item("type", "Bitcrusher").unwrap();
+        dict
+    }
+}
+
+/// A chorus. Its settings are shared with the sinks it is applied to, so they can be changed during
+/// playback.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[pyclass]
+pub struct Chorus {
+    #[pyo3(get)]
+    pub rate: Setting,
+    #[pyo3(get)]
+    pub depth: Setting,
+    #[pyo3(get)]
+    pub feedback: Setting,
+    #[pyo3(get)]
+    pub mix: Setting,
+    #[pyo3(get)]
+    pub apply_after: Option<f32>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub anchor: Option<Anchor>,
+}
+
+#[pymethods]
+impl Chorus {
+    #[new]
+    #[pyo3(signature = (rate=0.8, depth=0.5, feedback=0.0, mix=0.5, apply_after=None, anchor=None))]
+    pub fn new(
+        rate: f32,
+        depth: f32,
+        feedback: f32,
+        mix: f32,
+        apply_after: Option<f32>,
+        anchor: Option<Anchor>,
+    ) -> PyResult<Self> {
+        let chorus = Chorus {
+            rate: rate.into(),
+            depth: depth.into(),
+            feedback: feedback.into(),
+            mix: mix.into(),
+            apply_after,
+            anchor,
+        };
+        chorus.validate()?;
+        Ok(chorus)
+    }
+
+    #[setter]
+    fn set_rate(&mut self, rate: f32) -> PyResult<()> {
+        schema::update(&schema::CHORUS, "rate", &mut self.rate, rate)
+    }
+
+    #[setter]
+    fn set_depth(&mut self, depth: f32) -> PyResult<()> {
+        schema::update(&schema::CHORUS, "depth", &mut self.depth, depth)
+    }
+
+    #[setter]
+    fn set_feedback(&mut self, feedback: f32) -> PyResult<()> {
+        schema::update(&schema::CHORUS, "feedback", &mut self.feedback, feedback)
+    }
+
+    #[setter]
+    fn set_mix(&mut self, mix: f32) -> PyResult<()> {
+        schema::update(&schema::CHORUS, "mix", &mut self.mix, mix)
+    }
+
+    #[setter]
+    fn set_apply_after(&mut self, apply_after: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::CHORUS, "apply_after", &mut self.apply_after, apply_after)
+    }
+
+    #[staticmethod]
+    fn schema(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+        schema::to_dict(py, &schema::CHORUS)
+    }
+
+    fn as_dict<'py>(&self, py: Python<'py>) -> Bound<'py, PyDict> {
+        self.clone().into_py_dict_bound(py)
+    }
+}
+
+schema::validate!(Chorus, schema::CHORUS, [rate, depth, feedback, mix, apply_after]);
+
+impl fmt::Display for Chorus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Chorus {{ rate: {}, depth: {}, feedback: {}, mix: {}, apply_after: {:?} }}",
+            self.rate, self.depth, self.feedback, self.mix, self.apply_after)
+    }
+}
+
+impl IntoPyDict for Chorus {
+    fn into_py_dict_bound(self, py: Python<'_>) -> Bound<'_, PyDict> {
+        let value = serde_json::to_value(self).unwrap();
+        let dict: Bound<'_, PyDict> = json_to_py(py, &value).extract().unwrap();
+        dict.set_item("type", "Chorus").unwrap();
+        dict
+    }
+}
+
+/// A flanger. Its settings are shared with the sinks it is applied to, so they can be changed during
+/// playback.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[pyclass]
+pub struct Flanger {
+    #[pyo3(get)]
+    pub rate: Setting,
+    #[pyo3(get)]
+    pub depth: Setting,
+    #[pyo3(get)]
+    pub feedback: Setting,
+    #[pyo3(get)]
+    pub mix: Setting,
+    #[pyo3(get)]
+    pub apply_after: Option<f32>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub anchor: Option<Anchor>,
+}
+
+#[pymethods]
+impl Flanger {
+    #[new]
+    #[pyo3(signature = (rate=0.25, depth=0.7, feedback=0.5, mix=0.5, apply_after=None, anchor=None))]
+    pub fn new(
+        rate: f32,
+        depth: f32,
+        feedback: f32,
+        mix: f32,
+        apply_after: Option<f32>,
+        anchor: Option<Anchor>,
+    ) -> PyResult<Self> {
+        let flanger = Flanger {
+            rate: rate.into(),
+            depth: depth.into(),
+            feedback: feedback.into(),
+            mix: mix.into(),
+            apply_after,
+            anchor,
+        };
+        flanger.validate()?;
+        Ok(flanger)
+    }
+
+    #[setter]
+    fn set_rate(&mut self, rate: f32) -> PyResult<()> {
+        schema::update(&schema::FLANGER, "rate", &mut self.rate, rate)
+    }
+
+    #[setter]
+    fn set_depth(&mut self, depth: f32) -> PyResult<()> {
+        schema::update(&schema::FLANGER, "depth", &mut self.depth, depth)
+    }
+
+    #[setter]
+    fn set_feedback(&mut self, feedback: f32) -> PyResult<()> {
+        schema::update(&schema::FLANGER, "feedback", &mut self.feedback, feedback)
+    }
+
+    #[setter]
+    fn set_mix(&mut self, mix: f32) -> PyResult<()> {
+        schema::update(&schema::FLANGER, "mix", &mut self.mix, mix)
+    }
+
+    #[setter]
+    fn set_apply_after(&mut self, apply_after: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::FLANGER, "apply_after", &mut self.apply_after, apply_after)
+    }
+
+    #[staticmethod]
+    fn schema(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+        schema::to_dict(py, &schema::FLANGER)
+    }
+
+    fn as_dict<'py>(&self, py: Python<'py>) -> Bound<'py, PyDict> {
+        self.clone().into_py_dict_bound(py)
+    }
+}
+
+schema::validate!(Flanger, schema::FLANGER, [rate, depth, feedback, mix, apply_after]);
+
+impl fmt::Display for Flanger {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Flanger {{ rate: {}, depth: {}, feedback: {}, mix: {}, apply_after: {:?} }}",
+            self.rate, self.depth, self.feedback, self.mix, self.apply_after)
+    }
+}
+
+impl IntoPyDict for Flanger {
+    fn into_py_dict_bound(self, py: Python<'_>) -> Bound<'_, PyDict> {
+        let value = serde_json::to_value(self).unwrap();
+        let dict: Bound<'_, PyDict> = json_to_py(py, &value).extract().unwrap();
+        dict.set_item("type", "Flanger").unwrap();
+        dict
+    }
+}
+
+/// A phaser. Rate, depth, feedback and mix are shared with the sinks it is applied to, so they can
+/// be changed during playback; the number of stages is fixed once applied.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[pyclass]
+pub struct Phaser {
+    #[pyo3(get)]
+    pub rate: Setting,
+    #[pyo3(get)]
+    pub depth: Setting,
+    #[pyo3(get)]
+    pub feedback: Setting,
+    #[pyo3(get)]
+    pub mix: Setting,
+    #[pyo3(get)]
+    pub stages: usize,
+    #[pyo3(get)]
+    pub apply_after: Option<f32>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub anchor: Option<Anchor>,
+}
+
+#[pymethods]
+impl Phaser {
+    #[new]
+    #[pyo3(signature = (rate=0.5, depth=0.7, feedback=0.3, mix=0.5, stages=4, apply_after=None, anchor=None))]
+    pub fn new(
+        rate: f32,
+        depth: f32,
+        feedback: f32,
+        mix: f32,
+        stages: usize,
+        apply_after: Option<f32>,
+        anchor: Option<Anchor>,
+    ) -> PyResult<Self> {
+        let phaser = Phaser {
+            rate: rate.into(),
+            depth: depth.into(),
+            feedback: feedback.into(),
+            mix: mix.into(),
+            stages,
+            apply_after,
+            anchor,
+        };
+        phaser.validate()?;
+        Ok(phaser)
+    }
+
+    #[setter]
+    fn set_rate(&mut self, rate: f32) -> PyResult<()> {
+        schema::update(&schema::PHASER, "rate", &mut self.rate, rate)
+    }
+
+    #[setter]
+    fn set_depth(&mut self, depth: f32) -> PyResult<()> {
+        schema::update(&schema::PHASER, "depth", &mut self.depth, depth)
+    }
+
+    #[setter]
+    fn set_feedback(&mut self, feedback: f32) -> PyResult<()> {
+        schema::update(&schema::PHASER, "feedback", &mut self.feedback, feedback)
+    }
+
+    #[setter]
+    fn set_mix(&mut self, mix: f32) -> PyResult<()> {
+        schema::update(&schema::PHASER, "mix", &mut self.mix, mix)
+    }
+
+    #[setter]
+    fn set_apply_after(&mut self, apply_after: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::PHASER, "apply_after", &mut self.apply_after, apply_after)
+    }
+
+    #[staticmethod]
+    fn schema(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+        schema::to_dict(py, &schema::PHASER)
+    }
+
+    fn as_dict<'py>(&self, py: Python<'py>) -> Bound<'py, PyDict> {
+        self.clone().into_py_dict_bound(py)
+    }
+}
+
+schema::validate!(Phaser, schema::PHASER, [rate, depth, feedback, mix, stages, apply_after]);
+
+impl fmt::Display for Phaser {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Phaser {{ rate: {}, depth: {}, feedback: {}, mix: {}, stages: {}, apply_after: {:?} }}",
+            self.rate, self.depth, self.feedback, self.mix, self.stages, self.apply_after)
+    }
+}
+
+impl IntoPyDict for Phaser {
+    fn into_py_dict_bound(self, py: Python<'_>) -> Bound<'_, PyDict> {
+        let value = serde_json::to_value(self).unwrap();
+        let dict: Bound<'_, PyDict> = json_to_py(py, &value).extract().unwrap();
+        dict.set_item("type", "Phaser").unwrap();
+        dict
+    }
+}
+
+/// Volume modulation. Rate and depth are shared with the sinks the tremolo is applied to, so they
+/// can be changed during playback; the waveform is fixed once applied.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[pyclass]
+pub struct Tremolo {
+    #[pyo3(get)]
+    pub rate: Setting,
+    #[pyo3(get)]
+    pub depth: Setting,
+    #[pyo3(get, set)]
+    pub waveform: Waveform,
+    #[pyo3(get)]
+    pub duration: Option<f32>,
+    #[pyo3(get)]
+    pub apply_after: Option<f32>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub anchor: Option<Anchor>,
+}
+
+#[pymethods]
+impl Tremolo {
+    #[new]
+    #[pyo3(signature = (rate=5.0, depth=0.5, waveform=Waveform::Sine, duration=None, apply_after=None, anchor=None))]
+    pub fn new(
+        rate: f32,
+        depth: f32,
+        waveform: Waveform,
+        duration: Option<f32>,
+        apply_after: Option<f32>,
+        anchor: Option<Anchor>,
+    ) -> PyResult<Self> {
+        let tremolo = Tremolo {
+            rate: rate.into(),
+            depth: depth.into(),
+            waveform,
+            duration,
+            apply_after,
+            anchor,
+        };
+        tremolo.validate()?;
+        Ok(tremolo)
+    }
+
+    #[setter]
+    fn set_rate(&mut self, rate: f32) -> PyResult<()> {
+        schema::update(&schema::TREMOLO, "rate", &mut self.rate, rate)
+    }
+
+    #[setter]
+    fn set_depth(&mut self, depth: f32) -> PyResult<()> {
+        schema::update(&schema::TREMOLO, "depth", &mut self.depth, depth)
+    }
+
+    #[setter]
+    fn set_duration(&mut self, duration: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::TREMOLO, "duration", &mut self.duration, duration)
+    }
+
+    #[setter]
+    fn set_apply_after(&mut self, apply_after: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::TREMOLO, "apply_after", &mut self.apply_after, apply_after)
+    }
+
+    #[staticmethod]
+    fn schema(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+        schema::to_dict(py, &schema::TREMOLO)
+    }
+
+    fn as_dict<'py>(&self, py: Python<'py>) -> Bound<'py, PyDict> {
+        self.clone().into_py_dict_bound(py)
+    }
+}
+
+schema::validate!(Tremolo, schema::TREMOLO, [rate, depth, duration, apply_after]);
+
+impl fmt::Display for Tremolo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Tremolo {{ rate: {}, depth: {}, waveform: {:?}, duration: {:?}, apply_after: {:?} }}",
+            self.rate, self.depth, self.waveform, self.duration, self.apply_after)
+    }
+}
+
+impl IntoPyDict for Tremolo {
+    fn into_py_dict_bound(self, py: Python<'_>) -> Bound<'_, PyDict> {
+        let value = serde_json::to_value(self).unwrap();
+        let dict: Bound<'_, PyDict> = json_to_py(py, &value).extract().unwrap();
+        dict.set_item("type", "Tremolo").unwrap();
+        dict
+    }
+}
+
+/// Pitch modulation. Rate and depth are shared with the sinks the vibrato is applied to, so they
+/// can be changed during playback; the waveform is fixed once applied.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[pyclass]
+pub struct Vibrato {
+    #[pyo3(get)]
+    pub rate: Setting,
+    #[pyo3(get)]
+    pub depth: Setting,
+    #[pyo3(get, set)]
+    pub waveform: Waveform,
+    #[pyo3(get)]
+    pub duration: Option<f32>,
+    #[pyo3(get)]
+    pub apply_after: Option<f32>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub anchor: Option<Anchor>,
+}
+
+#[pymethods]
+impl Vibrato {
+    #[new]
+    #[pyo3(signature = (rate=5.0, depth=0.3, waveform=Waveform::Sine, duration=None, apply_after=None, anchor=None))]
+    pub fn new(
+        rate: f32,
+        depth: f32,
+        waveform: Waveform,
+        duration: Option<f32>,
+        apply_after: Option<f32>,
+        anchor: Option<Anchor>,
+    ) -> PyResult<Self> {
+        let vibrato = Vibrato {
+            rate: rate.into(),
+            depth: depth.into(),
+            waveform,
+            duration,
+            apply_after,
+            anchor,
+        };
+        vibrato.validate()?;
+        Ok(vibrato)
+    }
+
+    #[setter]
+    fn set_rate(&mut self, rate: f32) -> PyResult<()> {
+        schema::update(&schema::VIBRATO, "rate", &mut self.rate, rate)
+    }
+
+    #[setter]
+    fn set_depth(&mut self, depth: f32) -> PyResult<()> {
+        schema::update(&schema::VIBRATO, "depth", &mut self.depth, depth)
+    }
+
+    #[setter]
+    fn set_duration(&mut self, duration: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::VIBRATO, "duration", &mut self.duration, duration)
+    }
+
+    #[setter]
+    fn set_apply_after(&mut self, apply_after: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::VIBRATO, "apply_after", &mut self.apply_after, apply_after)
+    }
+
+    #[staticmethod]
+    fn schema(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+        schema::to_dict(py, &schema::VIBRATO)
+    }
+
+    fn as_dict<'py>(&self, py: Python<'py>) -> Bound<'py, PyDict> {
+        self.clone().into_py_dict_bound(py)
+    }
+}
+
+schema::validate!(Vibrato, schema::VIBRATO, [rate, depth, duration, apply_after]);
+
+impl fmt::Display for Vibrato {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Vibrato {{ rate: {}, depth: {}, waveform: {:?}, duration: {:?}, apply_after: {:?} }}",
+            self.rate, self.depth, self.waveform, self.duration, self.apply_after)
+    }
+}
+
+impl IntoPyDict for Vibrato {
+    fn into_py_dict_bound(self, py: Python<'_>) -> Bound<'_, PyDict> {
+        let value = serde_json::to_value(self).unwrap();
+        let dict: Bound<'_, PyDict> = json_to_py(py, &value).extract().unwrap();
+        dict.set_item("type", "Vibrato").unwrap();
+        dict
+    }
+}
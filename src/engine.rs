@@ -0,0 +1,1770 @@
+use crate::dsp::{
+    bitcrusher, delay, distortion, dynamics, eq, filter, lfo, modulation, reverb,
+    BitcrusherProcessor, DelayProcessor, DistortionProcessor, DynamicsMode, DynamicsProcessor,
+    EqProcessor, FilterProcessor, FilterType, Meter, ModulatedDelayProcessor, Params,
+    PhaserProcessor, Processor, ReverbProcessor, Setting, TremoloProcessor, VibratoProcessor,
+    BUTTERWORTH_Q, CHORUS_SWEEP, FLANGER_SWEEP,
+};
+use crate::effects::{Tremolo, Vibrato};
+use crate::exceptions::EffectConflictException;
+use crate::pyeffect::{self, PythonProcessor};
+use crate::output::{Output, OutputConfig, OutputFormat, OutputKey};
+use crate::player::{Player, PlayerControls, ProcessorId};
+use crate::timesync::{
+    ActionType, AnchorKind, AutomationTarget, ConflictPolicy, EffectResult, EffectState, EffectSync,
+};
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use rodio::Source;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const TICK: Duration = Duration::from_millis(3);
+const SEEK_TIMEOUT: Duration = Duration::from_secs(1);
+const SCHEDULE_LOOKAHEAD: Duration = Duration::from_millis(50);
+
+pub type VoiceId = u64;
+pub type EventId = u64;
+pub type EffectId = u64;
+pub type BoxedSource = Box<dyn Source<Item = f32> + Send>;
+pub type Callback = Arc<Option<Py<PyAny>>>;
+/// An effect on a voice and the status its handles watch.
+pub type TrackedEffect = (EffectSync, Arc<EffectStatus>);
+
+static NEXT_VOICE_ID: AtomicU64 = AtomicU64::new(1);
+static NEXT_EVENT_ID: AtomicU64 = AtomicU64::new(1);
+static NEXT_EFFECT_ID: AtomicU64 = AtomicU64::new(1);
+static ENGINE: OnceLock<Sender<EngineCommand>> = OnceLock::new();
+
+pub fn next_voice_id() -> VoiceId {
+    NEXT_VOICE_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Playback state published by the audio thread so handles can read it without a round trip.
+#[derive(Debug)]
+pub struct VoiceStatus {
+    loaded: AtomicBool,
+    is_playing: AtomicBool,
+    empty: AtomicBool,
+    position: AtomicU64,
+    volume: AtomicU32,
+    speed: AtomicU32,
+}
+
+impl Default for VoiceStatus {
+    fn default() -> Self {
+        VoiceStatus {
+            loaded: AtomicBool::new(false),
+            is_playing: AtomicBool::new(false),
+            empty: AtomicBool::new(false),
+            position: AtomicU64::new(0f64.to_bits()),
+            volume: AtomicU32::new(1f32.to_bits()),
+            speed: AtomicU32::new(1f32.to_bits()),
+        }
+    }
+}
+
+impl VoiceStatus {
+    pub fn loaded(&self) -> bool {
+        self.loaded.load(Ordering::SeqCst)
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.is_playing.load(Ordering::SeqCst)
+    }
+
+    pub fn empty(&self) -> bool {
+        self.empty.load(Ordering::SeqCst)
+    }
+
+    pub fn position(&self) -> f64 {
+        f64::from_bits(self.position.load(Ordering::SeqCst))
+    }
+
+    pub fn volume(&self) -> f32 {
+        f32::from_bits(self.volume.load(Ordering::SeqCst))
+    }
+
+    pub fn speed(&self) -> f32 {
+        f32::from_bits(self.speed.load(Ordering::SeqCst))
+    }
+
+    fn set_playing(&self, is_playing: bool) {
+        self.is_playing.store(is_playing, Ordering::SeqCst);
+    }
+
+    fn publish(&self, controls: &PlayerControls) {
+        self.empty.store(controls.finished(), Ordering::SeqCst);
+        self.position
+            .store(controls.position().to_bits(), Ordering::SeqCst);
+        self.volume
+            .store(controls.volume().to_bits(), Ordering::SeqCst);
+        self.speed.store(controls.speed().to_bits(), Ordering::SeqCst);
+    }
+
+    fn reset(&self) {
+        self.loaded.store(false, Ordering::SeqCst);
+        self.is_playing.store(false, Ordering::SeqCst);
+        self.empty.store(false, Ordering::SeqCst);
+        self.position.store(0f64.to_bits(), Ordering::SeqCst);
+    }
+}
+
+/// State of one effect published by the audio thread for its `EffectHandle`.
+#[derive(Debug)]
+pub struct EffectStatus {
+    id: EffectId,
+    state: AtomicU32,
+    progress: AtomicU32,
+    value: AtomicU32,
+}
+
+impl EffectStatus {
+    pub fn new() -> Arc<Self> {
+        Arc::new(EffectStatus {
+            id: NEXT_EFFECT_ID.fetch_add(1, Ordering::Relaxed),
+            state: AtomicU32::new(EffectState::Pending as u32),
+            progress: AtomicU32::new(0f32.to_bits()),
+            value: AtomicU32::new(0f32.to_bits()),
+        })
+    }
+
+    pub fn id(&self) -> EffectId {
+        self.id
+    }
+
+    pub fn state(&self) -> EffectState {
+        match self.state.load(Ordering::SeqCst) {
+            0 => EffectState::Pending,
+            1 => EffectState::Active,
+            2 => EffectState::Paused,
+            _ => EffectState::Done,
+        }
+    }
+
+    pub fn progress(&self) -> f32 {
+        f32::from_bits(self.progress.load(Ordering::SeqCst))
+    }
+
+    pub fn value(&self) -> f32 {
+        f32::from_bits(self.value.load(Ordering::SeqCst))
+    }
+
+    fn set_state(&self, state: EffectState) {
+        self.state.store(state as u32, Ordering::SeqCst);
+    }
+
+    fn publish(&self, state: EffectState, progress: f32, value: f32) {
+        self.progress.store(progress.to_bits(), Ordering::SeqCst);
+        self.value.store(value.to_bits(), Ordering::SeqCst);
+        self.set_state(state);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EffectControl {
+    Cancel,
+    Pause,
+    Resume,
+    Extend(f32),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScheduledAction {
+    Play,
+    Stop,
+}
+
+/// A transport change waiting for its time. It is turned into an output frame once it is
+/// close enough for the estimate to be accurate, and fires when the output reaches that frame.
+struct ScheduledEvent {
+    id: EventId,
+    ids: Vec<VoiceId>,
+    at: Instant,
+    action: ScheduledAction,
+    frame: Option<u64>,
+    armed: Vec<VoiceId>,
+    pending: Arc<AtomicBool>,
+}
+
+pub struct LoadRequest {
+    pub status: Arc<VoiceStatus>,
+    pub callback: Callback,
+    pub cancel_callback: Arc<AtomicBool>,
+    pub source: BoxedSource,
+    pub volume: f32,
+    pub policy: ConflictPolicy,
+    pub output: OutputConfig,
+}
+
+pub enum EngineCommand {
+    Load {
+        id: VoiceId,
+        request: LoadRequest,
+        reply: Sender<PyResult<()>>,
+    },
+    Unload {
+        id: VoiceId,
+        reply: Sender<PyResult<()>>,
+    },
+    Play {
+        ids: Vec<VoiceId>,
+        reply: Sender<PyResult<()>>,
+    },
+    Pause {
+        ids: Vec<VoiceId>,
+        reply: Sender<PyResult<()>>,
+    },
+    Stop {
+        ids: Vec<VoiceId>,
+        reply: Sender<PyResult<()>>,
+    },
+    SetVolume {
+        id: VoiceId,
+        volume: f32,
+        reply: Sender<PyResult<()>>,
+    },
+    SetConflictPolicy {
+        id: VoiceId,
+        policy: ConflictPolicy,
+        reply: Sender<PyResult<()>>,
+    },
+    SetSpeed {
+        ids: Vec<VoiceId>,
+        speed: f32,
+        reply: Sender<PyResult<()>>,
+    },
+    Seek {
+        ids: Vec<VoiceId>,
+        position: f32,
+        reply: Sender<PyResult<()>>,
+    },
+    SetEffectsChain {
+        id: VoiceId,
+        effects: Vec<ActionType>,
+        reply: Sender<PyResult<()>>,
+    },
+    PushEffects {
+        id: VoiceId,
+        effects: Vec<(ActionType, Arc<EffectStatus>)>,
+        duration: Option<f32>,
+        reply: Sender<PyResult<()>>,
+    },
+    RestoreEffects {
+        id: VoiceId,
+        effects: Vec<(EffectSync, f32, Arc<EffectStatus>)>,
+        reply: Sender<PyResult<()>>,
+    },
+    ControlEffect {
+        id: VoiceId,
+        effect: EffectId,
+        control: EffectControl,
+        reply: Sender<PyResult<bool>>,
+    },
+    Effects {
+        id: VoiceId,
+        reply: Sender<PyResult<Vec<TrackedEffect>>>,
+    },
+    OutputFormat {
+        config: OutputConfig,
+        reply: Sender<PyResult<OutputFormat>>,
+    },
+    Schedule {
+        ids: Vec<VoiceId>,
+        at: Instant,
+        action: ScheduledAction,
+        pending: Arc<AtomicBool>,
+        reply: Sender<PyResult<EventId>>,
+    },
+    CancelScheduled {
+        event: EventId,
+        reply: Sender<PyResult<bool>>,
+    },
+}
+
+/// How a caller waits for the audio thread's reply.
+pub trait Wait: Copy {
+    fn wait<T: Send>(self, response: Receiver<PyResult<T>>) -> PyResult<T>;
+}
+
+/// Python threads release the GIL while they wait, so the callback thread and the Python
+/// effect workers keep running during slow commands such as seeks.
+impl Wait for Python<'_> {
+    fn wait<T: Send>(self, response: Receiver<PyResult<T>>) -> PyResult<T> {
+        self.allow_threads(|| Detached.wait(response))
+    }
+}
+
+/// Waits on a thread that does not hold the GIL, such as a channel's consumer thread.
+#[derive(Clone, Copy)]
+pub struct Detached;
+
+impl Wait for Detached {
+    fn wait<T: Send>(self, response: Receiver<PyResult<T>>) -> PyResult<T> {
+        response
+            .recv()
+            .map_err(|_| PyRuntimeError::new_err("Audio thread stopped responding."))?
+    }
+}
+
+/// Sends a command to the audio thread and waits for its reply.
+pub fn request<T, F>(wait: impl Wait, build: F) -> PyResult<T>
+where
+    T: Send,
+    F: FnOnce(Sender<PyResult<T>>) -> EngineCommand,
+{
+    let (reply, response) = mpsc::channel();
+    engine()
+        .send(build(reply))
+        .map_err(|_| PyRuntimeError::new_err("Audio thread is not running."))?;
+    wait.wait(response)
+}
+
+fn engine() -> &'static Sender<EngineCommand> {
+    ENGINE.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel::<EngineCommand>();
+        let (callback_sender, callback_receiver) = mpsc::channel::<Callback>();
+
+        thread::spawn(move || {
+            for callback in callback_receiver {
+                pyeffect::call_python(|py| {
+                    if let Some(callback) = callback.as_ref() {
+                        if let Err(e) = callback.call0(py) {
+                            eprintln!("Failed to invoke callback: {}", e);
+                        }
+                    }
+                });
+            }
+        });
+
+        thread::spawn(move || {
+            let mut engine = Engine {
+                outputs: HashMap::new(),
+                voices: HashMap::new(),
+                scheduled: Vec::new(),
+                callbacks: callback_sender,
+            };
+            let mut next_tick = Instant::now() + TICK;
+
+            loop {
+                let timeout = next_tick.saturating_duration_since(Instant::now());
+                match receiver.recv_timeout(timeout) {
+                    Ok(command) => engine.handle(command),
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+                if Instant::now() >= next_tick {
+                    engine.tick();
+                    next_tick = Instant::now() + TICK;
+                }
+            }
+        });
+
+        sender
+    })
+}
+
+fn modulation_params(
+    rate: &Setting,
+    depth: &Setting,
+    feedback: &Setting,
+    mix: &Setting,
+) -> Arc<Params> {
+    Params::shared(&[&Setting::new(0.0), rate, depth, feedback, mix])
+}
+
+fn no_voice() -> PyErr {
+    PyRuntimeError::new_err("No sink available. Load audio first.")
+}
+
+/// The value effects drive a parameter to on one tick, combined by the voice's policy.
+#[derive(Default)]
+struct Drive {
+    value: Option<f32>,
+    running: bool,
+}
+
+impl Drive {
+    /// Adds an effect's value. Unless values multiply, the newest started effect wins
+    /// regardless of the order effects are visited in.
+    fn push(&mut self, value: f32, running: bool, policy: ConflictPolicy, newest: bool) {
+        self.value = Some(match (policy, self.value) {
+            (ConflictPolicy::Multiply, Some(product)) => product * value,
+            (_, Some(current)) if !newest => current,
+            _ => value,
+        });
+        self.running |= running;
+    }
+}
+
+/// An effect on a voice, with the parameters of its processor if it runs in the sample chain.
+struct ActiveEffect {
+    sync: EffectSync,
+    params: Option<Arc<Params>>,
+    processor: Option<ProcessorId>,
+    status: Arc<EffectStatus>,
+    paused: bool,
+}
+
+impl ActiveEffect {
+    /// Switches the effect's processor, or the sampling of its automation function, on or off.
+    fn switch(&self, on: bool) {
+        let Some(params) = &self.params else {
+            return;
+        };
+        let index = match self.sync.action {
+            ActionType::Filter(_) => filter::ENABLED,
+            ActionType::ParametricEQ(_) => eq::ENABLED,
+            ActionType::Reverb(_) => reverb::ENABLED,
+            ActionType::Delay(_) => delay::ENABLED,
+            ActionType::Compressor(_) | ActionType::Limiter(_) | ActionType::NoiseGate(_) => {
+                dynamics::ENABLED
+            }
+            ActionType::Distortion(_) => distortion::ENABLED,
+            ActionType::Bitcrusher(_) => bitcrusher::ENABLED,
+            ActionType::Chorus(_) | ActionType::Flanger(_) | ActionType::Phaser(_) => {
+                modulation::ENABLED
+            }
+            ActionType::Tremolo(_) | ActionType::Vibrato(_) => lfo::ENABLED,
+            ActionType::PythonEffect(_) => pyeffect::ENABLED,
+            ActionType::Automation(_) => pyeffect::automation::ACTIVE,
+            _ => return,
+        };
+        params.set(index, if on { 1.0 } else { 0.0 });
+    }
+
+    /// Ends the effect and takes its processor out of the voice's chain.
+    fn retire(&self, controls: &PlayerControls, filters: &mut Vec<Arc<Params>>) {
+        self.switch(false);
+        if let Some(processor) = self.processor {
+            controls.remove_processor(processor);
+        }
+        if let Some(params) = &self.params {
+            filters.retain(|filter| !Arc::ptr_eq(filter, params));
+        }
+        self.status.set_state(EffectState::Done);
+    }
+}
+
+struct Voice {
+    controls: Arc<PlayerControls>,
+    output: OutputKey,
+    status: Arc<VoiceStatus>,
+    callback: Callback,
+    cancel_callback: Arc<AtomicBool>,
+    volume: f32,
+    sample_rate: u32,
+    channels: usize,
+    effects: Vec<ActiveEffect>,
+    effects_chain: Vec<ActionType>,
+    filters: Vec<Arc<Params>>,
+    initial_play: bool,
+    vol_locked: bool,
+    speed_locked: bool,
+    policy: ConflictPolicy,
+    speed: f32,
+    queued_volume: Option<f32>,
+    queued_speed: Option<f32>,
+    last_position: f32,
+    finished: bool,
+}
+
+impl Voice {
+    fn publish(&self) {
+        self.status.publish(&self.controls);
+    }
+
+    fn initial_volume(&mut self) {
+        let fade_in_apply_after: Vec<Option<f32>> = self
+            .effects_chain
+            .iter()
+            .filter_map(|effect| match effect {
+                ActionType::FadeIn(fade_in) => Some(fade_in.apply_after),
+                _ => None,
+            })
+            .collect();
+
+        let current_volume = self.controls.volume();
+        if !fade_in_apply_after.is_empty()
+            && fade_in_apply_after
+                .iter()
+                .all(|val| val.is_none() || *val == Some(0.0))
+        {
+            if current_volume != 0.0 {
+                self.controls.set_volume(0.0);
+            }
+        } else if current_volume == 0.0 {
+            self.controls.set_volume(self.volume);
+        }
+    }
+
+    fn add_filter(&mut self, filter_type: FilterType, params: Arc<Params>) -> ProcessorId {
+        let processor = self.controls.add_processor(Box::new(FilterProcessor::new(
+            filter_type,
+            params.clone(),
+            self.sample_rate,
+            self.channels,
+        )));
+        self.filters.push(params);
+        processor
+    }
+
+    fn add_effect(&mut self, sync: EffectSync, status: Arc<EffectStatus>) {
+        let (sample_rate, channels) = (self.sample_rate, self.channels);
+        let (params, processor): (Arc<Params>, Box<dyn Processor>) = match sync.action {
+            ActionType::Filter(filter) => {
+                let params = Params::new(&[0.0, filter.cutoff, filter.q, filter.gain]);
+                let processor = self.add_filter(filter.filter_type, params.clone());
+                self.push_effect(sync, Some(params), Some(processor), status);
+                return;
+            }
+            ActionType::ParametricEQ(ref equalizer) => {
+                let enabled = Params::new(&[0.0]);
+                let processor = EqProcessor::new(
+                    equalizer.band_types(),
+                    equalizer.params.clone(),
+                    enabled.clone(),
+                    sample_rate,
+                    channels,
+                );
+                (enabled, Box::new(processor))
+            }
+            ActionType::Reverb(ref reverb) => {
+                let params = Params::shared(&[
+                    &Setting::new(0.0),
+                    &reverb.room_size,
+                    &reverb.damping,
+                    &reverb.mix,
+                ]);
+                let processor =
+                    ReverbProcessor::new(params.clone(), reverb.pre_delay, sample_rate, channels);
+                (params, Box::new(processor))
+            }
+            ActionType::Delay(ref delay) => {
+                let params = Params::shared(&[&Setting::new(0.0), &delay.feedback, &delay.mix]);
+                let processor = DelayProcessor::new(
+                    params.clone(),
+                    delay.time,
+                    delay.ping_pong,
+                    sample_rate,
+                    channels,
+                );
+                (params, Box::new(processor))
+            }
+            ActionType::Compressor(ref compressor) => self.dynamics(
+                DynamicsMode::Compressor,
+                &[
+                    &compressor.threshold,
+                    &compressor.ratio,
+                    &compressor.attack,
+                    &compressor.release,
+                    &compressor.knee,
+                    &compressor.makeup,
+                    &Setting::new(0.0),
+                ],
+                &compressor.meter,
+            ),
+            ActionType::Limiter(ref limiter) => self.dynamics(
+                DynamicsMode::Limiter,
+                &[
+                    &limiter.threshold,
+                    &Setting::new(f32::INFINITY),
+                    &Setting::new(0.0),
+                    &limiter.release,
+                    &Setting::new(0.0),
+                    &Setting::new(0.0),
+                    &Setting::new(0.0),
+                ],
+                &limiter.meter,
+            ),
+            ActionType::NoiseGate(ref gate) => self.dynamics(
+                DynamicsMode::Gate,
+                &[
+                    &gate.threshold,
+                    &Setting::new(1.0),
+                    &gate.attack,
+                    &gate.release,
+                    &Setting::new(0.0),
+                    &Setting::new(0.0),
+                    &gate.range,
+                ],
+                &gate.meter,
+            ),
+            ActionType::Distortion(ref distortion) => {
+                let params =
+                    Params::shared(&[&Setting::new(0.0), &Setting::new(0.0), &distortion.drive]);
+                let processor =
+                    DistortionProcessor::new(distortion.distortion_type, params.clone(), channels);
+                (params, Box::new(processor))
+            }
+            ActionType::Bitcrusher(ref bitcrusher) => {
+                let params = Params::shared(&[
+                    &Setting::new(0.0),
+                    &Setting::new(0.0),
+                    &bitcrusher.bits,
+                    &bitcrusher.sample_rate,
+                ]);
+                let processor = BitcrusherProcessor::new(params.clone(), sample_rate, channels);
+                (params, Box::new(processor))
+            }
+            ActionType::Chorus(ref chorus) => {
+                let params =
+                    modulation_params(&chorus.rate, &chorus.depth, &chorus.feedback, &chorus.mix);
+                let processor =
+                    ModulatedDelayProcessor::new(params.clone(), CHORUS_SWEEP, sample_rate, channels);
+                (params, Box::new(processor))
+            }
+            ActionType::Flanger(ref flanger) => {
+                let params = modulation_params(
+                    &flanger.rate,
+                    &flanger.depth,
+                    &flanger.feedback,
+                    &flanger.mix,
+                );
+                let processor =
+                    ModulatedDelayProcessor::new(params.clone(), FLANGER_SWEEP, sample_rate, channels);
+                (params, Box::new(processor))
+            }
+            ActionType::Phaser(ref phaser) => {
+                let params =
+                    modulation_params(&phaser.rate, &phaser.depth, &phaser.feedback, &phaser.mix);
+                let processor =
+                    PhaserProcessor::new(params.clone(), phaser.stages, sample_rate, channels);
+                (params, Box::new(processor))
+            }
+            ActionType::Tremolo(ref tremolo) => {
+                let params = Params::shared(&[&Setting::new(0.0), &tremolo.rate, &tremolo.depth]);
+                let processor = TremoloProcessor::new(params.clone(), tremolo.waveform, sample_rate);
+                (params, Box::new(processor))
+            }
+            ActionType::Vibrato(ref vibrato) => {
+                let params = Params::shared(&[&Setting::new(0.0), &vibrato.rate, &vibrato.depth]);
+                let processor =
+                    VibratoProcessor::new(params.clone(), vibrato.waveform, sample_rate, channels);
+                (params, Box::new(processor))
+            }
+            ActionType::PythonEffect(ref effect) => match &effect.callback {
+                Some(callback) => {
+                    let enabled = Params::new(&[0.0]);
+                    let processor = PythonProcessor::new(
+                        callback.clone(),
+                        enabled.clone(),
+                        effect.block_size,
+                        sample_rate,
+                        channels,
+                    );
+                    (enabled, Box::new(processor))
+                }
+                None => {
+                    self.push_effect(sync, None, None, status);
+                    return;
+                }
+            },
+            ActionType::Automation(ref automation) => {
+                let params = Params::new(&[0.0, 0.0, 0.0, 0.0]);
+                pyeffect::add_sampler(automation, &params);
+                self.push_effect(sync, Some(params), None, status);
+                return;
+            }
+            _ => {
+                self.push_effect(sync, None, None, status);
+                return;
+            }
+        };
+        let processor = self.controls.add_processor(processor);
+        self.push_effect(sync, Some(params), Some(processor), status);
+    }
+
+    fn push_effect(
+        &mut self,
+        sync: EffectSync,
+        params: Option<Arc<Params>>,
+        processor: Option<ProcessorId>,
+        status: Arc<EffectStatus>,
+    ) {
+        status.set_state(EffectState::Pending);
+        self.effects.push(ActiveEffect {
+            sync,
+            params,
+            processor,
+            status,
+            paused: false,
+        });
+    }
+
+    /// Builds a dynamics processor. `settings` are its parameters after `dynamics::ENABLED`.
+    fn dynamics(
+        &self,
+        mode: DynamicsMode,
+        settings: &[&Setting],
+        meter: &Meter,
+    ) -> (Arc<Params>, Box<dyn Processor>) {
+        let enabled = Setting::new(0.0);
+        let mut shared = vec![&enabled];
+        shared.extend_from_slice(settings);
+        let params = Params::shared(&shared);
+        let processor = DynamicsProcessor::new(mode, params.clone(), meter.clone(), self.sample_rate);
+        (params, Box::new(processor))
+    }
+
+    /// Points every filter on the voice at `cutoff`, adding a low pass if there is none.
+    fn set_cutoff(&mut self, cutoff: f32) {
+        if self.filters.is_empty() {
+            let params = Params::new(&[1.0, cutoff, BUTTERWORTH_Q, 0.0]);
+            self.add_filter(FilterType::LowPass, params);
+        }
+        for params in &self.filters {
+            params.set(filter::CUTOFF, cutoff);
+        }
+    }
+
+    fn apply_effects(&mut self, position: f32) {
+        let policy = self.policy;
+        let started = |effect: &ActiveEffect| effect.sync.started(position);
+        let newest = |target| {
+            self.effects
+                .iter()
+                .rposition(|effect| effect.sync.action.target() == Some(target) && started(effect))
+        };
+        let newest_volume = newest(AutomationTarget::Volume);
+        let newest_speed = newest(AutomationTarget::Speed);
+        let newest_pan = newest(AutomationTarget::Pan);
+        let newest_cutoff = newest(AutomationTarget::FilterCutoff);
+
+        let controls = &self.controls;
+        let filters = &mut self.filters;
+        let elapsed = position - self.last_position;
+        self.last_position = position;
+        let mut volume = Drive::default();
+        let mut speed = Drive::default();
+        let mut cutoff = None;
+        let mut index = 0;
+
+        let mut apply = |effect: &mut ActiveEffect, current: usize| -> bool {
+            let (value, active) = match effect.sync.update(position) {
+                EffectResult::Value(val) => (val, true),
+                EffectResult::Ignored => return true,
+                EffectResult::Completed(val) => (val, false),
+            };
+
+            if let Some(target) = effect.sync.action.target() {
+                let (drive, newest) = match target {
+                    AutomationTarget::Volume => (Some(&mut volume), newest_volume),
+                    AutomationTarget::Speed => (Some(&mut speed), newest_speed),
+                    AutomationTarget::Pan => (None, newest_pan),
+                    AutomationTarget::FilterCutoff => (None, newest_cutoff),
+                };
+                match (policy, &drive) {
+                    (ConflictPolicy::Override, Some(_)) if newest != Some(current) => return false,
+                    (ConflictPolicy::Queue, Some(drive)) if drive.running => {
+                        effect.sync.defer(position);
+                        return true;
+                    }
+                    _ => {}
+                }
+
+                let (value, running) = match effect.sync.action {
+                    ActionType::Automation(ref automation) => {
+                        // Without a duration the function drives the target until the sink ends.
+                        let running = active || automation.duration.is_none();
+                        let params = effect.params.as_ref().unwrap();
+                        params.set(pyeffect::automation::ACTIVE, if running { 1.0 } else { 0.0 });
+                        params.set(pyeffect::automation::TIME, position - effect.sync.start_position);
+                        if params.get(pyeffect::automation::SAMPLED) != 1.0 {
+                            return running;
+                        }
+                        (params.get(pyeffect::automation::VALUE), running)
+                    }
+                    _ => (value, active),
+                };
+
+                match (target, drive) {
+                    (_, Some(drive)) => drive.push(value, running, policy, newest == Some(current)),
+                    (_, None) if newest != Some(current) => {}
+                    (AutomationTarget::Pan, None) => controls.set_pan(value),
+                    (_, None) => cutoff = Some(value),
+                }
+                return running;
+            }
+
+            match effect.sync.action {
+                ActionType::Filter(_) => {
+                    let params = effect.params.as_ref().unwrap();
+                    params.set(filter::CUTOFF, value);
+                    params.set(filter::ENABLED, 1.0);
+                    // The filter stays in the chain once its sweep is done.
+                    return true;
+                }
+                ActionType::ParametricEQ(_) => {
+                    effect.params.as_ref().unwrap().set(eq::ENABLED, 1.0);
+                    return true;
+                }
+                ActionType::Reverb(_) => {
+                    effect.params.as_ref().unwrap().set(reverb::ENABLED, 1.0);
+                    return true;
+                }
+                ActionType::Delay(_) => {
+                    effect.params.as_ref().unwrap().set(delay::ENABLED, 1.0);
+                    return true;
+                }
+                ActionType::Compressor(_) | ActionType::Limiter(_) | ActionType::NoiseGate(_) => {
+                    effect.params.as_ref().unwrap().set(dynamics::ENABLED, 1.0);
+                    return true;
+                }
+                ActionType::Distortion(ref distortion) => {
+                    // Once the sweep is done the amount follows the setting.
+                    let amount = if active { value } else { distortion.amount.get() };
+                    let params = effect.params.as_ref().unwrap();
+                    params.set(distortion::AMOUNT, amount);
+                    params.set(distortion::ENABLED, 1.0);
+                    return true;
+                }
+                ActionType::Bitcrusher(ref bitcrusher) => {
+                    let amount = if active { value } else { bitcrusher.amount.get() };
+                    let params = effect.params.as_ref().unwrap();
+                    params.set(bitcrusher::AMOUNT, amount);
+                    params.set(bitcrusher::ENABLED, 1.0);
+                    return true;
+                }
+                ActionType::Chorus(_) | ActionType::Flanger(_) | ActionType::Phaser(_) => {
+                    effect.params.as_ref().unwrap().set(modulation::ENABLED, 1.0);
+                    return true;
+                }
+                ActionType::PythonEffect(_) => {
+                    // Effects restored without a callback have no processor.
+                    if let Some(params) = &effect.params {
+                        params.set(pyeffect::ENABLED, 1.0);
+                    }
+                    return true;
+                }
+                ActionType::Tremolo(Tremolo { duration, .. })
+                | ActionType::Vibrato(Vibrato { duration, .. }) => {
+                    // Without a duration the LFO runs until the sink ends.
+                    let running = active || duration.is_none();
+                    let enabled = if running { 1.0 } else { 0.0 };
+                    effect.params.as_ref().unwrap().set(lfo::ENABLED, enabled);
+                    return running;
+                }
+                _ => {}
+            }
+            active
+        };
+
+        self.effects.retain_mut(|effect| {
+            let current = index;
+            index += 1;
+            if effect.paused {
+                // A paused effect keeps its progress by moving with the playback position.
+                effect.sync.shift(elapsed);
+                return true;
+            }
+
+            let keep = apply(effect, current);
+            if !keep {
+                effect.retire(controls, filters);
+            }
+            let state = if !keep {
+                EffectState::Done
+            } else if effect.sync.started(position) {
+                EffectState::Active
+            } else {
+                EffectState::Pending
+            };
+            let value = match effect.sync.update(position) {
+                EffectResult::Value(value) | EffectResult::Completed(value) => value,
+                EffectResult::Ignored => effect.status.value(),
+            };
+            effect.status.publish(state, effect.sync.progress(position), value);
+            keep
+        });
+
+        if let Some(value) = volume.value {
+            let value = match policy {
+                ConflictPolicy::Multiply => self.volume * value,
+                _ => value,
+            };
+            self.controls.set_volume(value.clamp(0.0, 1.0));
+        }
+        self.vol_locked = volume.running;
+        if !volume.running {
+            if let Some(queued) = self.queued_volume.take() {
+                self.volume = queued;
+                self.controls.set_volume(queued);
+            }
+        }
+
+        if let Some(value) = speed.value {
+            let value = match policy {
+                ConflictPolicy::Multiply => self.speed * value,
+                _ => value,
+            };
+            self.controls.set_speed_at(value, 0);
+        }
+        self.speed_locked = speed.running;
+        if !speed.running {
+            if let Some(queued) = self.queued_speed.take() {
+                self.speed = queued;
+                self.controls.set_speed_at(queued, 0);
+            }
+        }
+
+        if let Some(cutoff) = cutoff {
+            self.set_cutoff(cutoff);
+        }
+    }
+
+    /// Starts the effects anchored to `event`.
+    fn trigger(&mut self, event: AnchorKind, position: f32) {
+        for effect in &mut self.effects {
+            effect.sync.trigger(event, position);
+        }
+    }
+
+    /// Removes the running effects that drive `target`.
+    fn cancel(&mut self, target: AutomationTarget) {
+        let position = self.controls.position() as f32;
+        self.effects.retain(|effect| {
+            let keep = effect.sync.action.target() != Some(target)
+                || !effect.sync.started(position);
+            if !keep {
+                effect.retire(&self.controls, &mut self.filters);
+            }
+            keep
+        });
+    }
+
+    /// Applies `control` to the effect `id`. Returns false if the effect is not on the voice, or
+    /// if it is extended after it finished.
+    fn control_effect(&mut self, id: EffectId, control: EffectControl) -> PyResult<bool> {
+        let Some(index) = self.effects.iter().position(|effect| effect.status.id() == id) else {
+            return Ok(false);
+        };
+        let effect = &mut self.effects[index];
+        match control {
+            EffectControl::Cancel => {
+                effect.retire(&self.controls, &mut self.filters);
+                self.effects.remove(index);
+            }
+            EffectControl::Pause => {
+                effect.switch(false);
+                effect.paused = true;
+                effect.status.set_state(EffectState::Paused);
+            }
+            EffectControl::Resume => {
+                let position = self.controls.position() as f32;
+                effect.switch(effect.sync.started(position));
+                effect.paused = false;
+            }
+            EffectControl::Extend(seconds) => {
+                let position = self.controls.position() as f32;
+                if effect.sync.finished(position) {
+                    return Ok(false);
+                }
+                if effect.sync.remaining(position) + seconds < 0.0 {
+                    return Err(PyValueError::new_err(
+                        "Cannot shorten an effect by more than its remaining time.",
+                    ));
+                }
+                effect.sync.extend(position, seconds);
+            }
+        }
+        Ok(true)
+    }
+
+    fn set_volume(&mut self, volume: f32) -> PyResult<()> {
+        if self.vol_locked {
+            match self.policy {
+                ConflictPolicy::Reject => {
+                    return Err(EffectConflictException::with_context("Volume"));
+                }
+                ConflictPolicy::Override => {
+                    self.cancel(AutomationTarget::Volume);
+                    self.vol_locked = false;
+                }
+                ConflictPolicy::Multiply => {
+                    // The running effects scale the new value on the next tick.
+                    self.volume = volume;
+                    return Ok(());
+                }
+                ConflictPolicy::Queue => {
+                    self.queued_volume = Some(volume);
+                    return Ok(());
+                }
+            }
+        }
+        self.controls.set_volume(volume);
+        self.volume = volume;
+        self.publish();
+        Ok(())
+    }
+
+    /// Fails if a running speed effect rejects a new speed.
+    fn check_speed(&self) -> PyResult<()> {
+        if self.speed_locked && self.policy == ConflictPolicy::Reject {
+            return Err(EffectConflictException::with_context("Speed"));
+        }
+        Ok(())
+    }
+
+    fn set_speed(&mut self, speed: f32, frame: u64) -> PyResult<()> {
+        if self.speed_locked {
+            match self.policy {
+                ConflictPolicy::Reject => {
+                    return Err(EffectConflictException::with_context("Speed"));
+                }
+                ConflictPolicy::Override => {
+                    self.cancel(AutomationTarget::Speed);
+                    self.speed_locked = false;
+                }
+                ConflictPolicy::Multiply => {
+                    self.speed = speed;
+                    return Ok(());
+                }
+                ConflictPolicy::Queue => {
+                    self.queued_speed = Some(speed);
+                    return Ok(());
+                }
+            }
+        }
+        self.controls.set_speed_at(speed, frame);
+        self.speed = speed;
+        self.publish();
+        Ok(())
+    }
+
+    fn tick(&mut self, callbacks: &Sender<Callback>) {
+        if self.finished {
+            return;
+        }
+
+        if self.controls.finished() {
+            self.finished = true;
+            for effect in &self.effects {
+                effect.status.set_state(EffectState::Done);
+            }
+            self.status.set_playing(false);
+            self.publish();
+            if self.callback.is_some() && !self.cancel_callback.load(Ordering::SeqCst) {
+                let _ = callbacks.send(self.callback.clone());
+            }
+            return;
+        }
+
+        if self.initial_play {
+            self.initial_volume();
+            self.initial_play = false;
+        }
+
+        self.apply_effects(self.controls.position() as f32);
+        self.controls.drop_retired();
+        self.publish();
+    }
+}
+
+impl Drop for Voice {
+    fn drop(&mut self) {
+        for effect in &self.effects {
+            effect.status.set_state(EffectState::Done);
+        }
+        self.controls.stop();
+    }
+}
+
+struct Engine {
+    outputs: HashMap<OutputKey, Output>,
+    voices: HashMap<VoiceId, Voice>,
+    scheduled: Vec<ScheduledEvent>,
+    callbacks: Sender<Callback>,
+}
+
+impl Engine {
+    fn output(&mut self, config: &OutputConfig) -> PyResult<&Output> {
+        let output = match self.outputs.entry(config.key()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(Output::open(config)?),
+        };
+        Ok(output)
+    }
+
+    fn load(&mut self, id: VoiceId, request: LoadRequest) -> PyResult<()> {
+        self.unload(id);
+
+        let output = self.output(&request.output)?;
+        let controls = Arc::new(PlayerControls::new(0.0));
+        let sample_rate = output.format.sample_rate;
+        let player = Player::new(
+            request.source,
+            controls.clone(),
+            output.clock(),
+            sample_rate,
+            output.format.channels,
+            request.output.resampler,
+        );
+        let channels = player.channels() as usize;
+        output.add(player);
+
+        request.status.loaded.store(true, Ordering::SeqCst);
+        request.status.publish(&controls);
+
+        self.voices.insert(
+            id,
+            Voice {
+                controls,
+                output: request.output.key(),
+                status: request.status,
+                callback: request.callback,
+                cancel_callback: request.cancel_callback,
+                volume: request.volume,
+                sample_rate,
+                channels,
+                effects: Vec::new(),
+                effects_chain: Vec::new(),
+                filters: Vec::new(),
+                initial_play: true,
+                vol_locked: false,
+                speed_locked: false,
+                policy: request.policy,
+                speed: 1.0,
+                queued_volume: None,
+                queued_speed: None,
+                last_position: 0.0,
+                finished: false,
+            },
+        );
+        Ok(())
+    }
+
+    fn unload(&mut self, id: VoiceId) {
+        if let Some(voice) = self.voices.remove(&id) {
+            voice.status.reset();
+        }
+    }
+
+    fn voice(&mut self, id: VoiceId) -> PyResult<&mut Voice> {
+        self.voices.get_mut(&id).ok_or_else(no_voice)
+    }
+
+    /// Output frame at which a transport change on `ids` takes effect. A single voice changes
+    /// on the next frame; several are scheduled past the buffer being rendered so they all
+    /// change on the same sample.
+    fn schedule(&self, ids: &[VoiceId]) -> PyResult<u64> {
+        let mut key = None;
+        for id in ids {
+            let voice = self.voices.get(id).ok_or_else(no_voice)?;
+            if key.is_some_and(|key| key != voice.output) {
+                return Err(PyValueError::new_err(
+                    "Synchronized sinks must share the same output configuration.",
+                ));
+            }
+            key = Some(voice.output);
+        }
+
+        Ok(match key.and_then(|key| self.outputs.get(&key)) {
+            Some(output) if ids.len() > 1 => output.sync_frame(),
+            Some(output) => output.now(),
+            None => 0,
+        })
+    }
+
+    /// The voices of `ids`, resolved before any of them is changed so a group is updated
+    /// whole or not at all.
+    fn group(&mut self, ids: &[VoiceId]) -> PyResult<Vec<&mut Voice>> {
+        if !ids.iter().all(|id| self.voices.contains_key(id)) {
+            return Err(no_voice());
+        }
+        Ok(self
+            .voices
+            .iter_mut()
+            .filter(|(id, _)| ids.contains(id))
+            .map(|(_, voice)| voice)
+            .collect())
+    }
+
+    fn play(&mut self, ids: &[VoiceId]) -> PyResult<()> {
+        let frame = self.schedule(ids)?;
+        for voice in self.group(ids)? {
+            voice.controls.play_at(frame);
+            voice.status.set_playing(true);
+            voice.trigger(AnchorKind::NextPlay, voice.controls.position() as f32);
+        }
+        Ok(())
+    }
+
+    fn pause(&mut self, ids: &[VoiceId]) -> PyResult<()> {
+        let frame = self.schedule(ids)?;
+        for voice in self.group(ids)? {
+            voice.controls.pause_at(frame);
+            voice.status.set_playing(false);
+        }
+        Ok(())
+    }
+
+    fn stop(&mut self, ids: &[VoiceId]) -> PyResult<()> {
+        self.schedule(ids)?;
+        for voice in self.group(ids)? {
+            voice.controls.stop();
+            voice.status.set_playing(false);
+        }
+        Ok(())
+    }
+
+    fn set_speed(&mut self, ids: &[VoiceId], speed: f32) -> PyResult<()> {
+        let frame = self.schedule(ids)?;
+        let voices = self.group(ids)?;
+        for voice in &voices {
+            voice.check_speed()?;
+        }
+        for voice in voices {
+            voice.set_speed(speed, frame)?;
+        }
+        Ok(())
+    }
+
+    fn seek(&mut self, ids: &[VoiceId], position: f32) -> PyResult<()> {
+        let frame = self.schedule(ids)?;
+        let voices = self.group(ids)?;
+        let responses: Vec<_> = voices
+            .iter()
+            .filter(|voice| !voice.controls.finished())
+            .map(|voice| {
+                voice
+                    .controls
+                    .seek_at(Duration::from_secs_f32(position), frame)
+            })
+            .collect();
+
+        for response in responses {
+            match response.recv_timeout(SEEK_TIMEOUT) {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => {
+                    return Err(PyRuntimeError::new_err(format!("Seek failed: {:?}", e)))
+                }
+                Err(_) => {
+                    return Err(PyRuntimeError::new_err(
+                        "Seek failed: the audio output is not running.",
+                    ))
+                }
+            }
+        }
+        for voice in voices {
+            voice.publish();
+            voice.last_position = position;
+            voice.trigger(AnchorKind::Seek, position);
+        }
+        Ok(())
+    }
+
+    fn schedule_at(
+        &mut self,
+        ids: Vec<VoiceId>,
+        at: Instant,
+        action: ScheduledAction,
+        pending: Arc<AtomicBool>,
+    ) -> PyResult<EventId> {
+        self.schedule(&ids)?;
+        let id = NEXT_EVENT_ID.fetch_add(1, Ordering::Relaxed);
+        pending.store(true, Ordering::SeqCst);
+        self.scheduled.push(ScheduledEvent {
+            id,
+            ids,
+            at,
+            action,
+            frame: None,
+            armed: Vec::new(),
+            pending,
+        });
+        Ok(id)
+    }
+
+    fn output_of(&self, ids: &[VoiceId]) -> Option<&Output> {
+        let voice = self.voices.get(ids.first()?)?;
+        self.outputs.get(&voice.output)
+    }
+
+    fn cancel_scheduled(&mut self, event: EventId) -> bool {
+        let Some(index) = self.scheduled.iter().position(|scheduled| scheduled.id == event)
+        else {
+            return false;
+        };
+
+        let scheduled = &self.scheduled[index];
+        if let Some(frame) = scheduled.frame {
+            let clock = self.output_of(&scheduled.ids).map_or(0, Output::now);
+            let withdrawn = scheduled.armed.iter().all(|id| {
+                self.voices.get(id).is_none_or(|voice| match scheduled.action {
+                    ScheduledAction::Play => voice.controls.cancel_play(frame, clock),
+                    ScheduledAction::Stop => voice.controls.cancel_pause(frame, clock),
+                })
+            });
+            if !withdrawn {
+                return false;
+            }
+        }
+
+        let scheduled = self.scheduled.remove(index);
+        scheduled.pending.store(false, Ordering::SeqCst);
+        true
+    }
+
+    /// Arms or fires a scheduled event. Returns false once the event is done.
+    fn advance(&mut self, event: &mut ScheduledEvent, now: Instant) -> bool {
+        let Some(frame) = event.frame else {
+            if event.at > now + SCHEDULE_LOOKAHEAD {
+                return true;
+            }
+            let Ok(earliest) = self.schedule(&event.ids) else {
+                event.pending.store(false, Ordering::SeqCst);
+                return false;
+            };
+            let frame = self
+                .output_of(&event.ids)
+                .map_or(earliest, |output| output.frame_at(event.at).max(earliest));
+
+            for id in &event.ids {
+                let controls = &self.voices[id].controls;
+                let armed = match event.action {
+                    ScheduledAction::Play => controls.play_at(frame),
+                    ScheduledAction::Stop => controls.pause_at(frame),
+                };
+                if armed {
+                    event.armed.push(*id);
+                }
+            }
+            event.frame = Some(frame);
+            return true;
+        };
+
+        if self.output_of(&event.ids).is_some_and(|output| output.now() < frame) {
+            return true;
+        }
+        for id in &event.ids {
+            if let Some(voice) = self.voices.get_mut(id) {
+                match event.action {
+                    ScheduledAction::Play => {
+                        voice.status.set_playing(true);
+                        voice.trigger(AnchorKind::NextPlay, voice.controls.position() as f32);
+                    }
+                    ScheduledAction::Stop => {
+                        voice.controls.stop();
+                        voice.status.set_playing(false);
+                    }
+                }
+            }
+        }
+        event.pending.store(false, Ordering::SeqCst);
+        false
+    }
+
+    fn handle(&mut self, command: EngineCommand) {
+        match command {
+            EngineCommand::Load { id, request, reply } => {
+                let _ = reply.send(self.load(id, request));
+            }
+            EngineCommand::Unload { id, reply } => {
+                self.unload(id);
+                let _ = reply.send(Ok(()));
+            }
+            EngineCommand::Play { ids, reply } => {
+                let _ = reply.send(self.play(&ids));
+            }
+            EngineCommand::Pause { ids, reply } => {
+                let _ = reply.send(self.pause(&ids));
+            }
+            EngineCommand::Stop { ids, reply } => {
+                let _ = reply.send(self.stop(&ids));
+            }
+            EngineCommand::SetVolume { id, volume, reply } => {
+                let _ = reply.send(self.voice(id).and_then(|voice| voice.set_volume(volume)));
+            }
+            EngineCommand::SetConflictPolicy { id, policy, reply } => {
+                let _ = reply.send(self.voice(id).map(|voice| voice.policy = policy));
+            }
+            EngineCommand::SetSpeed { ids, speed, reply } => {
+                let _ = reply.send(self.set_speed(&ids, speed));
+            }
+            EngineCommand::Seek {
+                ids,
+                position,
+                reply,
+            } => {
+                let _ = reply.send(self.seek(&ids, position));
+            }
+            EngineCommand::SetEffectsChain { id, effects, reply } => {
+                let _ = reply.send(self.voice(id).map(|voice| {
+                    voice.effects_chain = effects;
+                    voice.initial_volume();
+                }));
+            }
+            EngineCommand::PushEffects {
+                id,
+                effects,
+                duration,
+                reply,
+            } => {
+                let _ = reply.send(self.voice(id).map(|voice| {
+                    let position = voice.controls.position() as f32;
+                    for (action, status) in effects {
+                        voice.add_effect(EffectSync::new(action, position, duration), status);
+                    }
+                }));
+            }
+            EngineCommand::RestoreEffects { id, effects, reply } => {
+                let _ = reply.send(self.voice(id).map(|voice| {
+                    let position = voice.controls.position() as f32;
+                    for (mut sync, progress, status) in effects {
+                        sync.resume(position, progress);
+                        // Handles keep controlling the effect, and one they paused stays paused.
+                        let paused = status.state() == EffectState::Paused;
+                        let effect = status.id();
+                        voice.add_effect(sync, status);
+                        if paused {
+                            let _ = voice.control_effect(effect, EffectControl::Pause);
+                        }
+                    }
+                }));
+            }
+            EngineCommand::ControlEffect {
+                id,
+                effect,
+                control,
+                reply,
+            } => {
+                let _ = reply.send(
+                    self.voice(id)
+                        .and_then(|voice| voice.control_effect(effect, control)),
+                );
+            }
+            EngineCommand::Effects { id, reply } => {
+                let _ = reply.send(self.voice(id).map(|voice| {
+                    voice
+                        .effects
+                        .iter()
+                        .map(|effect| (effect.sync.clone(), effect.status.clone()))
+                        .collect()
+                }));
+            }
+            EngineCommand::OutputFormat { config, reply } => {
+                let _ = reply.send(self.output(&config).map(|output| OutputFormat {
+                    resampler: config.resampler,
+                    ..output.format.clone()
+                }));
+            }
+            EngineCommand::Schedule {
+                ids,
+                at,
+                action,
+                pending,
+                reply,
+            } => {
+                let _ = reply.send(self.schedule_at(ids, at, action, pending));
+            }
+            EngineCommand::CancelScheduled { event, reply } => {
+                let _ = reply.send(Ok(self.cancel_scheduled(event)));
+            }
+        }
+    }
+
+    fn tick(&mut self) {
+        let now = Instant::now();
+        let mut scheduled = std::mem::take(&mut self.scheduled);
+        scheduled.retain_mut(|event| self.advance(event, now));
+        self.scheduled = scheduled;
+
+        for voice in self.voices.values_mut() {
+            voice.tick(&self.callbacks);
+        }
+
+        // Voices nobody holds a handle to any more are dropped once they can no longer play.
+        let scheduled = &self.scheduled;
+        self.voices.retain(|id, voice| {
+            Arc::strong_count(&voice.status) > 1
+                || (!voice.finished && voice.status.is_playing())
+                || scheduled.iter().any(|event| event.ids.contains(id))
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsp::{DistortionType, Waveform};
+    use crate::effects::{
+        Bitcrusher, Chorus, Compressor, Delay, Distortion, Filter, Flanger, Limiter, NoiseGate,
+        Phaser, Reverb,
+    };
+    use crate::output::ResamplerQuality;
+    use crate::timesync::{Easing, FadeIn};
+    use rodio::buffer::SamplesBuffer;
+
+    const SAMPLE_RATE: u32 = 48_000;
+
+    fn voice() -> (Voice, Player<SamplesBuffer<f32>>) {
+        let tone: Vec<f32> = (0..SAMPLE_RATE)
+            .map(|n| (n as f32 * 440.0 * std::f32::consts::TAU / SAMPLE_RATE as f32).sin() * 0.5)
+            .collect();
+        let controls = Arc::new(PlayerControls::new(1.0));
+        controls.play_at(0);
+        let player = Player::new(
+            SamplesBuffer::new(1, SAMPLE_RATE, tone),
+            controls.clone(),
+            Arc::new(AtomicU64::new(0)),
+            SAMPLE_RATE,
+            1,
+            ResamplerQuality::Linear,
+        );
+        let voice = Voice {
+            controls,
+            output: OutputConfig::default().key(),
+            status: Arc::new(VoiceStatus::default()),
+            callback: Arc::new(None),
+            cancel_callback: Arc::new(AtomicBool::new(false)),
+            volume: 1.0,
+            sample_rate: SAMPLE_RATE,
+            channels: 1,
+            effects: Vec::new(),
+            effects_chain: Vec::new(),
+            filters: Vec::new(),
+            initial_play: false,
+            vol_locked: false,
+            speed_locked: false,
+            policy: ConflictPolicy::default(),
+            speed: 1.0,
+            queued_volume: None,
+            queued_speed: None,
+            last_position: 0.0,
+            finished: false,
+        };
+        (voice, player)
+    }
+
+    fn render(player: &mut Player<SamplesBuffer<f32>>, frames: usize) -> Vec<f32> {
+        player.by_ref().take(frames).collect()
+    }
+
+    /// Runs `action` against a dry voice, cancels it, and checks the output is dry again.
+    fn assert_cancel_removes(action: ActionType) {
+        let (_reference, mut dry) = voice();
+        let (mut wet, mut player) = voice();
+        let status = EffectStatus::new();
+        wet.add_effect(EffectSync::new(action, 0.0, None), status.clone());
+        // Effects start once playback has moved past their start position.
+        wet.apply_effects(0.01);
+
+        assert_ne!(render(&mut player, 4800), render(&mut dry, 4800));
+
+        assert!(wet.control_effect(status.id(), EffectControl::Cancel).unwrap());
+        assert_eq!(status.state(), EffectState::Done);
+        assert!(wet.effects.is_empty() && wet.filters.is_empty());
+        assert_eq!(render(&mut player, 4800), render(&mut dry, 4800));
+    }
+
+    #[test]
+    fn cancelled_reverb_leaves_output_dry() {
+        let reverb = Reverb::new(0.8, 0.5, 0.5, 0.0, None, None).unwrap();
+        assert_cancel_removes(ActionType::Reverb(reverb));
+    }
+
+    #[test]
+    fn cancelled_filter_leaves_output_dry() {
+        let filter = Filter::new(
+            FilterType::LowPass,
+            200.0,
+            0.707,
+            0.0,
+            None,
+            None,
+            None,
+            None,
+            Easing::default(),
+        )
+        .unwrap();
+        assert_cancel_removes(ActionType::Filter(filter));
+    }
+
+    #[test]
+    fn tremolo_ends_after_its_duration() {
+        let (mut voice, _player) = voice();
+        let tremolo = Tremolo::new(8.0, 1.0, Waveform::Square, Some(0.5), None, None).unwrap();
+        let status = EffectStatus::new();
+        voice.add_effect(EffectSync::new(ActionType::Tremolo(tremolo), 0.0, None), status.clone());
+
+        voice.apply_effects(0.25);
+        assert_eq!(voice.effects[0].params.as_ref().unwrap().get(lfo::ENABLED), 1.0);
+        voice.apply_effects(0.75);
+        assert!(voice.effects.is_empty());
+        assert_eq!(status.state(), EffectState::Done);
+    }
+
+    #[test]
+    fn lfo_setting_changes_reach_their_params() {
+        let (mut voice, _player) = voice();
+        let tremolo = Tremolo::new(5.0, 0.5, Waveform::Sine, None, None, None).unwrap();
+        let vibrato = Vibrato::new(5.0, 0.5, Waveform::Sine, None, None, None).unwrap();
+        for action in [ActionType::Tremolo(tremolo.clone()), ActionType::Vibrato(vibrato.clone())] {
+            voice.add_effect(EffectSync::new(action, 0.0, None), EffectStatus::new());
+        }
+
+        tremolo.depth.set(0.9);
+        vibrato.rate.set(2.0);
+        let params = |index: usize| voice.effects[index].params.clone().unwrap();
+        assert_eq!(params(0).get(lfo::DEPTH), 0.9);
+        assert_eq!(params(1).get(lfo::RATE), 2.0);
+    }
+
+    #[test]
+    fn paused_effect_stops_processing_until_resumed() {
+        let (_reference, mut dry) = voice();
+        let (mut wet, mut player) = voice();
+        let reverb = Reverb::new(0.8, 0.5, 0.5, 0.0, None, None).unwrap();
+        let status = EffectStatus::new();
+        wet.add_effect(EffectSync::new(ActionType::Reverb(reverb), 0.0, None), status.clone());
+        wet.apply_effects(0.01);
+        render(&mut player, 4800);
+        render(&mut dry, 4800);
+
+        wet.control_effect(status.id(), EffectControl::Pause).unwrap();
+        // The tail already in the reverb has been switched off along with the input.
+        assert_eq!(render(&mut player, 4800), render(&mut dry, 4800));
+
+        wet.control_effect(status.id(), EffectControl::Resume).unwrap();
+        assert_ne!(render(&mut player, 4800), render(&mut dry, 4800));
+    }
+
+    /// Runs `action` against a dry voice, then checks `bypass` makes the output dry while it plays.
+    fn assert_setting_is_live(action: ActionType, bypass: impl FnOnce()) {
+        let (_reference, mut dry) = voice();
+        let (mut wet, mut player) = voice();
+        wet.add_effect(EffectSync::new(action, 0.0, None), EffectStatus::new());
+        wet.apply_effects(0.01);
+        assert_ne!(render(&mut player, 4800), render(&mut dry, 4800));
+
+        bypass();
+        wet.apply_effects(0.02);
+        assert_eq!(render(&mut player, 4800), render(&mut dry, 4800));
+    }
+
+    #[test]
+    fn reverb_setting_changes_are_heard_live() {
+        let reverb = Reverb::new(0.8, 0.5, 0.5, 0.0, None, None).unwrap();
+        assert_setting_is_live(ActionType::Reverb(reverb.clone()), || reverb.mix.set(0.0));
+    }
+
+    #[test]
+    fn delay_setting_changes_are_heard_live() {
+        let delay = Delay::new(0.01, 0.5, 0.5, false, None, None, None, None).unwrap();
+        assert_setting_is_live(ActionType::Delay(delay.clone()), || delay.mix.set(0.0));
+    }
+
+    #[test]
+    fn distortion_amount_follows_its_setting_after_the_sweep() {
+        let distortion = Distortion::new(
+            DistortionType::HardClip,
+            24.0,
+            1.0,
+            None,
+            None,
+            None,
+            None,
+            Easing::default(),
+        )
+        .unwrap();
+        assert_setting_is_live(ActionType::Distortion(distortion.clone()), || {
+            distortion.amount.set(0.0)
+        });
+    }
+
+    #[test]
+    fn bitcrusher_amount_follows_its_setting_after_the_sweep() {
+        let bitcrusher =
+            Bitcrusher::new(4.0, 4000.0, 1.0, None, None, None, None, Easing::default()).unwrap();
+        assert_setting_is_live(ActionType::Bitcrusher(bitcrusher.clone()), || {
+            bitcrusher.amount.set(0.0)
+        });
+    }
+
+    #[test]
+    fn modulation_setting_changes_are_heard_live() {
+        let chorus = Chorus::new(1.5, 0.5, 0.0, 0.5, None, None).unwrap();
+        assert_setting_is_live(ActionType::Chorus(chorus.clone()), || chorus.mix.set(0.0));
+        let flanger = Flanger::new(0.5, 0.7, 0.5, 0.5, None, None).unwrap();
+        assert_setting_is_live(ActionType::Flanger(flanger.clone()), || flanger.mix.set(0.0));
+        let phaser = Phaser::new(0.5, 0.7, 0.3, 0.5, 4, None, None).unwrap();
+        assert_setting_is_live(ActionType::Phaser(phaser.clone()), || phaser.mix.set(0.0));
+    }
+
+    #[test]
+    fn dynamics_setting_changes_reach_their_params() {
+        let (mut voice, _player) = voice();
+        let compressor = Compressor::new(-18.0, 4.0, 0.01, 0.1, 6.0, 0.0, None, None).unwrap();
+        let limiter = Limiter::new(-1.0, 0.05, None, None).unwrap();
+        let gate = NoiseGate::new(-50.0, 0.002, 0.1, 80.0, None, None).unwrap();
+        for action in [
+            ActionType::Compressor(compressor.clone()),
+            ActionType::Limiter(limiter.clone()),
+            ActionType::NoiseGate(gate.clone()),
+        ] {
+            voice.add_effect(EffectSync::new(action, 0.0, None), EffectStatus::new());
+        }
+
+        compressor.ratio.set(8.0);
+        compressor.makeup.set(3.0);
+        limiter.release.set(0.2);
+        gate.range.set(20.0);
+        let params = |index: usize| voice.effects[index].params.clone().unwrap();
+        assert_eq!(params(0).get(dynamics::RATIO), 8.0);
+        assert_eq!(params(0).get(dynamics::MAKEUP), 3.0);
+        assert_eq!(params(1).get(dynamics::RELEASE), 0.2);
+        assert_eq!(params(2).get(dynamics::RANGE), 20.0);
+    }
+
+    #[test]
+    fn restored_effects_keep_their_statuses() {
+        let (voice, _player) = voice();
+        let mut engine = Engine {
+            outputs: HashMap::new(),
+            voices: HashMap::from([(1, voice)]),
+            scheduled: Vec::new(),
+            callbacks: mpsc::channel().0,
+        };
+        let fade = FadeIn::new(Some(4.0), Some(0.0), Some(1.0), None, None, Easing::default()).unwrap();
+        let (faded, paused) = (EffectStatus::new(), EffectStatus::new());
+        paused.set_state(EffectState::Paused);
+        let sync = EffectSync::new(ActionType::FadeIn(fade), 0.0, None);
+        let (reply, response) = mpsc::channel();
+        engine.handle(EngineCommand::RestoreEffects {
+            id: 1,
+            effects: vec![(sync.clone(), 0.5, faded.clone()), (sync, 0.5, paused.clone())],
+            reply,
+        });
+        response.recv().unwrap().unwrap();
+
+        let voice = engine.voices.get_mut(&1).unwrap();
+        assert!(voice.effects[1].paused);
+        assert_eq!(paused.state(), EffectState::Paused);
+        assert!(voice.control_effect(faded.id(), EffectControl::Cancel).unwrap());
+        assert_eq!(faded.state(), EffectState::Done);
+    }
+
+    #[test]
+    fn rejected_group_speed_changes_no_voice() {
+        let (free, _free_player) = voice();
+        let (mut locked, _locked_player) = voice();
+        locked.speed_locked = true;
+        locked.policy = ConflictPolicy::Reject;
+        let mut engine = Engine {
+            outputs: HashMap::new(),
+            voices: HashMap::from([(1, free), (2, locked)]),
+            scheduled: Vec::new(),
+            callbacks: mpsc::channel().0,
+        };
+
+        assert!(engine.set_speed(&[1, 2], 2.0).is_err());
+        assert!(engine.voices.values().all(|voice| voice.speed == 1.0));
+        assert_eq!(engine.voices[&1].controls.speed(), 1.0);
+    }
+}
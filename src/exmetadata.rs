@@ -2,11 +2,13 @@ use pyo3::types::PyDict;
 use pyo3::{prelude::*, types::IntoPyDict};
 use pyo3::exceptions::PyRuntimeError;
 use audiotags::{AudioTagEdit, Id3v2Tag, Tag};
+use lewton::inside_ogg::OggStreamReader;
 use serde::{Deserialize, Serialize};
 use std::{fs::File, path::Path};
 use rodio::{Decoder, Source};
 use std::io::BufReader;
 use crate::AudioSink;
+use crate::loudness::LoudnessStats;
 use crate::utils::json_to_py;
 
 #[pyclass]
@@ -28,6 +30,13 @@ pub struct MetaData {
     pub sample_rate: Option<u32>,
     pub channels: Option<String>,
     pub duration: Option<f64>,
+    pub integrated_loudness: Option<f64>,
+    pub loudness_range: Option<f64>,
+    pub true_peak: Option<f64>,
+    pub replaygain_track_gain: Option<f64>,
+    pub replaygain_track_peak: Option<f64>,
+    pub replaygain_album_gain: Option<f64>,
+    pub replaygain_album_peak: Option<f64>,
 }
 
 
@@ -43,24 +52,7 @@ impl IntoPyDict for MetaData {
 impl MetaData {
     #[new]
     pub fn new(audio_sink: &AudioSink) -> Self {
-        MetaData {
-            title: audio_sink.metadata.title.clone(),
-            artist: audio_sink.metadata.artist.clone(),
-            date: audio_sink.metadata.date.clone(),
-            year: audio_sink.metadata.year.clone(),
-            album_title: audio_sink.metadata.album_title.clone(),
-            album_artist: audio_sink.metadata.album_artist.clone(),
-            track_number: audio_sink.metadata.track_number.clone(),
-            total_tracks: audio_sink.metadata.total_tracks.clone(),
-            disc_number: audio_sink.metadata.disc_number.clone(),
-            total_discs: audio_sink.metadata.total_discs.clone(),
-            genre: audio_sink.metadata.genre.clone(),
-            composer: audio_sink.metadata.composer.clone(),
-            comment: audio_sink.metadata.comment.clone(),
-            sample_rate: audio_sink.metadata.sample_rate,
-            channels: audio_sink.metadata.channels.clone(),
-            duration: audio_sink.metadata.duration.clone(),
-        }
+        audio_sink.current_metadata()
     }
 
     fn as_dict<'py>(&self, py: Python<'py>) -> Bound<'py, PyDict> {
@@ -146,6 +138,60 @@ impl MetaData {
     fn duration(&self) -> Option<f64> {
         self.duration
     }
+
+    #[getter]
+    fn integrated_loudness(&self) -> Option<f64> {
+        self.integrated_loudness
+    }
+
+    #[getter]
+    fn loudness_range(&self) -> Option<f64> {
+        self.loudness_range
+    }
+
+    #[getter]
+    fn true_peak(&self) -> Option<f64> {
+        self.true_peak
+    }
+
+    #[getter]
+    fn replaygain_track_gain(&self) -> Option<f64> {
+        self.replaygain_track_gain
+    }
+
+    #[getter]
+    fn replaygain_track_peak(&self) -> Option<f64> {
+        self.replaygain_track_peak
+    }
+
+    #[getter]
+    fn replaygain_album_gain(&self) -> Option<f64> {
+        self.replaygain_album_gain
+    }
+
+    #[getter]
+    fn replaygain_album_peak(&self) -> Option<f64> {
+        self.replaygain_album_peak
+    }
+}
+
+impl MetaData {
+    pub fn set_loudness(&mut self, stats: &LoudnessStats) {
+        self.integrated_loudness = stats.integrated_loudness;
+        self.loudness_range = stats.loudness_range;
+        self.true_peak = stats.true_peak;
+    }
+
+    pub fn loudness(&self) -> Option<LoudnessStats> {
+        if self.integrated_loudness.is_none() && self.true_peak.is_none() {
+            return None;
+        }
+        Some(LoudnessStats {
+            integrated_loudness: self.integrated_loudness,
+            loudness_range: self.loudness_range,
+            true_peak: self.true_peak,
+        })
+    }
 }
 
 pub trait AudioTag {
@@ -175,13 +221,86 @@ impl AudioTag for Id3v2Tag {
             sample_rate: None,
             channels: None,
             duration: self.duration().and_then(|s| Some(s as f64)),
+            integrated_loudness: None,
+            loudness_range: None,
+            true_peak: None,
+            replaygain_track_gain: None,
+            replaygain_track_peak: None,
+            replaygain_album_gain: None,
+            replaygain_album_peak: None,
         }
     }
 }
 
+fn parse_replay_gain_value(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let value = value
+        .strip_suffix("dB")
+        .or_else(|| value.strip_suffix("db"))
+        .unwrap_or(value);
+    value.trim().parse::<f64>().ok()
+}
 
-pub fn extract_metadata(path: &Path) -> PyResult<MetaData> {
+fn apply_replay_gain_tags<'a, I>(metadata: &mut MetaData, tags: I)
+where
+    I: IntoIterator<Item = (&'a str, &'a str)>,
+{
+    for (key, value) in tags {
+        let value = parse_replay_gain_value(value);
+        match key.to_ascii_uppercase().as_str() {
+            "REPLAYGAIN_TRACK_GAIN" => metadata.replaygain_track_gain = value,
+            "REPLAYGAIN_TRACK_PEAK" => metadata.replaygain_track_peak = value,
+            "REPLAYGAIN_ALBUM_GAIN" => metadata.replaygain_album_gain = value,
+            "REPLAYGAIN_ALBUM_PEAK" => metadata.replaygain_album_peak = value,
+            _ => {}
+        }
+    }
+}
+
+fn read_replay_gain(path: &Path, metadata: &mut MetaData) {
     match path.extension().and_then(|ext| ext.to_str()) {
+        Some("mp3") => {
+            if let Ok(tag) = id3::Tag::read_from_path(path) {
+                apply_replay_gain_tags(
+                    metadata,
+                    tag.extended_texts()
+                        .map(|text| (text.description.as_str(), text.value.as_str())),
+                );
+            }
+        }
+        Some("flac") => {
+            if let Ok(tag) = metaflac::Tag::read_from_path(path) {
+                if let Some(comments) = tag.vorbis_comments() {
+                    apply_replay_gain_tags(
+                        metadata,
+                        comments.comments.iter().filter_map(|(key, values)| {
+                            values.first().map(|value| (key.as_str(), value.as_str()))
+                        }),
+                    );
+                }
+            }
+        }
+        Some("ogg") => {
+            if let Ok(file) = File::open(path) {
+                if let Ok(reader) = OggStreamReader::new(BufReader::new(file)) {
+                    apply_replay_gain_tags(
+                        metadata,
+                        reader
+                            .comment_hdr
+                            .comment_list
+                            .iter()
+                            .map(|(key, value)| (key.as_str(), value.as_str())),
+                    );
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+
+pub fn extract_metadata(path: &Path) -> PyResult<MetaData> {
+    let mut metadata = match path.extension().and_then(|ext| ext.to_str()) {
         Some("mp3") | Some("m4a") | Some("mp4") | Some("flac") => {
             let tag_result = Tag::new()
                 .read_from_path(path)
@@ -196,11 +315,11 @@ pub fn extract_metadata(path: &Path) -> PyResult<MetaData> {
                         let source = Decoder::new(BufReader::new(file)).map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
                         metadata.duration = source.total_duration().map(|d| d.as_secs_f64());
                     }
-                    Ok(metadata)
+                    metadata
                 },
                 Err(e) => {
                     eprintln!("Failed to read tag: {}. Returning empty metadata.", e);
-                    Ok(MetaData::default())
+                    MetaData::default()
                 }
             }
         },
@@ -210,14 +329,61 @@ pub fn extract_metadata(path: &Path) -> PyResult<MetaData> {
             let sample_rate = source.sample_rate();
             let channels = source.channels();
             let duration = source.total_duration().map_or(0.0, |d| d.as_secs_f64());
-            let metadata = MetaData {
+            MetaData {
                 sample_rate: Some(sample_rate),
                 channels: Some(channels.to_string()),
                 duration: Some(duration),
                 ..MetaData::default()
-            };
-            Ok(metadata)
+            }
         },
-        _ => Ok(MetaData::default()),
+        Some("ogg") => {
+            let file = File::open(path).map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+            let source = Decoder::new(BufReader::new(file)).map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+            MetaData {
+                sample_rate: Some(source.sample_rate()),
+                channels: Some(source.channels().to_string()),
+                duration: source.total_duration().map(|d| d.as_secs_f64()),
+                ..MetaData::default()
+            }
+        },
+        _ => return Ok(MetaData::default()),
+    };
+    // ReplayGain tags are read separately, so they are kept even when the tags above fail.
+    read_replay_gain(path, &mut metadata);
+    Ok(metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_replay_gain_values() {
+        assert_eq!(parse_replay_gain_value("-6.50 dB"), Some(-6.5));
+        assert_eq!(parse_replay_gain_value("+2.1db"), Some(2.1));
+        assert_eq!(parse_replay_gain_value(" 0.988547 "), Some(0.988547));
+        assert_eq!(parse_replay_gain_value(""), None);
+        assert_eq!(parse_replay_gain_value("dB"), None);
+        assert_eq!(parse_replay_gain_value("loud"), None);
+        assert_eq!(parse_replay_gain_value("-6.50 dB extra"), None);
+    }
+
+    #[test]
+    fn applies_replay_gain_tags_by_name() {
+        let mut metadata = MetaData::default();
+        apply_replay_gain_tags(
+            &mut metadata,
+            [
+                ("replaygain_track_gain", "-6.50 dB"),
+                ("REPLAYGAIN_TRACK_PEAK", "0.95"),
+                ("ReplayGain_Album_Gain", "garbled"),
+                ("TITLE", "-1.0 dB"),
+            ],
+        );
+        assert_eq!(metadata.replaygain_track_gain, Some(-6.5));
+        assert_eq!(metadata.replaygain_track_peak, Some(0.95));
+        assert_eq!(metadata.replaygain_album_gain, None);
+        assert_eq!(metadata.replaygain_album_peak, None);
+        assert_eq!(metadata.title, None);
     }
 }
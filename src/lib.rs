@@ -2,12 +2,35 @@ use audiosink::AudioSink;
 use audioqueue::AudioChannel;
 use mixer::ChannelManager;
 use pyo3::prelude::*;
-use timesync::{ActionType, ChangeSpeed, FadeIn, FadeOut};
+use loudness::ReplayGainMode;
+use output::{OutputConfig, ResamplerQuality};
+use syncgroup::SyncGroup;
+use timesync::{
+    ActionType, Anchor, AnchorKind, AutomationTarget, ChangeSpeed, ConflictPolicy, CubicBezier, Curve, Envelope, FadeIn, FadeOut,
+    EffectHandle, EffectState, ScheduledEvent,
+};
+use effects::{
+    Bitcrusher, Chorus, Compressor, Delay, Distortion, EQBand, Filter, Flanger, Limiter, NoiseGate,
+    NoteValue, ParametricEQ, Phaser, Reverb, Tremolo, Vibrato,
+};
+use dsp::{DistortionType, FilterType, Waveform};
+use pyeffect::{Automation, PythonEffect};
+use preset::{dumps_preset, load_preset, loads_preset, save_preset};
 mod audioqueue;
 mod exceptions;
 mod exmetadata;
+mod loudness;
 mod audiosink;
+mod dsp;
+mod effects;
+mod engine;
 mod mixer;
+mod output;
+mod player;
+mod preset;
+mod pyeffect;
+mod schema;
+mod syncgroup;
 mod timesync;
 mod utils;
 use crate::exceptions::EffectConflictException;
@@ -26,7 +49,26 @@ mod rpaudio {
     use super::ChannelManager;
     #[pymodule_export]
     use super::ActionType;
+    #[pymodule_export]
+    use super::ReplayGainMode;
+    #[pymodule_export]
+    use super::OutputConfig;
+    #[pymodule_export]
+    use super::ResamplerQuality;
+    #[pymodule_export]
+    use super::SyncGroup;
+    #[pymodule_export]
+    use super::ScheduledEvent;
+    #[pymodule_export]
+    use super::EffectHandle;
+    #[pymodule_export]
+    use super::EffectState;
 
+    /// Stops background threads calling into Python once the interpreter starts to exit.
+    #[pymodule_init]
+    fn init(m: &Bound<'_, PyModule>) -> PyResult<()> {
+        pyeffect::register_shutdown(m.py())
+    }
 
     #[pymodule]
     mod effects {
@@ -36,6 +78,70 @@ mod rpaudio {
         use super::FadeOut;
         #[pymodule_export]
         use super::ChangeSpeed;
+        #[pymodule_export]
+        use super::Curve;
+        #[pymodule_export]
+        use super::CubicBezier;
+        #[pymodule_export]
+        use super::Envelope;
+        #[pymodule_export]
+        use super::Automation;
+        #[pymodule_export]
+        use super::AutomationTarget;
+        #[pymodule_export]
+        use super::Anchor;
+        #[pymodule_export]
+        use super::AnchorKind;
+        #[pymodule_export]
+        use super::ConflictPolicy;
+        #[pymodule_export]
+        use super::Filter;
+        #[pymodule_export]
+        use super::FilterType;
+        #[pymodule_export]
+        use super::ParametricEQ;
+        #[pymodule_export]
+        use super::EQBand;
+        #[pymodule_export]
+        use super::Reverb;
+        #[pymodule_export]
+        use super::Delay;
+        #[pymodule_export]
+        use super::NoteValue;
+        #[pymodule_export]
+        use super::Compressor;
+        #[pymodule_export]
+        use super::Limiter;
+        #[pymodule_export]
+        use super::NoiseGate;
+        #[pymodule_export]
+        use super::Distortion;
+        #[pymodule_export]
+        use super::DistortionType;
+        #[pymodule_export]
+        use super::Bitcrusher;
+        #[pymodule_export]
+        use super::Chorus;
+        #[pymodule_export]
+        use super::Flanger;
+        #[pymodule_export]
+        use super::Phaser;
+        #[pymodule_export]
+        use super::Tremolo;
+        #[pymodule_export]
+        use super::Vibrato;
+        #[pymodule_export]
+        use super::Waveform;
+        #[pymodule_export]
+        use super::PythonEffect;
+        #[pymodule_export]
+        use super::dumps_preset;
+        #[pymodule_export]
+        use super::loads_preset;
+        #[pymodule_export]
+        use super::save_preset;
+        #[pymodule_export]
+        use super::load_preset;
     }
 
     #[pymodule]
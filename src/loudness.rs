@@ -0,0 +1,489 @@
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+use pyo3::types::{IntoPyDict, PyDict};
+use rodio::source::SeekError;
+use rodio::{Decoder, Source};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::dsp::Biquad;
+use crate::utils::json_to_py;
+use crate::MetaData;
+
+const ABSOLUTE_GATE: f64 = -70.0;
+const INTEGRATED_RELATIVE_GATE: f64 = -10.0;
+const RANGE_RELATIVE_GATE: f64 = -20.0;
+const OVERSAMPLING: usize = 4;
+const TRUE_PEAK_TAPS: usize = 48;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct LoudnessStats {
+    pub integrated_loudness: Option<f64>,
+    pub loudness_range: Option<f64>,
+    pub true_peak: Option<f64>,
+}
+
+impl IntoPyDict for LoudnessStats {
+    fn into_py_dict_bound(self, py: Python<'_>) -> Bound<'_, PyDict> {
+        let value = serde_json::to_value(self).unwrap();
+        json_to_py(py, &value).extract().unwrap()
+    }
+}
+
+impl LoudnessStats {
+    /// Linear gain that brings the integrated loudness to `target_lufs`, reduced if
+    /// needed so the true peak stays below 0 dBTP.
+    pub fn normalization_gain(&self, target_lufs: f32) -> f32 {
+        let integrated = match self.integrated_loudness {
+            Some(integrated) => integrated,
+            None => return 1.0,
+        };
+        let mut gain = db_to_linear(target_lufs as f64 - integrated);
+        if let Some(true_peak) = self.true_peak {
+            let peak = db_to_linear(true_peak);
+            if peak * gain > 1.0 {
+                gain = 1.0 / peak;
+            }
+        }
+        gain as f32
+    }
+}
+
+#[pyclass(eq, eq_int)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ReplayGainMode {
+    Track,
+    Album,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReplayGainSettings {
+    pub mode: ReplayGainMode,
+    pub preamp: f32,
+    pub prevent_clipping: bool,
+}
+
+impl ReplayGainSettings {
+    /// Linear gain from the ReplayGain tags, or None if the file carries no gain tag.
+    /// Album mode falls back to the track values when the album tags are missing.
+    pub fn gain(&self, metadata: &MetaData) -> Option<f32> {
+        let track = (metadata.replaygain_track_gain, metadata.replaygain_track_peak);
+        let album = (metadata.replaygain_album_gain, metadata.replaygain_album_peak);
+        let (gain_db, peak) = match self.mode {
+            ReplayGainMode::Track => track,
+            ReplayGainMode::Album if album.0.is_some() => album,
+            ReplayGainMode::Album => track,
+        };
+
+        let mut gain = db_to_linear(gain_db? + self.preamp as f64);
+        if self.prevent_clipping {
+            if let Some(peak) = peak.filter(|peak| *peak > 0.0) {
+                if peak * gain > 1.0 {
+                    gain = 1.0 / peak;
+                }
+            }
+        }
+        Some(gain as f32)
+    }
+}
+
+pub fn db_to_linear(db: f64) -> f64 {
+    10f64.powf(db / 20.0)
+}
+
+fn energy_to_loudness(energy: f64) -> f64 {
+    -0.691 + 10.0 * energy.log10()
+}
+
+// K-weighting from ITU-R BS.1770, with the coefficients recomputed for any sample rate.
+fn k_weighting(sample_rate: f64, channels: usize) -> [Biquad; 2] {
+    let f0 = 1681.974450955533;
+    let gain_db = 3.999843853973347;
+    let q = 0.7071752369554196;
+    let k = (PI * f0 / sample_rate).tan();
+    let vh = db_to_linear(gain_db);
+    let vb = vh.powf(0.4996667741545416);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad::from_coefficients(
+        [
+            (vh + vb * k / q + k * k) / a0,
+            2.0 * (k * k - vh) / a0,
+            (vh - vb * k / q + k * k) / a0,
+        ],
+        [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        channels,
+    );
+
+    let f0 = 38.13547087602444;
+    let q = 0.5003270373238773;
+    let k = (PI * f0 / sample_rate).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let high_pass = Biquad::from_coefficients(
+        [1.0, -2.0, 1.0],
+        [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        channels,
+    );
+
+    [shelf, high_pass]
+}
+
+fn channel_weight(channels: u16, index: usize) -> f64 {
+    match (channels, index) {
+        (6, 3) => 0.0,
+        (5, 3) | (5, 4) | (6, 4) | (6, 5) => 1.41,
+        _ => 1.0,
+    }
+}
+
+struct TruePeakMeter {
+    phases: Vec<[f64; TRUE_PEAK_TAPS / OVERSAMPLING]>,
+    history: Vec<[f64; TRUE_PEAK_TAPS / OVERSAMPLING]>,
+    peak: f64,
+}
+
+impl TruePeakMeter {
+    fn new(channels: usize) -> Self {
+        let center = TRUE_PEAK_TAPS as f64 / 2.0;
+        let mut phases = vec![[0.0; TRUE_PEAK_TAPS / OVERSAMPLING]; OVERSAMPLING];
+        for n in 0..TRUE_PEAK_TAPS {
+            let x = (n as f64 - center) / OVERSAMPLING as f64;
+            let sinc = if x == 0.0 { 1.0 } else { (PI * x).sin() / (PI * x) };
+            let window = 0.5 - 0.5 * (2.0 * PI * n as f64 / TRUE_PEAK_TAPS as f64).cos();
+            phases[n % OVERSAMPLING][n / OVERSAMPLING] = sinc * window;
+        }
+        TruePeakMeter {
+            phases,
+            history: vec![[0.0; TRUE_PEAK_TAPS / OVERSAMPLING]; channels],
+            peak: 0.0,
+        }
+    }
+
+    fn process(&mut self, channel: usize, sample: f64) {
+        let history = &mut self.history[channel];
+        history.rotate_right(1);
+        history[0] = sample;
+        self.peak = self.peak.max(sample.abs());
+        for phase in &self.phases {
+            let value: f64 = phase.iter().zip(history.iter()).map(|(h, x)| h * x).sum();
+            self.peak = self.peak.max(value.abs());
+        }
+    }
+}
+
+fn gated_mean(energies: &[f64], relative_gate: f64) -> Option<(f64, Vec<f64>)> {
+    let above_absolute: Vec<f64> = energies
+        .iter()
+        .copied()
+        .filter(|energy| energy_to_loudness(*energy) > ABSOLUTE_GATE)
+        .collect();
+    if above_absolute.is_empty() {
+        return None;
+    }
+    let mean = above_absolute.iter().sum::<f64>() / above_absolute.len() as f64;
+    let threshold = energy_to_loudness(mean) + relative_gate;
+    let gated: Vec<f64> = above_absolute
+        .into_iter()
+        .filter(|energy| energy_to_loudness(*energy) > threshold)
+        .collect();
+    if gated.is_empty() {
+        return None;
+    }
+    let mean = gated.iter().sum::<f64>() / gated.len() as f64;
+    Some((mean, gated))
+}
+
+fn percentile(sorted: &[f64], fraction: f64) -> f64 {
+    let index = ((sorted.len() - 1) as f64 * fraction).round() as usize;
+    sorted[index]
+}
+
+/// Measures integrated loudness (LUFS), loudness range (LU) and true peak (dBTP)
+/// following EBU R128 / ITU-R BS.1770.
+pub fn analyze_file(path: &Path) -> PyResult<LoudnessStats> {
+    let file = File::open(path).map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+    let source = Decoder::new(BufReader::new(file))
+        .map_err(|e| PyRuntimeError::new_err(format!("Failed to decode audio file: {}", e)))?;
+    let channels = source.channels();
+    let sample_rate = source.sample_rate();
+    Ok(analyze_samples(source.convert_samples::<f32>(), channels, sample_rate))
+}
+
+pub fn analyze_samples<I>(samples: I, channels: u16, sample_rate: u32) -> LoudnessStats
+where
+    I: Iterator<Item = f32>,
+{
+    let channel_count = channels.max(1) as usize;
+    let hop = (sample_rate as usize / 10).max(1);
+    let [mut shelf, mut high_pass] = k_weighting(sample_rate as f64, channel_count);
+    let weights: Vec<f64> = (0..channel_count)
+        .map(|index| channel_weight(channels, index))
+        .collect();
+    let mut true_peak = TruePeakMeter::new(channel_count);
+
+    let mut segments: Vec<f64> = Vec::new();
+    let mut segment_energy = 0.0;
+    let mut frames_in_segment = 0;
+    let mut channel = 0;
+
+    for sample in samples {
+        let sample = sample as f64;
+        true_peak.process(channel, sample);
+
+        let weighted = high_pass.process(channel, shelf.process(channel, sample));
+        segment_energy += weights[channel] * weighted * weighted;
+
+        channel += 1;
+        if channel == channel_count {
+            channel = 0;
+            frames_in_segment += 1;
+            if frames_in_segment == hop {
+                segments.push(segment_energy);
+                segment_energy = 0.0;
+                frames_in_segment = 0;
+            }
+        }
+    }
+
+    let block_energies = |segments_per_block: usize, step: usize| -> Vec<f64> {
+        if segments.len() < segments_per_block {
+            return Vec::new();
+        }
+        (0..=segments.len() - segments_per_block)
+            .step_by(step)
+            .map(|start| {
+                segments[start..start + segments_per_block].iter().sum::<f64>()
+                    / (segments_per_block * hop) as f64
+            })
+            .collect()
+    };
+
+    let integrated_loudness = gated_mean(&block_energies(4, 1), INTEGRATED_RELATIVE_GATE)
+        .map(|(mean, _)| energy_to_loudness(mean));
+
+    let loudness_range = gated_mean(&block_energies(30, 1), RANGE_RELATIVE_GATE).map(|(_, gated)| {
+        let mut loudness: Vec<f64> = gated.into_iter().map(energy_to_loudness).collect();
+        loudness.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        percentile(&loudness, 0.95) - percentile(&loudness, 0.10)
+    });
+
+    let true_peak = if true_peak.peak > 0.0 {
+        Some(20.0 * true_peak.peak.log10())
+    } else {
+        None
+    };
+
+    LoudnessStats {
+        integrated_loudness,
+        loudness_range,
+        true_peak,
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SharedGain(Arc<AtomicU32>);
+
+impl Default for SharedGain {
+    fn default() -> Self {
+        SharedGain(Arc::new(AtomicU32::new(1.0f32.to_bits())))
+    }
+}
+
+impl SharedGain {
+    pub fn get(&self) -> f32 {
+        f32::from_bits(self.0.load(Ordering::Relaxed))
+    }
+
+    pub fn set(&self, gain: f32) {
+        self.0.store(gain.to_bits(), Ordering::Relaxed);
+    }
+}
+
+/// Applies a gain that can be changed from other threads while the source is playing.
+pub struct GainSource<I> {
+    input: I,
+    gain: SharedGain,
+}
+
+impl<I> GainSource<I> {
+    pub fn new(input: I, gain: SharedGain) -> Self {
+        GainSource { input, gain }
+    }
+}
+
+impl<I> Iterator for GainSource<I>
+where
+    I: Source<Item = f32>,
+{
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        self.input.next().map(|sample| sample * self.gain.get())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> Source for GainSource<I>
+where
+    I: Source<Item = f32>,
+{
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tagged(track: (Option<f64>, Option<f64>), album: (Option<f64>, Option<f64>)) -> MetaData {
+        MetaData {
+            replaygain_track_gain: track.0,
+            replaygain_track_peak: track.1,
+            replaygain_album_gain: album.0,
+            replaygain_album_peak: album.1,
+            ..MetaData::default()
+        }
+    }
+
+    fn settings(mode: ReplayGainMode, preamp: f32, prevent_clipping: bool) -> ReplayGainSettings {
+        ReplayGainSettings {
+            mode,
+            preamp,
+            prevent_clipping,
+        }
+    }
+
+    fn assert_close(actual: Option<f32>, expected: f64) {
+        let actual = actual.expect("expected a gain") as f64;
+        assert!((actual - expected).abs() < 1e-5, "{} != {}", actual, expected);
+    }
+
+    /// Interleaved stereo sine with both channels at `level_db` dBFS peak.
+    fn stereo_sine(frequency: f64, level_db: f64, seconds: f64, phase: f64) -> Vec<f32> {
+        let amplitude = db_to_linear(level_db);
+        let frames = (48000.0 * seconds) as usize;
+        (0..frames)
+            .flat_map(|n| {
+                let sample = amplitude * (2.0 * PI * frequency * n as f64 / 48000.0 + phase).sin();
+                [sample as f32; 2]
+            })
+            .collect()
+    }
+
+    fn analyze(samples: Vec<f32>) -> LoudnessStats {
+        analyze_samples(samples.into_iter(), 2, 48000)
+    }
+
+    #[test]
+    fn integrated_loudness_of_reference_sine() {
+        let stats = analyze(stereo_sine(1000.0, -23.0, 10.0, 0.0));
+        let integrated = stats.integrated_loudness.unwrap();
+        assert!((integrated + 23.0).abs() < 0.1, "{}", integrated);
+    }
+
+    #[test]
+    fn silence_is_gated_out() {
+        let mut samples = stereo_sine(1000.0, -23.0, 10.0, 0.0);
+        samples.extend(vec![0.0; 48000 * 2 * 10]);
+        let integrated = analyze(samples).integrated_loudness.unwrap();
+        assert!((integrated + 23.0).abs() < 0.1, "{}", integrated);
+
+        let silent = analyze(vec![0.0; 48000 * 2 * 5]);
+        assert_eq!(silent.integrated_loudness, None);
+        assert_eq!(silent.loudness_range, None);
+        assert_eq!(silent.true_peak, None);
+    }
+
+    #[test]
+    fn true_peak_finds_inter_sample_peak() {
+        // At a quarter of the sample rate with a 45 degree phase every sample lands 3 dB
+        // below the crest of the wave.
+        let stats = analyze(stereo_sine(12000.0, -6.0, 1.0, PI / 4.0));
+        let true_peak = stats.true_peak.unwrap();
+        assert!((true_peak + 6.0).abs() < 0.3, "{}", true_peak);
+    }
+
+    #[test]
+    fn loudness_range_of_two_levels() {
+        // EBU Tech 3342 case 1: 20 s at -20 dBFS followed by 20 s at -30 dBFS.
+        let mut samples = stereo_sine(1000.0, -20.0, 20.0, 0.0);
+        samples.extend(stereo_sine(1000.0, -30.0, 20.0, 0.0));
+        let range = analyze(samples).loudness_range.unwrap();
+        assert!((range - 10.0).abs() < 1.0, "{}", range);
+    }
+
+    #[test]
+    fn no_gain_without_tags() {
+        let metadata = MetaData::default();
+        assert_eq!(settings(ReplayGainMode::Track, 0.0, true).gain(&metadata), None);
+        assert_eq!(settings(ReplayGainMode::Album, 0.0, true).gain(&metadata), None);
+    }
+
+    #[test]
+    fn album_mode_uses_album_tags() {
+        let metadata = tagged((Some(-6.0), None), (Some(-3.0), None));
+        let album = settings(ReplayGainMode::Album, 0.0, false).gain(&metadata);
+        assert_close(album, db_to_linear(-3.0));
+        let track = settings(ReplayGainMode::Track, 0.0, false).gain(&metadata);
+        assert_close(track, db_to_linear(-6.0));
+    }
+
+    #[test]
+    fn album_mode_falls_back_to_track_tags() {
+        let metadata = tagged((Some(-6.0), Some(0.5)), (None, Some(0.9)));
+        let gain = settings(ReplayGainMode::Album, 0.0, false).gain(&metadata);
+        assert_close(gain, db_to_linear(-6.0));
+    }
+
+    #[test]
+    fn preamp_adds_to_tagged_gain() {
+        let metadata = tagged((Some(-6.0), None), (None, None));
+        let gain = settings(ReplayGainMode::Track, 4.0, false).gain(&metadata);
+        assert_close(gain, db_to_linear(-2.0));
+    }
+
+    #[test]
+    fn clipping_prevention_limits_gain_to_peak() {
+        let metadata = tagged((Some(6.0), Some(0.8)), (None, None));
+        let limited = settings(ReplayGainMode::Track, 0.0, true).gain(&metadata);
+        assert_close(limited, 1.0 / 0.8);
+        let unlimited = settings(ReplayGainMode::Track, 0.0, false).gain(&metadata);
+        assert_close(unlimited, db_to_linear(6.0));
+    }
+
+    #[test]
+    fn clipping_prevention_ignores_missing_or_zero_peak() {
+        let boost = db_to_linear(6.0);
+        let missing = tagged((Some(6.0), None), (None, None));
+        assert_close(settings(ReplayGainMode::Track, 0.0, true).gain(&missing), boost);
+        let zero = tagged((Some(6.0), Some(0.0)), (None, None));
+        assert_close(settings(ReplayGainMode::Track, 0.0, true).gain(&zero), boost);
+    }
+}
@@ -1,7 +1,9 @@
 use crate::audioqueue::AudioChannel;
+use crate::engine::{self, EngineCommand};
+use crate::output::{OutputConfig, OutputFormat};
 use pyo3::exceptions::PyRuntimeError;
 use pyo3::prelude::*;
-use pyo3::types::PyModule;
+use pyo3::types::{IntoPyDict, PyModule};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
@@ -9,33 +11,68 @@ use std::sync::{Arc, Mutex};
 #[pyclass]
 pub struct ChannelManager {
     channels: Arc<Mutex<HashMap<String, AudioChannel>>>,
+    output_config: Arc<Mutex<Option<OutputConfig>>>,
+}
+
+impl ChannelManager {
+    fn channels(&self) -> Vec<AudioChannel> {
+        self.channels.lock().unwrap().values().cloned().collect()
+    }
 }
 
 #[pymethods]
 impl ChannelManager {
     #[new]
-    pub fn new() -> Self {
+    #[pyo3(signature = (output_config=None))]
+    pub fn new(output_config: Option<OutputConfig>) -> Self {
         Self {
             channels: Arc::new(Mutex::new(HashMap::new())),
+            output_config: Arc::new(Mutex::new(output_config)),
         }
     }
 
-    pub fn add_channel(&self, name: String, channel: &AudioChannel) {
-        self.channels.lock().unwrap().insert(name, channel.clone());
+    pub fn add_channel(&self, py: Python, name: String, channel: &AudioChannel) -> PyResult<()> {
+        let mut channel = channel.clone();
+        if let Some(output_config) = *self.output_config.lock().unwrap() {
+            channel.set_output_config(py, output_config)?;
+        }
+        self.channels.lock().unwrap().insert(name, channel);
+        Ok(())
     }
 
-    pub fn drop_channel(&self, name: String) -> PyResult<()> {
-        let mut channels = self.channels.lock().unwrap();
+    #[getter]
+    pub fn output_config(&self) -> Option<OutputConfig> {
+        *self.output_config.lock().unwrap()
+    }
+
+    pub fn set_output_config(&self, py: Python, output_config: OutputConfig) -> PyResult<()> {
+        *self.output_config.lock().unwrap() = Some(output_config);
 
-        if let Some(channel) = channels.remove(&name) {
-            if let Some(mut current_audio) = channel.current_audio() {
-                let _ = current_audio.pause();
+        for mut channel in self.channels() {
+            channel.set_output_config(py, output_config)?;
+        }
+        Ok(())
+    }
+
+    pub fn output_format(&self, py: Python) -> PyResult<PyObject> {
+        let config = self.output_config.lock().unwrap().unwrap_or_default();
+        let format: OutputFormat =
+            engine::request(py, |reply| EngineCommand::OutputFormat { config, reply })?;
+        Ok(format.into_py_dict_bound(py).into())
+    }
+
+    pub fn drop_channel(&self, py: Python, name: String) -> PyResult<()> {
+        let removed = self.channels.lock().unwrap().remove(&name);
+
+        if let Some(channel) = removed {
+            if let Some(current_audio) = channel.current_audio() {
+                let _ = current_audio.pause(py);
             }
 
             channel.queue.lock().unwrap().clear();
 
-            if let Some(mut current_audio) = channel.current_audio() {
-                let _ = current_audio.stop();
+            if let Some(current_audio) = channel.current_audio() {
+                let _ = current_audio.stop(py);
             }
 
             Ok(())
@@ -59,11 +96,10 @@ impl ChannelManager {
         }
     }
 
-    pub fn stop_all(&self) {
-        let mut channels = self.channels.lock().unwrap();
-        for (_, channel) in channels.iter_mut() {
+    pub fn stop_all(&self, py: Python) {
+        for mut channel in self.channels() {
             channel.set_auto_consume(false);
-            channel.drop_current_audio();
+            channel.drop_current_audio(py);
         }
     }
 }
@@ -0,0 +1,328 @@
+use crate::utils::json_to_py;
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{IntoPyDict, PyDict};
+use rodio::cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use rodio::cpal::{
+    self, BufferSize, FromSample, SampleFormat, SampleRate, SizedSample, StreamConfig,
+    SupportedBufferSize, SupportedStreamConfig,
+};
+use rodio::dynamic_mixer::{self, DynamicMixer, DynamicMixerController};
+use rodio::Source;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+const SYNC_MARGIN_FRAMES: u64 = 64;
+
+#[pyclass(eq, eq_int)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ResamplerQuality {
+    #[default]
+    Linear,
+    Cubic,
+    Sinc,
+}
+
+#[pyclass]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct OutputConfig {
+    #[pyo3(get)]
+    pub sample_rate: Option<u32>,
+    #[pyo3(get)]
+    pub channels: Option<u16>,
+    #[pyo3(get)]
+    pub buffer_size: Option<u32>,
+    #[pyo3(get)]
+    pub latency: Option<f32>,
+    #[pyo3(get)]
+    #[serde(default)]
+    pub resampler: ResamplerQuality,
+}
+
+/// Identifies the device stream a config resolves to. The resampler runs per voice, so it is
+/// not part of the key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct OutputKey {
+    sample_rate: Option<u32>,
+    channels: Option<u16>,
+    buffer_size: Option<u32>,
+    latency_micros: Option<u64>,
+}
+
+#[pymethods]
+impl OutputConfig {
+    #[new]
+    #[pyo3(signature = (sample_rate=None, channels=None, buffer_size=None, latency=None, resampler=ResamplerQuality::Linear))]
+    pub fn new(
+        sample_rate: Option<u32>,
+        channels: Option<u16>,
+        buffer_size: Option<u32>,
+        latency: Option<f32>,
+        resampler: ResamplerQuality,
+    ) -> PyResult<Self> {
+        if sample_rate == Some(0) {
+            return Err(PyValueError::new_err("sample_rate must be greater than 0."));
+        }
+        if channels == Some(0) {
+            return Err(PyValueError::new_err("channels must be greater than 0."));
+        }
+        if buffer_size == Some(0) {
+            return Err(PyValueError::new_err("buffer_size must be greater than 0."));
+        }
+        if latency.is_some_and(|latency| latency.is_nan() || latency <= 0.0) {
+            return Err(PyValueError::new_err("latency must be greater than 0."));
+        }
+
+        Ok(OutputConfig {
+            sample_rate,
+            channels,
+            buffer_size,
+            latency,
+            resampler,
+        })
+    }
+
+    fn __repr__(&self) -> String {
+        fn field<T: ToString>(value: Option<T>) -> String {
+            value.map_or_else(|| "None".to_string(), |value| value.to_string())
+        }
+
+        format!(
+            "OutputConfig(sample_rate={}, channels={}, buffer_size={}, latency={}, resampler=ResamplerQuality.{:?})",
+            field(self.sample_rate),
+            field(self.channels),
+            field(self.buffer_size),
+            field(self.latency),
+            self.resampler
+        )
+    }
+}
+
+impl OutputConfig {
+    pub fn key(&self) -> OutputKey {
+        OutputKey {
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+            buffer_size: self.buffer_size,
+            latency_micros: self
+                .latency
+                .map(|latency| (latency as f64 * 1_000_000.0).round() as u64),
+        }
+    }
+}
+
+/// The format a device stream was actually opened with.
+#[derive(Clone, Debug, Serialize)]
+pub struct OutputFormat {
+    pub device: Option<String>,
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub sample_format: String,
+    pub buffer_size: Option<u32>,
+    pub latency: Option<f64>,
+    pub resampler: ResamplerQuality,
+}
+
+impl IntoPyDict for OutputFormat {
+    fn into_py_dict_bound(self, py: Python<'_>) -> Bound<'_, PyDict> {
+        let value = serde_json::to_value(self).unwrap();
+        json_to_py(py, &value).extract().unwrap()
+    }
+}
+
+pub struct Output {
+    _stream: cpal::Stream,
+    mixer: Arc<DynamicMixerController<f32>>,
+    clock: Arc<AtomicU64>,
+    buffer_frames: Arc<AtomicU64>,
+    anchor: Arc<Anchor>,
+    pub format: OutputFormat,
+}
+
+/// Output frame at the start of the last callback and when that callback ran.
+struct Anchor {
+    epoch: Instant,
+    frame: AtomicU64,
+    nanos: AtomicU64,
+}
+
+impl Output {
+    pub fn open(config: &OutputConfig) -> PyResult<Output> {
+        let device = cpal::default_host()
+            .default_output_device()
+            .ok_or_else(|| PyRuntimeError::new_err("No audio output device available."))?;
+        let supported = supported_config(&device, config)?;
+
+        let buffer_size = config.buffer_size.or_else(|| {
+            config
+                .latency
+                .map(|latency| (latency as f64 * supported.sample_rate().0 as f64).round() as u32)
+        });
+        let buffer_size = match (buffer_size, supported.buffer_size()) {
+            (Some(frames), SupportedBufferSize::Range { min, max }) => {
+                Some(frames.clamp(*min, *max))
+            }
+            (frames, _) => frames,
+        };
+
+        let stream_config = StreamConfig {
+            channels: supported.channels(),
+            sample_rate: supported.sample_rate(),
+            buffer_size: match buffer_size {
+                Some(frames) => BufferSize::Fixed(frames),
+                None => BufferSize::Default,
+            },
+        };
+
+        let (mixer, source) =
+            dynamic_mixer::mixer::<f32>(stream_config.channels, stream_config.sample_rate.0);
+        let clock = Arc::new(AtomicU64::new(0));
+        let buffer_frames = Arc::new(AtomicU64::new(0));
+        let anchor = Arc::new(Anchor {
+            epoch: Instant::now(),
+            frame: AtomicU64::new(0),
+            nanos: AtomicU64::new(0),
+        });
+        let counters = (clock.clone(), buffer_frames.clone(), anchor.clone());
+        let stream = match supported.sample_format() {
+            SampleFormat::F32 => build_stream::<f32>(&device, &stream_config, source, counters),
+            SampleFormat::F64 => build_stream::<f64>(&device, &stream_config, source, counters),
+            SampleFormat::I16 => build_stream::<i16>(&device, &stream_config, source, counters),
+            SampleFormat::I32 => build_stream::<i32>(&device, &stream_config, source, counters),
+            SampleFormat::U16 => build_stream::<u16>(&device, &stream_config, source, counters),
+            SampleFormat::U8 => build_stream::<u8>(&device, &stream_config, source, counters),
+            sample_format => {
+                return Err(PyRuntimeError::new_err(format!(
+                    "Unsupported output sample format: {}",
+                    sample_format
+                )))
+            }
+        }
+        .map_err(|e| PyRuntimeError::new_err(format!("Failed to open audio output: {}", e)))?;
+        stream
+            .play()
+            .map_err(|e| PyRuntimeError::new_err(format!("Failed to start audio output: {}", e)))?;
+
+        let format = OutputFormat {
+            device: device.name().ok(),
+            sample_rate: stream_config.sample_rate.0,
+            channels: stream_config.channels,
+            sample_format: supported.sample_format().to_string(),
+            buffer_size,
+            latency: buffer_size.map(|frames| frames as f64 / stream_config.sample_rate.0 as f64),
+            resampler: config.resampler,
+        };
+
+        Ok(Output {
+            _stream: stream,
+            mixer,
+            clock,
+            buffer_frames,
+            anchor,
+            format,
+        })
+    }
+
+    pub fn clock(&self) -> Arc<AtomicU64> {
+        self.clock.clone()
+    }
+
+    /// Output frame currently being rendered.
+    pub fn now(&self) -> u64 {
+        self.clock.load(Ordering::SeqCst)
+    }
+
+    /// First frame the output callback cannot have rendered yet, even if it is running while
+    /// this is called. Voices scheduled on it all change state on the same sample.
+    pub fn sync_frame(&self) -> u64 {
+        self.now() + self.buffer_frames.load(Ordering::SeqCst) + SYNC_MARGIN_FRAMES
+    }
+
+    /// Output frame rendered at `instant`, estimated from the last callback. Never earlier than
+    /// the frame currently being rendered.
+    pub fn frame_at(&self, instant: Instant) -> u64 {
+        let anchor_frame = self.anchor.frame.load(Ordering::SeqCst);
+        let anchor_time =
+            self.anchor.epoch + std::time::Duration::from_nanos(self.anchor.nanos.load(Ordering::SeqCst));
+        let offset = instant.saturating_duration_since(anchor_time).as_secs_f64()
+            * self.format.sample_rate as f64;
+        (anchor_frame + offset.round() as u64).max(self.now())
+    }
+
+    pub fn add<S>(&self, source: S)
+    where
+        S: Source<Item = f32> + Send + 'static,
+    {
+        self.mixer.add(source);
+    }
+}
+
+fn supported_config(device: &cpal::Device, config: &OutputConfig) -> PyResult<SupportedStreamConfig> {
+    let default = device
+        .default_output_config()
+        .map_err(|e| PyRuntimeError::new_err(format!("Failed to query audio output: {}", e)))?;
+    if config.sample_rate.is_none() && config.channels.is_none() {
+        return Ok(default);
+    }
+
+    let sample_rate = config.sample_rate.unwrap_or(default.sample_rate().0);
+    let channels = config.channels.unwrap_or(default.channels());
+    let preference = |format: SampleFormat| match format {
+        SampleFormat::F32 => 3,
+        SampleFormat::I16 => 2,
+        format if format == default.sample_format() => 1,
+        _ => 0,
+    };
+
+    device
+        .supported_output_configs()
+        .map_err(|e| PyRuntimeError::new_err(format!("Failed to query audio output: {}", e)))?
+        .filter(|range| {
+            range.channels() == channels
+                && range.min_sample_rate().0 <= sample_rate
+                && sample_rate <= range.max_sample_rate().0
+        })
+        .max_by_key(|range| preference(range.sample_format()))
+        .map(|range| range.with_sample_rate(SampleRate(sample_rate)))
+        .ok_or_else(|| {
+            PyValueError::new_err(format!(
+                "Output device does not support {} channels at {} Hz.",
+                channels, sample_rate
+            ))
+        })
+}
+
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &StreamConfig,
+    mut source: DynamicMixer<f32>,
+    (clock, buffer_frames, anchor): (Arc<AtomicU64>, Arc<AtomicU64>, Arc<Anchor>),
+) -> Result<cpal::Stream, cpal::BuildStreamError>
+where
+    T: SizedSample + FromSample<f32>,
+{
+    let channels = config.channels as usize;
+    device.build_output_stream::<T, _, _>(
+        config,
+        move |data: &mut [T], _| {
+            buffer_frames.store((data.len() / channels) as u64, Ordering::SeqCst);
+            let mut frame = clock.load(Ordering::SeqCst);
+            anchor
+                .nanos
+                .store(anchor.epoch.elapsed().as_nanos() as u64, Ordering::SeqCst);
+            anchor.frame.store(frame, Ordering::SeqCst);
+            for samples in data.chunks_mut(channels) {
+                clock.store(frame, Ordering::SeqCst);
+                for sample in samples.iter_mut() {
+                    *sample = T::from_sample(source.next().unwrap_or(0.0));
+                }
+                frame += 1;
+            }
+            clock.store(frame, Ordering::SeqCst);
+        },
+        |err| eprintln!("an error occurred on output stream: {}", err),
+        None,
+    )
+}
@@ -0,0 +1,527 @@
+use crate::dsp::Processor;
+use crate::output::ResamplerQuality;
+use rodio::source::SeekError;
+use rodio::Source;
+use std::collections::VecDeque;
+use std::f64::consts::PI;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const NEVER: u64 = u64::MAX;
+static NEXT_PROCESSOR_ID: AtomicU64 = AtomicU64::new(1);
+
+pub type ProcessorId = u64;
+const LINEAR_HALF_WIDTH: usize = 1;
+const CUBIC_HALF_WIDTH: usize = 2;
+const SINC_HALF_WIDTH: usize = 16;
+/// Time a full-scale volume change is spread over, so control-rate updates don't click.
+const VOLUME_RAMP_SECONDS: f32 = 0.005;
+
+struct SeekOrder {
+    position: Duration,
+    frame: u64,
+    reply: Sender<Result<(), SeekError>>,
+}
+
+/// Transport state shared between the audio thread and the output callback. Frames are counted
+/// on the output's clock, so players given the same frame change state on the same sample.
+pub struct PlayerControls {
+    start_frame: AtomicU64,
+    stop_frame: AtomicU64,
+    volume: AtomicU32,
+    speed: AtomicU32,
+    speed_frame: AtomicU64,
+    pan: AtomicU32,
+    stopped: AtomicBool,
+    finished: AtomicBool,
+    position: AtomicU64,
+    seek_pending: AtomicBool,
+    seek: Mutex<Option<SeekOrder>>,
+    processors_pending: AtomicBool,
+    processors: Mutex<Vec<(ProcessorId, Box<dyn Processor>)>>,
+    removals_pending: AtomicBool,
+    removals: Mutex<Vec<ProcessorId>>,
+    /// Processors taken out of the chain, dropped by the audio thread rather than the callback.
+    retired: Mutex<Vec<Box<dyn Processor>>>,
+}
+
+impl PlayerControls {
+    pub fn new(volume: f32) -> Self {
+        PlayerControls {
+            start_frame: AtomicU64::new(NEVER),
+            stop_frame: AtomicU64::new(NEVER),
+            volume: AtomicU32::new(volume.to_bits()),
+            speed: AtomicU32::new(1f32.to_bits()),
+            speed_frame: AtomicU64::new(0),
+            pan: AtomicU32::new(0f32.to_bits()),
+            stopped: AtomicBool::new(false),
+            finished: AtomicBool::new(false),
+            position: AtomicU64::new(0f64.to_bits()),
+            seek_pending: AtomicBool::new(false),
+            seek: Mutex::new(None),
+            processors_pending: AtomicBool::new(false),
+            processors: Mutex::new(Vec::new()),
+            removals_pending: AtomicBool::new(false),
+            removals: Mutex::new(Vec::new()),
+            retired: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Starts playback at `frame` unless the player is already playing by then.
+    pub fn play_at(&self, frame: u64) -> bool {
+        if self.start_frame.load(Ordering::SeqCst) <= frame
+            && self.stop_frame.load(Ordering::SeqCst) == NEVER
+        {
+            return false;
+        }
+        self.start_frame.store(frame, Ordering::SeqCst);
+        self.stop_frame.store(NEVER, Ordering::SeqCst);
+        true
+    }
+
+    /// Pauses playback at `frame` unless the player is already paused by then.
+    pub fn pause_at(&self, frame: u64) -> bool {
+        self.stop_frame.fetch_min(frame, Ordering::SeqCst) > frame
+    }
+
+    /// Withdraws a `play_at(frame)` that has not been reached yet.
+    pub fn cancel_play(&self, frame: u64, clock: u64) -> bool {
+        clock < frame
+            && self
+                .start_frame
+                .compare_exchange(frame, NEVER, Ordering::SeqCst, Ordering::SeqCst)
+                .is_ok()
+    }
+
+    /// Withdraws a `pause_at(frame)` that has not been reached yet.
+    pub fn cancel_pause(&self, frame: u64, clock: u64) -> bool {
+        clock < frame
+            && self
+                .stop_frame
+                .compare_exchange(frame, NEVER, Ordering::SeqCst, Ordering::SeqCst)
+                .is_ok()
+    }
+
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+
+    pub fn finished(&self) -> bool {
+        self.finished.load(Ordering::SeqCst)
+    }
+
+    pub fn volume(&self) -> f32 {
+        f32::from_bits(self.volume.load(Ordering::Relaxed))
+    }
+
+    pub fn set_volume(&self, volume: f32) {
+        self.volume.store(volume.to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn speed(&self) -> f32 {
+        f32::from_bits(self.speed.load(Ordering::SeqCst))
+    }
+
+    pub fn set_speed_at(&self, speed: f32, frame: u64) {
+        self.speed_frame.store(NEVER, Ordering::SeqCst);
+        self.speed.store(speed.to_bits(), Ordering::SeqCst);
+        self.speed_frame.store(frame, Ordering::SeqCst);
+    }
+
+    pub fn pan(&self) -> f32 {
+        f32::from_bits(self.pan.load(Ordering::Relaxed))
+    }
+
+    pub fn set_pan(&self, pan: f32) {
+        self.pan.store(pan.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Appends a processor to the end of the player's chain.
+    pub fn add_processor(&self, processor: Box<dyn Processor>) -> ProcessorId {
+        let id = NEXT_PROCESSOR_ID.fetch_add(1, Ordering::Relaxed);
+        self.processors.lock().unwrap().push((id, processor));
+        self.processors_pending.store(true, Ordering::SeqCst);
+        id
+    }
+
+    /// Takes a processor out of the player's chain.
+    pub fn remove_processor(&self, id: ProcessorId) {
+        let mut added = self.processors.lock().unwrap();
+        if let Some(index) = added.iter().position(|(added, _)| *added == id) {
+            added.remove(index);
+            return;
+        }
+        let mut removals = self.removals.lock().unwrap();
+        removals.push(id);
+        // Room for the callback to hand the processor back without allocating.
+        self.retired.lock().unwrap().reserve(removals.len());
+        self.removals_pending.store(true, Ordering::SeqCst);
+    }
+
+    /// Drops the processors the player has taken out of its chain.
+    pub fn drop_retired(&self) {
+        self.retired.lock().unwrap().clear();
+    }
+
+    pub fn position(&self) -> f64 {
+        f64::from_bits(self.position.load(Ordering::Relaxed))
+    }
+
+    pub fn seek_at(&self, position: Duration, frame: u64) -> Receiver<Result<(), SeekError>> {
+        let (reply, response) = mpsc::channel();
+        *self.seek.lock().unwrap() = Some(SeekOrder {
+            position,
+            frame,
+            reply,
+        });
+        self.seek_pending.store(true, Ordering::SeqCst);
+        response
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    if x.abs() < 1e-9 {
+        1.0
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+/// Plays a source at the output rate. Speed and sample rate conversion are done here with the
+/// configured interpolation, and the transport is checked on every output frame.
+pub struct Player<I> {
+    input: I,
+    controls: Arc<PlayerControls>,
+    clock: Arc<AtomicU64>,
+    channels: usize,
+    out_channels: usize,
+    input_rate: u32,
+    output_rate: u32,
+    quality: ResamplerQuality,
+    half_width: usize,
+    speed: f32,
+    gain: f32,
+    gain_step: f32,
+    processors: Vec<(ProcessorId, Box<dyn Processor>)>,
+    tail: Option<usize>,
+    window: VecDeque<f32>,
+    window_start: i64,
+    next_input: i64,
+    input_frames: Option<i64>,
+    position: f64,
+    offset: f64,
+    frame: Vec<f32>,
+    frame_pos: usize,
+}
+
+impl<I> Player<I>
+where
+    I: Source<Item = f32>,
+{
+    pub fn new(
+        input: I,
+        controls: Arc<PlayerControls>,
+        clock: Arc<AtomicU64>,
+        output_rate: u32,
+        output_channels: u16,
+        quality: ResamplerQuality,
+    ) -> Self {
+        let channels = input.channels().max(1) as usize;
+        // Mono is spread over two channels so it can be panned.
+        let out_channels = if channels == 1 && output_channels >= 2 {
+            2
+        } else {
+            channels
+        };
+        let input_rate = input.sample_rate();
+        let half_width = match quality {
+            ResamplerQuality::Linear => LINEAR_HALF_WIDTH,
+            ResamplerQuality::Cubic => CUBIC_HALF_WIDTH,
+            ResamplerQuality::Sinc => SINC_HALF_WIDTH,
+        };
+
+        let gain = controls.volume();
+
+        let mut player = Player {
+            input,
+            controls,
+            clock,
+            channels,
+            out_channels,
+            input_rate,
+            output_rate,
+            quality,
+            half_width,
+            speed: 1.0,
+            gain,
+            gain_step: 1.0 / (output_rate as f32 * VOLUME_RAMP_SECONDS),
+            processors: Vec::new(),
+            tail: None,
+            window: VecDeque::new(),
+            window_start: 0,
+            next_input: 0,
+            input_frames: None,
+            position: 0.0,
+            offset: 0.0,
+            frame: vec![0.0; out_channels],
+            frame_pos: out_channels,
+        };
+        player.reset();
+        player
+    }
+
+    fn reset(&mut self) {
+        let history = self.half_width - 1;
+        self.window.clear();
+        self.window.resize(history * self.channels, 0.0);
+        self.window_start = -(history as i64);
+        self.next_input = 0;
+        self.input_frames = None;
+        self.position = 0.0;
+        self.tail = None;
+    }
+
+    fn apply_seek(&mut self, clock: u64) {
+        let order = {
+            let mut seek = self.controls.seek.lock().unwrap();
+            match seek.take() {
+                Some(order) if order.frame <= clock => order,
+                order => {
+                    *seek = order;
+                    return;
+                }
+            }
+        };
+        self.controls.seek_pending.store(false, Ordering::SeqCst);
+        let result = self.input.try_seek(order.position);
+        if result.is_ok() {
+            self.reset();
+            self.offset = order.position.as_secs_f64();
+            self.publish_position();
+        }
+        let _ = order.reply.send(result);
+    }
+
+    fn retire_processors(&mut self) {
+        let (Ok(mut removals), Ok(mut retired)) =
+            (self.controls.removals.try_lock(), self.controls.retired.try_lock())
+        else {
+            return;
+        };
+        removals.retain(|id| match self.processors.iter().position(|(added, _)| added == id) {
+            Some(index) => {
+                retired.push(self.processors.remove(index).1);
+                false
+            }
+            // Not appended to the chain yet.
+            None => true,
+        });
+        self.controls
+            .removals_pending
+            .store(!removals.is_empty(), Ordering::SeqCst);
+    }
+
+    fn publish_position(&self) {
+        let position = self.offset + self.position / self.input_rate as f64;
+        self.controls
+            .position
+            .store(position.to_bits(), Ordering::Relaxed);
+    }
+
+    fn read_frame(&mut self) {
+        for channel in 0..self.channels {
+            let sample = match self.input_frames {
+                Some(_) => 0.0,
+                None => match self.input.next() {
+                    Some(sample) => sample,
+                    None => {
+                        self.input_frames = Some(self.next_input + (channel > 0) as i64);
+                        0.0
+                    }
+                },
+            };
+            self.window.push_back(sample);
+        }
+        self.next_input += 1;
+    }
+
+    fn sample(&self, frame: i64, channel: usize) -> f32 {
+        let index = (frame - self.window_start) as usize * self.channels + channel;
+        self.window[index]
+    }
+
+    fn interpolate(&mut self) -> bool {
+        let base = self.position.floor() as i64;
+        let first = base - (self.half_width as i64 - 1);
+        let last = base + self.half_width as i64;
+
+        while self.next_input <= last {
+            if self.input_frames.is_some_and(|total| base >= total) {
+                return false;
+            }
+            self.read_frame();
+        }
+        if self.input_frames.is_some_and(|total| base >= total) {
+            return false;
+        }
+        while self.window_start < first {
+            self.window.drain(..self.channels);
+            self.window_start += 1;
+        }
+
+        let step = self.input_rate as f64 / self.output_rate as f64 * self.speed as f64;
+        let frac = self.position - base as f64;
+        for channel in 0..self.channels {
+            self.frame[channel] = if frac == 0.0 {
+                self.sample(base, channel)
+            } else {
+                match self.quality {
+                    ResamplerQuality::Linear => {
+                        let p0 = self.sample(base, channel) as f64;
+                        let p1 = self.sample(base + 1, channel) as f64;
+                        (p0 + (p1 - p0) * frac) as f32
+                    }
+                    ResamplerQuality::Cubic => {
+                        let p0 = self.sample(base - 1, channel) as f64;
+                        let p1 = self.sample(base, channel) as f64;
+                        let p2 = self.sample(base + 1, channel) as f64;
+                        let p3 = self.sample(base + 2, channel) as f64;
+                        (p1 + 0.5
+                            * frac
+                            * (p2 - p0
+                                + frac
+                                    * (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3
+                                        + frac * (3.0 * (p1 - p2) + p3 - p0))))
+                            as f32
+                    }
+                    ResamplerQuality::Sinc => {
+                        let cutoff = (1.0 / step).min(1.0);
+                        (first..=last)
+                            .map(|frame| {
+                                let x = frame as f64 - base as f64 - frac;
+                                let weight =
+                                    cutoff * sinc(cutoff * x) * sinc(x / self.half_width as f64);
+                                self.sample(frame, channel) as f64 * weight
+                            })
+                            .sum::<f64>() as f32
+                    }
+                }
+            };
+        }
+
+        self.position += step;
+        true
+    }
+
+    fn render_frame(&mut self) -> bool {
+        let clock = self.clock.load(Ordering::Relaxed);
+
+        if self.controls.stopped.load(Ordering::SeqCst) {
+            self.controls.finished.store(true, Ordering::SeqCst);
+            return false;
+        }
+        if self.controls.seek_pending.load(Ordering::SeqCst) {
+            self.apply_seek(clock);
+        }
+        if clock >= self.controls.speed_frame.load(Ordering::SeqCst) {
+            self.speed = self.controls.speed();
+        }
+
+        let playing = self.controls.start_frame.load(Ordering::SeqCst) <= clock
+            && clock < self.controls.stop_frame.load(Ordering::SeqCst);
+        if !playing {
+            self.frame.fill(0.0);
+            return true;
+        }
+
+        if self.controls.processors_pending.load(Ordering::SeqCst) {
+            if let Ok(mut added) = self.controls.processors.try_lock() {
+                self.processors.append(&mut added);
+                self.controls.processors_pending.store(false, Ordering::SeqCst);
+            }
+        }
+        if self.controls.removals_pending.load(Ordering::SeqCst) {
+            self.retire_processors();
+        }
+
+        if self.tail.is_none() && !self.interpolate() {
+            // Let processors ring out on silence before the voice finishes.
+            self.tail = Some(self.processors.iter().map(|(_, p)| p.tail()).max().unwrap_or(0));
+        }
+        match self.tail {
+            Some(0) => {
+                self.controls.finished.store(true, Ordering::SeqCst);
+                return false;
+            }
+            Some(ref mut remaining) => {
+                *remaining -= 1;
+                self.frame.fill(0.0);
+            }
+            None => {
+                if self.out_channels > self.channels {
+                    self.frame[1] = self.frame[0];
+                }
+            }
+        }
+        for (_, processor) in self.processors.iter_mut() {
+            processor.process(&mut self.frame);
+        }
+
+        let pan = self.controls.pan();
+        if pan != 0.0 && self.out_channels == 2 {
+            self.frame[0] *= (1.0 - pan).min(1.0);
+            self.frame[1] *= (1.0 + pan).min(1.0);
+        }
+
+        let target = self.controls.volume();
+        self.gain += (target - self.gain).clamp(-self.gain_step, self.gain_step);
+        for sample in self.frame.iter_mut() {
+            *sample *= self.gain;
+        }
+        self.publish_position();
+        true
+    }
+}
+
+impl<I> Iterator for Player<I>
+where
+    I: Source<Item = f32>,
+{
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        if self.frame_pos == self.out_channels {
+            if !self.render_frame() {
+                return None;
+            }
+            self.frame_pos = 0;
+        }
+        let sample = self.frame[self.frame_pos];
+        self.frame_pos += 1;
+        Some(sample)
+    }
+}
+
+impl<I> Source for Player<I>
+where
+    I: Source<Item = f32>,
+{
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.out_channels as u16
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.output_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
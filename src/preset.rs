@@ -0,0 +1,94 @@
+use std::fs;
+use std::path::PathBuf;
+
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyList;
+use serde::{Deserialize, Serialize};
+
+use crate::timesync::{ActionType, ExtractableEffect};
+
+const PRESET_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct EffectPreset {
+    version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    effects: Vec<ActionType>,
+}
+
+/// Parses a preset, rejecting versions newer than this build understands, effects that need a
+/// Python callable, and effects whose parameters their constructors would reject.
+pub fn parse(json: &str) -> PyResult<Vec<ActionType>> {
+    let preset: EffectPreset = serde_json::from_str(json)
+        .map_err(|e| PyValueError::new_err(format!("Invalid effects preset: {}", e)))?;
+    if preset.version > PRESET_VERSION {
+        return Err(PyValueError::new_err(format!(
+            "Unsupported effects preset version {}",
+            preset.version
+        )));
+    }
+    for effect in &preset.effects {
+        effect.check_saveable("a preset")?;
+        effect.validate()?;
+    }
+    Ok(preset.effects)
+}
+
+pub fn read(path: &PathBuf) -> PyResult<Vec<ActionType>> {
+    let json = fs::read_to_string(path)
+        .map_err(|e| PyRuntimeError::new_err(format!("Failed to read effects preset: {}", e)))?;
+    parse(&json)
+}
+
+fn to_json(effect_list: &Bound<'_, PyList>, name: Option<String>) -> PyResult<String> {
+    let effects = effect_list
+        .iter()
+        .map(|effect| {
+            let action = effect.extract_action()?;
+            action.check_saveable("a preset")?;
+            Ok(action)
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+
+    let preset = EffectPreset {
+        version: PRESET_VERSION,
+        name,
+        effects,
+    };
+    serde_json::to_string_pretty(&preset)
+        .map_err(|e| PyRuntimeError::new_err(format!("Failed to serialize effects preset: {}", e)))
+}
+
+fn to_objects(py: Python, effects: Vec<ActionType>) -> Vec<PyObject> {
+    effects.iter().map(|effect| effect.to_object(py)).collect()
+}
+
+#[pyfunction]
+#[pyo3(signature = (effect_list, name=None))]
+pub fn dumps_preset(effect_list: &Bound<'_, PyList>, name: Option<String>) -> PyResult<String> {
+    to_json(effect_list, name)
+}
+
+#[pyfunction]
+pub fn loads_preset(py: Python, json: &str) -> PyResult<Vec<PyObject>> {
+    Ok(to_objects(py, parse(json)?))
+}
+
+#[pyfunction]
+#[pyo3(signature = (effect_list, path, name=None))]
+pub fn save_preset(
+    effect_list: &Bound<'_, PyList>,
+    path: PathBuf,
+    name: Option<String>,
+) -> PyResult<()> {
+    let json = to_json(effect_list, name)?;
+    fs::write(&path, json)
+        .map_err(|e| PyRuntimeError::new_err(format!("Failed to write effects preset: {}", e)))
+}
+
+#[pyfunction]
+pub fn load_preset(py: Python, path: PathBuf) -> PyResult<Vec<PyObject>> {
+    Ok(to_objects(py, read(&path)?))
+}
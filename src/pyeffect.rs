@@ -0,0 +1,669 @@
+use std::cell::UnsafeCell;
+use std::collections::VecDeque;
+use std::fmt;
+use std::mem;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, OnceLock, Weak};
+use std::thread::{self, Thread};
+use std::time::{Duration, Instant};
+
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::types::{IntoPyDict, PyBytes, PyDict};
+use pyo3::{intern, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::dsp::{Params, Processor};
+use crate::schema;
+use crate::timesync::{Anchor, AutomationTarget};
+use crate::utils::json_to_py;
+
+pub const ENABLED: usize = 0;
+
+/// Blocks buffered ahead of the output, giving the worker one block of time to answer.
+const LATENCY_BLOCKS: usize = 2;
+/// Blocks owned by a processor: the one being filled and those queued ahead of the output.
+const POOL_BLOCKS: usize = LATENCY_BLOCKS + 1;
+
+static WORKER: OnceLock<Worker> = OnceLock::new();
+static SAMPLERS: OnceLock<Mutex<Vec<Sampler>>> = OnceLock::new();
+/// Wakes the sampler thread when an automation is registered.
+static SAMPLER_ADDED: Condvar = Condvar::new();
+/// Held by the background threads while they call into Python.
+static BUSY: Mutex<()> = Mutex::new(());
+static SHUTDOWN: AtomicBool = AtomicBool::new(false);
+
+/// A bounded queue with one producer and one consumer that neither locks nor allocates, so
+/// the output callback can hand blocks to the worker and take them back.
+struct Ring<T> {
+    slots: Box<[UnsafeCell<Option<T>>]>,
+    /// Count of values taken, written only by the consumer.
+    head: AtomicUsize,
+    /// Count of values added, written only by the producer.
+    tail: AtomicUsize,
+}
+
+// SAFETY: a slot is only touched by the producer between `head` passing it and `tail` being
+// published, and only by the consumer after that until `head` moves on.
+unsafe impl<T: Send> Sync for Ring<T> {}
+
+impl<T> Ring<T> {
+    fn with_capacity(capacity: usize) -> Self {
+        Ring {
+            slots: (0..capacity).map(|_| UnsafeCell::new(None)).collect(),
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+        }
+    }
+
+    /// Adds `value`, or gives it back if the ring is full. Called by the producer only.
+    fn push(&self, value: T) -> Result<(), T> {
+        let tail = self.tail.load(Ordering::Relaxed);
+        if tail.wrapping_sub(self.head.load(Ordering::Acquire)) == self.slots.len() {
+            return Err(value);
+        }
+        // SAFETY: the slot is free, and the consumer does not read it until `tail` moves.
+        unsafe { *self.slots[tail % self.slots.len()].get() = Some(value) };
+        self.tail.store(tail.wrapping_add(1), Ordering::Release);
+        Ok(())
+    }
+
+    /// Takes the oldest value. Called by the consumer only.
+    fn pop(&self) -> Option<T> {
+        let head = self.head.load(Ordering::Relaxed);
+        if head == self.tail.load(Ordering::Acquire) {
+            return None;
+        }
+        // SAFETY: the slot was published by `tail`, and the producer does not reuse it until
+        // `head` moves.
+        let value = unsafe { (*self.slots[head % self.slots.len()].get()).take() };
+        self.head.store(head.wrapping_add(1), Ordering::Release);
+        value
+    }
+}
+
+/// A block of interleaved samples. Blocks arriving while the effect is off are passed straight
+/// back, which keeps the latency the same before and after the effect starts.
+struct Block {
+    samples: Vec<f32>,
+    enabled: bool,
+}
+
+/// The queues between one `PythonProcessor` and the worker, sized for the processor's whole
+/// pool so a push never fails.
+struct Channel {
+    callback: Arc<Py<PyAny>>,
+    sample_rate: u32,
+    channels: usize,
+    requests: Ring<Block>,
+    replies: Ring<Block>,
+}
+
+/// Runs Python callbacks for every `PythonEffect`, taking the GIL once for all the blocks
+/// waiting at the time.
+struct Worker {
+    thread: Thread,
+    channels: Arc<Mutex<Vec<Weak<Channel>>>>,
+}
+
+/// Started from Python so the exit hook can be registered.
+fn start_worker(py: Python) -> PyResult<()> {
+    if WORKER.get().is_some() {
+        return Ok(());
+    }
+    register_shutdown(py)?;
+
+    WORKER.get_or_init(|| {
+        let channels: Arc<Mutex<Vec<Weak<Channel>>>> = Arc::default();
+        let registered = channels.clone();
+        let handle = thread::spawn(move || loop {
+            // Woken by a processor whenever it queues a block.
+            thread::park();
+            let ready: Vec<Arc<Channel>> = {
+                let mut registered = registered.lock().unwrap();
+                registered.retain(|channel| channel.strong_count() > 0);
+                registered.iter().filter_map(Weak::upgrade).collect()
+            };
+
+            let _busy = BUSY.lock().unwrap();
+            let shutdown = SHUTDOWN.load(Ordering::SeqCst);
+            let mut python = Vec::new();
+            for channel in &ready {
+                while let Some(block) = channel.requests.pop() {
+                    if block.enabled && !shutdown {
+                        python.push((channel, block));
+                    } else {
+                        let _ = channel.replies.push(block);
+                    }
+                }
+            }
+            if python.is_empty() {
+                continue;
+            }
+            Python::with_gil(|py| {
+                for (channel, mut block) in python {
+                    if let Err(e) = run(py, channel, &mut block.samples) {
+                        eprintln!("Failed to run PythonEffect callback: {}", e);
+                    }
+                    let _ = channel.replies.push(block);
+                }
+            });
+        });
+        Worker {
+            thread: handle.thread().clone(),
+            channels,
+        }
+    });
+    Ok(())
+}
+
+pub fn register_shutdown(py: Python) -> PyResult<()> {
+    static REGISTERED: OnceLock<()> = OnceLock::new();
+    if REGISTERED.get().is_none() {
+        py.import_bound(intern!(py, "atexit"))?
+            .call_method1(intern!(py, "register"), (wrap_pyfunction_bound!(shutdown, py)?,))?;
+        let _ = REGISTERED.set(());
+    }
+    Ok(())
+}
+
+/// Runs `f` with the GIL from a background thread, or skips it once Python has started to
+/// finalize.
+pub fn call_python(f: impl FnOnce(Python)) {
+    let _busy = BUSY.lock().unwrap();
+    if !SHUTDOWN.load(Ordering::SeqCst) {
+        Python::with_gil(f);
+    }
+}
+
+/// Waits for the running batch and stops calling into Python, which is about to finalize.
+#[pyfunction]
+fn shutdown(py: Python) {
+    py.allow_threads(|| {
+        SHUTDOWN.store(true, Ordering::SeqCst);
+        drop(BUSY.lock());
+    });
+}
+
+/// Calls the effect with an `array.array('f')` of the block. The callback either changes the
+/// array in place and returns None, or returns the processed samples.
+fn run(py: Python, channel: &Channel, block: &mut [f32]) -> PyResult<()> {
+    let bytes: Vec<u8> = block.iter().flat_map(|sample| sample.to_ne_bytes()).collect();
+    let array = py
+        .import_bound(intern!(py, "array"))?
+        .getattr(intern!(py, "array"))?
+        .call1(("f",))?;
+    array.call_method1(intern!(py, "frombytes"), (PyBytes::new_bound(py, &bytes),))?;
+
+    let result = channel
+        .callback
+        .call1(py, (array.clone(), channel.sample_rate, channel.channels))?;
+    let samples: Vec<f32> = if result.is_none(py) {
+        let bytes: Vec<u8> = array.call_method0(intern!(py, "tobytes"))?.extract()?;
+        bytes
+            .chunks_exact(4)
+            .map(|chunk| f32::from_ne_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect()
+    } else {
+        result.extract(py)?
+    };
+
+    if samples.len() != block.len() {
+        return Err(PyValueError::new_err(format!(
+            "Expected {} samples back, got {}.",
+            block.len(),
+            samples.len()
+        )));
+    }
+    block.copy_from_slice(&samples);
+    Ok(())
+}
+
+/// Sends blocks of the output to a Python callback on the worker and plays them back
+/// `LATENCY_BLOCKS` later. Every buffer is allocated here, so `process` only moves them.
+pub struct PythonProcessor {
+    enabled: Arc<Params>,
+    block_len: usize,
+    input: Vec<f32>,
+    output: VecDeque<f32>,
+    spare: Vec<Vec<f32>>,
+    channel: Arc<Channel>,
+    worker: Thread,
+}
+
+impl PythonProcessor {
+    pub fn new(
+        callback: Arc<Py<PyAny>>,
+        enabled: Arc<Params>,
+        block_size: usize,
+        sample_rate: u32,
+        channels: usize,
+    ) -> Self {
+        let block_len = block_size * channels;
+        let worker = WORKER
+            .get()
+            .expect("the worker is started by PythonEffect::new");
+        let channel = Arc::new(Channel {
+            callback,
+            sample_rate,
+            channels,
+            requests: Ring::with_capacity(POOL_BLOCKS),
+            replies: Ring::with_capacity(POOL_BLOCKS),
+        });
+        worker.channels.lock().unwrap().push(Arc::downgrade(&channel));
+
+        let mut output = VecDeque::with_capacity(block_len * POOL_BLOCKS);
+        output.resize(block_len * LATENCY_BLOCKS, 0.0);
+        let mut spare = Vec::with_capacity(POOL_BLOCKS);
+        spare.extend((1..POOL_BLOCKS).map(|_| Vec::with_capacity(block_len)));
+        PythonProcessor {
+            enabled,
+            block_len,
+            input: Vec::with_capacity(block_len),
+            output,
+            spare,
+            channel,
+            worker: worker.thread.clone(),
+        }
+    }
+}
+
+impl Processor for PythonProcessor {
+    fn process(&mut self, frame: &mut [f32]) {
+        while self.output.len() < frame.len() {
+            match self.channel.replies.pop() {
+                Some(mut block) => {
+                    self.output.extend(block.samples.drain(..));
+                    self.spare.push(block.samples);
+                }
+                None => break,
+            }
+        }
+
+        self.input.extend_from_slice(frame);
+        if self.input.len() >= self.block_len {
+            match self.spare.pop() {
+                Some(next) => {
+                    let block = Block {
+                        samples: mem::replace(&mut self.input, next),
+                        enabled: self.enabled.get(ENABLED) != 0.0,
+                    };
+                    // Never full: the ring holds the whole pool.
+                    let _ = self.channel.requests.push(block);
+                    self.worker.unpark();
+                }
+                // Every block is still with the worker, so this one is dropped.
+                None => self.input.clear(),
+            }
+        }
+
+        // Silence if the worker has fallen behind.
+        for sample in frame.iter_mut() {
+            *sample = self.output.pop_front().unwrap_or(0.0);
+        }
+    }
+
+    fn tail(&self) -> usize {
+        self.block_len / self.channel.channels * (LATENCY_BLOCKS + 1)
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[pyclass]
+pub struct PythonEffect {
+    #[serde(skip)]
+    pub callback: Option<Arc<Py<PyAny>>>,
+    #[pyo3(get)]
+    pub block_size: usize,
+    #[pyo3(get)]
+    pub apply_after: Option<f32>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub anchor: Option<Anchor>,
+}
+
+impl PartialEq for PythonEffect {
+    fn eq(&self, other: &Self) -> bool {
+        let same_callback = match (&self.callback, &other.callback) {
+            (Some(a), Some(b)) => a.is(b.as_ref()),
+            (None, None) => true,
+            _ => false,
+        };
+        same_callback
+            && self.block_size == other.block_size
+            && self.apply_after == other.apply_after
+            && self.anchor == other.anchor
+    }
+}
+
+#[pymethods]
+impl PythonEffect {
+    #[new]
+    #[pyo3(signature = (callback, block_size=1024, apply_after=None, anchor=None))]
+    pub fn new(
+        py: Python,
+        callback: Bound<'_, PyAny>,
+        block_size: usize,
+        apply_after: Option<f32>,
+        anchor: Option<Anchor>,
+    ) -> PyResult<Self> {
+        if !callback.is_callable() {
+            return Err(PyTypeError::new_err("Callback must be callable."));
+        }
+        let effect = PythonEffect {
+            callback: Some(Arc::new(callback.unbind())),
+            block_size,
+            apply_after,
+            anchor,
+        };
+        effect.validate()?;
+        start_worker(py)?;
+        Ok(effect)
+    }
+
+    #[getter]
+    fn callback(&self, py: Python) -> Option<PyObject> {
+        self.callback.as_ref().map(|callback| callback.clone_ref(py))
+    }
+
+    #[setter]
+    fn set_apply_after(&mut self, apply_after: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::PYTHON_EFFECT, "apply_after", &mut self.apply_after, apply_after)
+    }
+
+    #[staticmethod]
+    fn schema(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+        schema::to_dict(py, &schema::PYTHON_EFFECT)
+    }
+
+    fn as_dict<'py>(&self, py: Python<'py>) -> Bound<'py, PyDict> {
+        self.clone().into_py_dict_bound(py)
+    }
+}
+
+schema::validate!(PythonEffect, schema::PYTHON_EFFECT, [block_size, apply_after]);
+
+impl fmt::Display for PythonEffect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PythonEffect {{ block_size: {}, apply_after: {:?} }}",
+            self.block_size, self.apply_after)
+    }
+}
+
+impl IntoPyDict for PythonEffect {
+    fn into_py_dict_bound(self, py: Python<'_>) -> Bound<'_, PyDict> {
+        let value = serde_json::to_value(self).unwrap();
+        let dict: Bound<'_, PyDict> = json_to_py(py, &value).extract().unwrap();
+        dict.set_item("type", "PythonEffect").unwrap();
+        dict
+    }
+}
+
+pub mod automation {
+    pub const ACTIVE: usize = 0;
+    pub const TIME: usize = 1;
+    pub const VALUE: usize = 2;
+    pub const SAMPLED: usize = 3;
+}
+
+/// An automation function evaluated for one sink. The engine writes the time into `params`
+/// and applies the value the sampler writes back.
+struct Sampler {
+    function: Arc<Py<PyAny>>,
+    target: AutomationTarget,
+    params: std::sync::Weak<Params>,
+    interval: Duration,
+    next: Instant,
+    failed: bool,
+}
+
+/// Registers an automation of a sink with the sampler. Called from the audio thread.
+pub fn add_sampler(effect: &Automation, params: &Arc<Params>) {
+    let Some(function) = effect.function.clone() else {
+        return;
+    };
+    if let Some(samplers) = SAMPLERS.get() {
+        samplers.lock().unwrap().push(Sampler {
+            function,
+            target: effect.target,
+            params: Arc::downgrade(params),
+            interval: Duration::from_secs_f32(1.0 / effect.control_rate),
+            next: Instant::now(),
+            failed: false,
+        });
+        SAMPLER_ADDED.notify_one();
+    }
+}
+
+/// Evaluates every running automation that is due at its control rate, taking the GIL once
+/// for all of them.
+fn start_sampler(py: Python) -> PyResult<()> {
+    if SAMPLERS.get().is_some() {
+        return Ok(());
+    }
+    register_shutdown(py)?;
+    // The thread is spawned once the list is stored, so it never sees `SAMPLERS` unset.
+    let mut created = false;
+    let samplers = SAMPLERS.get_or_init(|| {
+        created = true;
+        Mutex::new(Vec::new())
+    });
+    if created {
+        thread::spawn(move || loop {
+            let now = Instant::now();
+            let due: Vec<(Arc<Py<PyAny>>, AutomationTarget, Arc<Params>)> = {
+                let mut samplers = samplers.lock().unwrap();
+                samplers.retain(|sampler| sampler.params.strong_count() > 0);
+                samplers
+                    .iter_mut()
+                    .filter(|sampler| !sampler.failed && sampler.next <= now)
+                    .filter_map(|sampler| {
+                        let params = sampler.params.upgrade()?;
+                        // Pending and paused automations are checked again at their rate.
+                        sampler.next = now + sampler.interval;
+                        if params.get(automation::ACTIVE) == 0.0 {
+                            return None;
+                        }
+                        Some((sampler.function.clone(), sampler.target, params))
+                    })
+                    .collect()
+            };
+
+            if !due.is_empty() {
+                let _busy = BUSY.lock().unwrap();
+                if !SHUTDOWN.load(Ordering::SeqCst) {
+                    Python::with_gil(|py| {
+                        for (function, target, params) in due {
+                            let value = function
+                                .call1(py, (params.get(automation::TIME),))
+                                .and_then(|value| value.extract::<f32>(py))
+                                .and_then(|value| target.validate(value).map(|_| value));
+                            match value {
+                                Ok(value) => {
+                                    params.set(automation::VALUE, value);
+                                    params.set(automation::SAMPLED, 1.0);
+                                }
+                                Err(e) => {
+                                    eprintln!("Failed to run Automation function: {}", e);
+                                    fail(&params);
+                                }
+                            }
+                        }
+                    });
+                }
+            }
+
+            // Sleeps until the next sampler is due, or until one is added.
+            let samplers = samplers.lock().unwrap();
+            let next = samplers
+                .iter()
+                .filter(|sampler| !sampler.failed)
+                .map(|sampler| sampler.next)
+                .min();
+            match next {
+                Some(next) => {
+                    let wait = next.saturating_duration_since(Instant::now());
+                    drop(SAMPLER_ADDED.wait_timeout(samplers, wait).unwrap());
+                }
+                None => drop(SAMPLER_ADDED.wait(samplers).unwrap()),
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Stops sampling a function after it raised or returned an invalid value.
+fn fail(params: &Arc<Params>) {
+    if let Some(samplers) = SAMPLERS.get() {
+        for sampler in samplers.lock().unwrap().iter_mut() {
+            if sampler.params.upgrade().is_some_and(|p| Arc::ptr_eq(&p, params)) {
+                sampler.failed = true;
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[pyclass]
+pub struct Automation {
+    #[serde(skip)]
+    pub function: Option<Arc<Py<PyAny>>>,
+    #[pyo3(get)]
+    pub target: AutomationTarget,
+    #[pyo3(get)]
+    pub control_rate: f32,
+    #[pyo3(get)]
+    pub duration: Option<f32>,
+    #[pyo3(get)]
+    pub apply_after: Option<f32>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub anchor: Option<Anchor>,
+}
+
+impl PartialEq for Automation {
+    fn eq(&self, other: &Self) -> bool {
+        let same_function = match (&self.function, &other.function) {
+            (Some(a), Some(b)) => a.is(b.as_ref()),
+            (None, None) => true,
+            _ => false,
+        };
+        same_function
+            && self.target == other.target
+            && self.control_rate == other.control_rate
+            && self.duration == other.duration
+            && self.apply_after == other.apply_after
+            && self.anchor == other.anchor
+    }
+}
+
+#[pymethods]
+impl Automation {
+    #[new]
+    #[pyo3(signature = (function, target=AutomationTarget::Volume, duration=None, control_rate=100.0, apply_after=None, anchor=None))]
+    pub fn new(
+        py: Python,
+        function: Bound<'_, PyAny>,
+        target: AutomationTarget,
+        duration: Option<f32>,
+        control_rate: f32,
+        apply_after: Option<f32>,
+        anchor: Option<Anchor>,
+    ) -> PyResult<Self> {
+        if !function.is_callable() {
+            return Err(PyTypeError::new_err("Function must be callable."));
+        }
+        let automation = Automation {
+            function: Some(Arc::new(function.unbind())),
+            target,
+            control_rate,
+            duration,
+            apply_after,
+            anchor,
+        };
+        automation.validate()?;
+        start_sampler(py)?;
+        Ok(automation)
+    }
+
+    #[getter]
+    fn function(&self, py: Python) -> Option<PyObject> {
+        self.function.as_ref().map(|function| function.clone_ref(py))
+    }
+
+    #[setter]
+    fn set_duration(&mut self, duration: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::AUTOMATION, "duration", &mut self.duration, duration)
+    }
+
+    #[setter]
+    fn set_apply_after(&mut self, apply_after: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::AUTOMATION, "apply_after", &mut self.apply_after, apply_after)
+    }
+
+    #[staticmethod]
+    fn schema(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+        schema::to_dict(py, &schema::AUTOMATION)
+    }
+
+    fn as_dict<'py>(&self, py: Python<'py>) -> Bound<'py, PyDict> {
+        self.clone().into_py_dict_bound(py)
+    }
+}
+
+schema::validate!(Automation, schema::AUTOMATION, [duration, control_rate, apply_after]);
+
+impl fmt::Display for Automation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Automation {{ target: {:?}, control_rate: {}, duration: {:?}, apply_after: {:?} }}",
+            self.target, self.control_rate, self.duration, self.apply_after)
+    }
+}
+
+impl IntoPyDict for Automation {
+    fn into_py_dict_bound(self, py: Python<'_>) -> Bound<'_, PyDict> {
+        let value = serde_json::to_value(self).unwrap();
+        let dict: Bound<'_, PyDict> = json_to_py(py, &value).extract().unwrap();
+        dict.set_item("type", "Automation").unwrap();
+        dict
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ring_is_fifo_and_bounded() {
+        let ring = Ring::with_capacity(2);
+        assert_eq!(ring.pop(), None);
+        for round in 0..3 {
+            assert_eq!(ring.push(round), Ok(()));
+            assert_eq!(ring.push(round + 10), Ok(()));
+            assert_eq!(ring.push(round + 20), Err(round + 20));
+            assert_eq!(ring.pop(), Some(round));
+            assert_eq!(ring.pop(), Some(round + 10));
+            assert_eq!(ring.pop(), None);
+        }
+    }
+
+    #[test]
+    fn ring_hands_values_across_threads() {
+        let ring = Arc::new(Ring::with_capacity(POOL_BLOCKS));
+        let producer = ring.clone();
+        let sender = thread::spawn(move || {
+            for value in 0..10_000 {
+                while producer.push(value).is_err() {
+                    thread::yield_now();
+                }
+            }
+        });
+        let mut expected = 0;
+        while expected < 10_000 {
+            if let Some(value) = ring.pop() {
+                assert_eq!(value, expected);
+                expected += 1;
+            }
+        }
+        sender.join().unwrap();
+    }
+}
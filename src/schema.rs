@@ -0,0 +1,497 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::dsp::Setting;
+
+/// A declared effect parameter. Numeric parameters are validated against their bounds in the
+/// constructor and setters, and the same declarations are exposed to Python via `schema()`.
+pub struct Param {
+    pub name: &'static str,
+    pub kind: &'static str,
+    pub default: Option<f32>,
+    pub min: Option<f32>,
+    pub max: Option<f32>,
+    /// Whether `min` itself is out of range.
+    pub exclusive_min: bool,
+    /// Whether `max` itself is out of range.
+    pub exclusive_max: bool,
+    pub description: &'static str,
+}
+
+impl Param {
+    const fn float(name: &'static str, default: Option<f32>, description: &'static str) -> Self {
+        Param {
+            name,
+            kind: "float",
+            default,
+            min: None,
+            max: None,
+            exclusive_min: false,
+            exclusive_max: false,
+            description,
+        }
+    }
+
+    /// A whole number parameter, checked like a float.
+    const fn int(name: &'static str, default: f32, description: &'static str) -> Self {
+        let mut param = Param::float(name, Some(default), description);
+        param.kind = "int";
+        param
+    }
+
+    const fn between(mut self, min: f32, max: f32) -> Self {
+        self.min = Some(min);
+        self.max = Some(max);
+        self
+    }
+
+    const fn at_least(mut self, min: f32) -> Self {
+        self.min = Some(min);
+        self
+    }
+
+    const fn above(mut self, min: f32) -> Self {
+        self.min = Some(min);
+        self.exclusive_min = true;
+        self
+    }
+
+    const fn at_most(mut self, max: f32) -> Self {
+        self.max = Some(max);
+        self
+    }
+
+    const fn below(mut self, max: f32) -> Self {
+        self.max = Some(max);
+        self.exclusive_max = true;
+        self
+    }
+
+    const fn other(name: &'static str, kind: &'static str, description: &'static str) -> Self {
+        Param {
+            name,
+            kind,
+            default: None,
+            min: None,
+            max: None,
+            exclusive_min: false,
+            exclusive_max: false,
+            description,
+        }
+    }
+
+    pub fn check(&self, value: Option<f32>) -> PyResult<()> {
+        let Some(value) = value else {
+            return Ok(());
+        };
+        let above_min = match self.min {
+            Some(min) if self.exclusive_min => value > min,
+            Some(min) => value >= min,
+            None => true,
+        };
+        let below_max = match self.max {
+            Some(max) if self.exclusive_max => value < max,
+            Some(max) => value <= max,
+            None => true,
+        };
+        let in_range = value.is_finite() && above_min && below_max;
+        if in_range {
+            return Ok(());
+        }
+        Err(PyValueError::new_err(format!(
+            "{} must be {}, got {}.",
+            self.name,
+            self.expected(),
+            value
+        )))
+    }
+
+    /// The allowed range, as it reads in an error message.
+    fn expected(&self) -> String {
+        if let (Some(min), Some(max), false, false) =
+            (self.min, self.max, self.exclusive_min, self.exclusive_max)
+        {
+            return format!("between {} and {}", self.bound(min), self.bound(max));
+        }
+        let lower = self.min.map(|min| match self.exclusive_min {
+            true => format!("greater than {}", self.bound(min)),
+            false => format!("at least {}", self.bound(min)),
+        });
+        let upper = self.max.map(|max| match self.exclusive_max {
+            true => format!("less than {}", self.bound(max)),
+            false => format!("at most {}", self.bound(max)),
+        });
+        match (lower, upper) {
+            (Some(lower), Some(upper)) => format!("{} and {}", lower, upper),
+            (Some(bound), None) | (None, Some(bound)) => bound,
+            (None, None) => "a finite number".to_string(),
+        }
+    }
+
+    fn bound(&self, value: f32) -> String {
+        match self.kind {
+            "int" => format!("{}", value as i64),
+            _ => format!("{:?}", value),
+        }
+    }
+
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new_bound(py);
+        dict.set_item("type", self.kind)?;
+        dict.set_item("default", self.default)?;
+        dict.set_item("min", self.min)?;
+        dict.set_item("max", self.max)?;
+        dict.set_item("exclusive_min", self.exclusive_min)?;
+        dict.set_item("exclusive_max", self.exclusive_max)?;
+        dict.set_item("description", self.description)?;
+        Ok(dict)
+    }
+}
+
+pub fn find(params: &'static [Param], name: &str) -> &'static Param {
+    params
+        .iter()
+        .find(|param| param.name == name)
+        .expect("undeclared effect parameter")
+}
+
+/// An effect field declared in a schema table, as the effect holds it.
+pub trait Field {
+    /// What the field's setter takes.
+    type Value;
+
+    /// The number a new value is checked as, or None for an unset optional field.
+    fn number(value: &Self::Value) -> Option<f32>;
+
+    /// The number the field currently holds.
+    fn current(&self) -> Option<f32>;
+
+    fn store(&mut self, value: Self::Value);
+}
+
+impl Field for f32 {
+    type Value = f32;
+
+    fn number(value: &f32) -> Option<f32> {
+        Some(*value)
+    }
+
+    fn current(&self) -> Option<f32> {
+        Some(*self)
+    }
+
+    fn store(&mut self, value: f32) {
+        *self = value;
+    }
+}
+
+impl Field for Option<f32> {
+    type Value = Option<f32>;
+
+    fn number(value: &Option<f32>) -> Option<f32> {
+        *value
+    }
+
+    fn current(&self) -> Option<f32> {
+        *self
+    }
+
+    fn store(&mut self, value: Option<f32>) {
+        *self = value;
+    }
+}
+
+impl Field for usize {
+    type Value = usize;
+
+    fn number(value: &usize) -> Option<f32> {
+        Some(*value as f32)
+    }
+
+    fn current(&self) -> Option<f32> {
+        Some(*self as f32)
+    }
+
+    fn store(&mut self, value: usize) {
+        *self = value;
+    }
+}
+
+/// Settings are stored in place, so the processors sharing them hear the new value.
+impl Field for Setting {
+    type Value = f32;
+
+    fn number(value: &f32) -> Option<f32> {
+        Some(*value)
+    }
+
+    fn current(&self) -> Option<f32> {
+        Some(self.get())
+    }
+
+    fn store(&mut self, value: f32) {
+        self.set(value);
+    }
+}
+
+/// Checks `value` against the parameter `name` in `params`, then stores it in `field`. Every
+/// checked setter goes through here, so an out of range value leaves the field unchanged.
+pub fn update<F: Field>(
+    params: &'static [Param],
+    name: &str,
+    field: &mut F,
+    value: F::Value,
+) -> PyResult<()> {
+    find(params, name).check(F::number(&value))?;
+    field.store(value);
+    Ok(())
+}
+
+/// Implements `validate` for an effect, checking each listed field against the parameter of the
+/// same name in the effect's schema table. Constructors call it, as does `ActionType::validate`
+/// for effects read from a preset or a snapshot.
+macro_rules! validate {
+    ($effect:ty, $params:expr, [$($field:ident),+ $(,)?]) => {
+        impl $effect {
+            pub fn validate(&self) -> pyo3::PyResult<()> {
+                $(
+                    $crate::schema::find(&$params, stringify!($field))
+                        .check($crate::schema::Field::current(&self.$field))?;
+                )+
+                Ok(())
+            }
+        }
+    };
+}
+
+pub(crate) use validate;
+
+/// Builds the dict each effect's `schema()` returns: one entry per parameter, keyed by name,
+/// with its type, default, bounds and description, for building effect editors.
+pub fn to_dict<'py>(py: Python<'py>, params: &[Param]) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new_bound(py);
+    for param in params {
+        dict.set_item(param.name, param.to_dict(py)?)?;
+    }
+    Ok(dict)
+}
+
+const DURATION: &str = "Length of the ramp in seconds.";
+const APPLY_AFTER: &str = "Seconds after the anchor before the effect starts.";
+const ANCHOR: Param = Param::other("anchor", "Anchor", "The point apply_after is measured from.");
+const CURVE: Param = Param::other("curve", "Curve | CubicBezier", "Easing applied to the ramp.");
+const START: Param = Param::float("apply_after", None, APPLY_AFTER).at_least(0.0);
+const SWEEP: Param = Param::float("duration", None, "Length of the sweep in seconds.").at_least(0.0);
+const SWEEP_CURVE: Param = Param::other("curve", "Curve | CubicBezier", "Easing applied to the sweep.");
+const RATE: &str = "LFO rate in Hz.";
+const DEPTH: &str = "How far the LFO sweeps.";
+const FEEDBACK: &str = "Amount of output fed back into the effect.";
+const MIX: &str = "Wet/dry balance, from dry to only the effect.";
+const THRESHOLD: &str = "Level in dB the effect acts on.";
+const RELEASE: &str = "Seconds to recover after the level falls.";
+const AMOUNT: &str = "Blend of the processed signal with the dry one.";
+const LFO_DURATION: &str = "Seconds the effect runs for. If None, it runs until the audio ends.";
+const CONTROL: Param = Param::other("target", "AutomationTarget", "The parameter to automate.");
+
+pub const FADE_IN: [Param; 6] = [
+    Param::float("duration", Some(2.0), DURATION).at_least(0.0),
+    Param::float("start_val", Some(0.0), "Volume at the start of the fade.").between(0.0, 1.0),
+    Param::float("end_val", Some(1.0), "Volume at the end of the fade.").between(0.0, 1.0),
+    START,
+    ANCHOR,
+    CURVE,
+];
+
+pub const FADE_OUT: [Param; 6] = [
+    Param::float("duration", Some(2.0), DURATION).at_least(0.0),
+    Param::float("start_val", Some(1.0), "Volume at the start of the fade.").between(0.0, 1.0),
+    Param::float("end_val", Some(0.0), "Volume at the end of the fade.").between(0.0, 1.0),
+    Param::float(
+        "apply_after",
+        None,
+        "Seconds after the anchor before the effect starts. If None, the fade ends with the audio.",
+    )
+    .at_least(0.0),
+    ANCHOR,
+    CURVE,
+];
+
+pub const CHANGE_SPEED: [Param; 6] = [
+    Param::float("duration", Some(0.0), DURATION).at_least(0.0),
+    Param::float("start_val", Some(1.0), "Playback speed at the start of the change.").above(0.0),
+    Param::float("end_val", Some(1.5), "Playback speed at the end of the change.").above(0.0),
+    START,
+    ANCHOR,
+    CURVE,
+];
+
+pub const FILTER: [Param; 9] = [
+    Param::other("filter_type", "FilterType", "The filter response."),
+    Param::float("cutoff", Some(1000.0), "Cutoff or center frequency in Hz.").above(0.0),
+    Param::float("q", Some(0.707), "Resonance of the filter.").above(0.0),
+    Param::float("gain", Some(0.0), "Boost or cut in dB for shelf and peak filters."),
+    Param::float("start_cutoff", None, "Cutoff in Hz to sweep from. If None, there is no sweep.")
+        .above(0.0),
+    SWEEP,
+    START,
+    ANCHOR,
+    SWEEP_CURVE,
+];
+
+pub const EQ_BAND: [Param; 4] = [
+    Param::other("band_type", "FilterType", "The band response."),
+    Param::float("frequency", Some(1000.0), "Center or cutoff frequency in Hz.").above(0.0),
+    Param::float("gain", Some(0.0), "Boost or cut in dB for peak and shelf bands."),
+    Param::float("q", Some(0.707), "Width of the band.").above(0.0),
+];
+
+pub const PARAMETRIC_EQ: [Param; 3] = [
+    Param::other("bands", "list[EQBand]", "The bands, applied in order."),
+    START,
+    ANCHOR,
+];
+
+pub const REVERB: [Param; 6] = [
+    Param::float("room_size", Some(0.5), "Size of the room. Larger rooms have longer tails.")
+        .between(0.0, 1.0),
+    Param::float("damping", Some(0.5), "High frequency absorption, from bright to dark.")
+        .between(0.0, 1.0),
+    Param::float("mix", Some(0.3), MIX).between(0.0, 1.0),
+    Param::float("pre_delay", Some(0.0), "Seconds before the reverb starts.").between(0.0, 1.0),
+    START,
+    ANCHOR,
+];
+
+pub const DELAY: [Param; 8] = [
+    Param::float("time", Some(0.25), "Delay time in seconds.").above(0.0).at_most(4.0),
+    Param::float("feedback", Some(0.35), "Amount of each echo fed back into the delay.")
+        .at_least(0.0)
+        .below(1.0),
+    Param::float("mix", Some(0.3), MIX).between(0.0, 1.0),
+    Param::other("ping_pong", "bool", "Bounce echoes between the left and right channels."),
+    Param::other("note", "NoteValue", "Note value of the delay time, used with bpm."),
+    Param::float("bpm", None, "Tempo in beats per minute, used with note.").above(0.0),
+    START,
+    ANCHOR,
+];
+
+pub const COMPRESSOR: [Param; 8] = [
+    Param::float("threshold", Some(-18.0), THRESHOLD).at_most(0.0),
+    Param::float("ratio", Some(4.0), "Input to output ratio above the threshold.").at_least(1.0),
+    Param::float("attack", Some(0.01), "Seconds to react to a rise in level.").between(0.0, 1.0),
+    Param::float("release", Some(0.1), RELEASE).above(0.0).at_most(5.0),
+    Param::float("knee", Some(6.0), "Width in dB of the soft knee.").at_least(0.0),
+    Param::float("makeup", Some(0.0), "Gain in dB added after compression."),
+    START,
+    ANCHOR,
+];
+
+pub const LIMITER: [Param; 4] = [
+    Param::float("threshold", Some(-1.0), THRESHOLD).at_most(0.0),
+    Param::float("release", Some(0.05), RELEASE).above(0.0).at_most(5.0),
+    START,
+    ANCHOR,
+];
+
+pub const NOISE_GATE: [Param; 6] = [
+    Param::float("threshold", Some(-50.0), THRESHOLD).at_most(0.0),
+    Param::float("attack", Some(0.002), "Seconds for the gate to open.").between(0.0, 1.0),
+    Param::float("release", Some(0.1), RELEASE).above(0.0).at_most(5.0),
+    Param::float("range", Some(80.0), "Attenuation in dB while the gate is closed.").above(0.0),
+    START,
+    ANCHOR,
+];
+
+pub const DISTORTION: [Param; 8] = [
+    Param::other("distortion_type", "DistortionType", "The waveshaper."),
+    Param::float("drive", Some(12.0), "Gain in dB into the waveshaper.").between(0.0, 48.0),
+    Param::float("amount", Some(1.0), AMOUNT).between(0.0, 1.0),
+    Param::float("start_amount", None, "Amount to sweep from. If None, there is no sweep.")
+        .between(0.0, 1.0),
+    SWEEP,
+    START,
+    ANCHOR,
+    SWEEP_CURVE,
+];
+
+pub const BITCRUSHER: [Param; 8] = [
+    Param::float("bits", Some(8.0), "Bit depth.").between(1.0, 24.0),
+    Param::float("sample_rate", Some(11025.0), "Sample rate in Hz to imitate.").above(0.0),
+    Param::float("amount", Some(1.0), AMOUNT).between(0.0, 1.0),
+    Param::float("start_amount", None, "Amount to sweep from. If None, there is no sweep.")
+        .between(0.0, 1.0),
+    SWEEP,
+    START,
+    ANCHOR,
+    SWEEP_CURVE,
+];
+
+pub const CHORUS: [Param; 6] = [
+    Param::float("rate", Some(0.8), RATE).above(0.0).at_most(20.0),
+    Param::float("depth", Some(0.5), DEPTH).between(0.0, 1.0),
+    Param::float("feedback", Some(0.0), FEEDBACK).between(-0.95, 0.95),
+    Param::float("mix", Some(0.5), MIX).between(0.0, 1.0),
+    START,
+    ANCHOR,
+];
+
+pub const FLANGER: [Param; 6] = [
+    Param::float("rate", Some(0.25), RATE).above(0.0).at_most(20.0),
+    Param::float("depth", Some(0.7), DEPTH).between(0.0, 1.0),
+    Param::float("feedback", Some(0.5), FEEDBACK).between(-0.95, 0.95),
+    Param::float("mix", Some(0.5), MIX).between(0.0, 1.0),
+    START,
+    ANCHOR,
+];
+
+pub const PHASER: [Param; 7] = [
+    Param::float("rate", Some(0.5), RATE).above(0.0).at_most(20.0),
+    Param::float("depth", Some(0.7), DEPTH).between(0.0, 1.0),
+    Param::float("feedback", Some(0.3), FEEDBACK).between(-0.95, 0.95),
+    Param::float("mix", Some(0.5), MIX).between(0.0, 1.0),
+    Param::int("stages", 4.0, "Number of allpass stages.").between(2.0, 12.0),
+    START,
+    ANCHOR,
+];
+
+pub const TREMOLO: [Param; 6] = [
+    Param::float("rate", Some(5.0), RATE).above(0.0).at_most(20.0),
+    Param::float("depth", Some(0.5), "Volume dip at the bottom of each cycle.").between(0.0, 1.0),
+    Param::other("waveform", "Waveform", "Shape of the LFO."),
+    Param::float("duration", None, LFO_DURATION).above(0.0),
+    START,
+    ANCHOR,
+];
+
+pub const VIBRATO: [Param; 6] = [
+    Param::float("rate", Some(5.0), RATE).above(0.0).at_most(20.0),
+    Param::float("depth", Some(0.3), "Pitch sweep.").between(0.0, 1.0),
+    Param::other("waveform", "Waveform", "Shape of the LFO."),
+    Param::float("duration", None, LFO_DURATION).above(0.0),
+    START,
+    ANCHOR,
+];
+
+pub const ENVELOPE: [Param; 4] = [
+    Param::other("points", "list[Breakpoint]", "Breakpoints as (time, value, curve), in increasing time order."),
+    CONTROL,
+    START,
+    ANCHOR,
+];
+
+pub const AUTOMATION: [Param; 6] = [
+    Param::other("function", "Callable", "Called with the elapsed seconds, returns the value."),
+    CONTROL,
+    Param::float("duration", None, LFO_DURATION).above(0.0),
+    Param::float("control_rate", Some(100.0), "Calls per second.").above(0.0).at_most(1000.0),
+    START,
+    ANCHOR,
+];
+
+pub const PYTHON_EFFECT: [Param; 4] = [
+    Param::other("callback", "Callable", "Called as callback(block, sample_rate, channels)."),
+    Param::int("block_size", 1024.0, "Frames per block.").between(16.0, 65536.0),
+    START,
+    ANCHOR,
+];
@@ -0,0 +1,202 @@
+use crate::audioqueue::AudioChannel;
+use crate::engine::{self, EngineCommand, ScheduledAction, VoiceId};
+use crate::timesync::ScheduledEvent;
+use crate::AudioSink;
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, FromPyObject)]
+pub enum SyncMember {
+    Sink(AudioSink),
+    Channel(AudioChannel),
+}
+
+impl SyncMember {
+    fn same(&self, other: &SyncMember) -> bool {
+        match (self, other) {
+            (SyncMember::Sink(a), SyncMember::Sink(b)) => a.voice_id() == b.voice_id(),
+            (SyncMember::Channel(a), SyncMember::Channel(b)) => a.same_channel(b),
+            _ => false,
+        }
+    }
+
+    /// The sink `sink` would return, without cueing it.
+    fn peek(&self, cue: bool) -> Option<AudioSink> {
+        match self {
+            SyncMember::Sink(sink) => Some(sink.clone()),
+            SyncMember::Channel(channel) if cue => channel.next_audio(),
+            SyncMember::Channel(channel) => channel.current_audio(),
+        }
+    }
+
+    fn sink(&self, py: Python, cue: bool) -> Option<AudioSink> {
+        match self {
+            SyncMember::Sink(sink) => Some(sink.clone()),
+            SyncMember::Channel(channel) if cue => channel.cue(py),
+            SyncMember::Channel(channel) => channel.current_audio(),
+        }
+    }
+}
+
+impl IntoPy<PyObject> for SyncMember {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        match self {
+            SyncMember::Sink(sink) => sink.into_py(py),
+            SyncMember::Channel(channel) => channel.into_py(py),
+        }
+    }
+}
+
+/// Controls several sinks and channels as one. Transport changes are scheduled on the same
+/// output frame for every member.
+#[derive(Clone)]
+#[pyclass]
+pub struct SyncGroup {
+    members: Arc<Mutex<Vec<SyncMember>>>,
+}
+
+impl SyncGroup {
+    fn sinks(&self, py: Python, cue: bool) -> Vec<AudioSink> {
+        self.members()
+            .iter()
+            .filter_map(|member| member.sink(py, cue))
+            .collect()
+    }
+
+    fn ids(&self, py: Python, cue: bool) -> PyResult<Vec<VoiceId>> {
+        // Checked before cueing, so a refused start leaves the channel queues as they were.
+        let members = self.members();
+        let mut next = members.iter().filter_map(|member| member.peek(cue));
+        if next.any(|sink| !sink.is_loaded()) {
+            return Err(PyRuntimeError::new_err(
+                "All sinks in a sync group must be loaded.",
+            ));
+        }
+        Ok(self.sinks(py, cue).iter().map(AudioSink::voice_id).collect())
+    }
+}
+
+#[pymethods]
+impl SyncGroup {
+    #[new]
+    #[pyo3(signature = (members=None))]
+    pub fn new(members: Option<Vec<SyncMember>>) -> Self {
+        let group = SyncGroup {
+            members: Arc::new(Mutex::new(Vec::new())),
+        };
+        for member in members.unwrap_or_default() {
+            group.add(member);
+        }
+        group
+    }
+
+    pub fn add(&self, member: SyncMember) {
+        let mut members = self.members.lock().unwrap();
+        if !members.iter().any(|existing| existing.same(&member)) {
+            members.push(member);
+        }
+    }
+
+    pub fn remove(&self, member: SyncMember) -> PyResult<()> {
+        let mut members = self.members.lock().unwrap();
+        let count = members.len();
+        members.retain(|existing| !existing.same(&member));
+        if members.len() == count {
+            return Err(PyValueError::new_err("Member is not part of this sync group."));
+        }
+        Ok(())
+    }
+
+    #[getter]
+    pub fn members(&self) -> Vec<SyncMember> {
+        self.members.lock().unwrap().clone()
+    }
+
+    #[getter]
+    pub fn is_playing(&self, py: Python) -> bool {
+        self.sinks(py, false).iter().any(AudioSink::is_playing)
+    }
+
+    fn __len__(&self) -> usize {
+        self.members.lock().unwrap().len()
+    }
+
+    pub fn play(&self, py: Python) -> PyResult<()> {
+        let ids = self.ids(py, true)?;
+        engine::request(py, |reply| EngineCommand::Play { ids, reply })?;
+
+        for member in self.members.lock().unwrap().iter() {
+            if let SyncMember::Channel(channel) = member {
+                *channel.auto_consume.lock().unwrap() = true;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn play_at(&self, py: Python, timestamp: f64) -> PyResult<ScheduledEvent> {
+        let ids = self.ids(py, true)?;
+        let event = ScheduledEvent::schedule(py, ids, timestamp, ScheduledAction::Play)?;
+
+        for member in self.members.lock().unwrap().iter() {
+            if let SyncMember::Channel(channel) = member {
+                *channel.auto_consume.lock().unwrap() = true;
+            }
+        }
+        Ok(event)
+    }
+
+    pub fn stop_at(&self, py: Python, timestamp: f64) -> PyResult<ScheduledEvent> {
+        let ids = self.ids(py, false)?;
+        ScheduledEvent::schedule(py, ids, timestamp, ScheduledAction::Stop)
+    }
+
+    pub fn pause(&self, py: Python) -> PyResult<()> {
+        let ids = self.ids(py, false)?;
+        engine::request(py, |reply| EngineCommand::Pause { ids, reply })
+    }
+
+    pub fn stop(&self, py: Python) -> PyResult<()> {
+        for member in self.members.lock().unwrap().iter() {
+            if let SyncMember::Channel(channel) = member {
+                *channel.auto_consume.lock().unwrap() = false;
+            }
+        }
+
+        let ids = self.ids(py, false)?;
+        engine::request(py, |reply| EngineCommand::Stop { ids, reply })
+    }
+
+    pub fn try_seek(&self, py: Python, position: f32) -> PyResult<()> {
+        if position < 0.0 {
+            return Err(PyValueError::new_err("Position must be non-negative."));
+        }
+
+        let ids = self.ids(py, false)?;
+        engine::request(py, |reply| EngineCommand::Seek {
+            ids,
+            position,
+            reply,
+        })
+    }
+
+    pub fn set_speed(&self, py: Python, speed: f32) -> PyResult<()> {
+        if speed <= 0.0 {
+            return Err(PyValueError::new_err("Speed must be greater than 0."));
+        }
+
+        let ids = self.ids(py, false)?;
+        engine::request(py, |reply| EngineCommand::SetSpeed { ids, speed, reply })?;
+        for sink in self.sinks(py, false) {
+            sink.remember_speed(speed);
+        }
+        Ok(())
+    }
+
+    pub fn get_pos(&self, py: Python) -> PyResult<f64> {
+        match self.sinks(py, false).first() {
+            Some(sink) => sink.get_pos(),
+            None => Err(PyRuntimeError::new_err("No sink available. Load audio first.")),
+        }
+    }
+}
@@ -1,21 +1,236 @@
+use std::f32::consts::{FRAC_PI_2, PI};
 use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+use pyo3::exceptions::PyValueError;
 use pyo3::{prelude::*, types::{IntoPyDict, PyDict}};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
+use crate::effects::{
+    Bitcrusher, Chorus, Compressor, Delay, Distortion, Filter, Flanger, Limiter, NoiseGate,
+    ParametricEQ, Phaser, Reverb, Tremolo, Vibrato,
+};
+use crate::pyeffect::{Automation, PythonEffect};
+use crate::engine::{
+    self, EffectControl, EffectId, EffectStatus, EngineCommand, EventId, ScheduledAction, VoiceId,
+};
+use crate::schema;
 use crate::utils::json_to_py;
 
-#[derive(Clone, Debug, Copy, PartialEq, Serialize)]
+/// Dynamic range covered by the exponential and logarithmic curves, in nepers (60 dB).
+const CURVE_RANGE: f32 = 6.907_755;
+
+#[pyclass(eq, eq_int)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[allow(clippy::enum_variant_names)]
+pub enum Curve {
+    #[default]
+    Linear,
+    Exponential,
+    Logarithmic,
+    EqualPower,
+    SCurve,
+}
+
+#[derive(Clone, Debug, Copy, PartialEq, Serialize, Deserialize)]
+#[pyclass]
+pub struct CubicBezier {
+    #[pyo3(get)]
+    pub x1: f32,
+    #[pyo3(get)]
+    pub y1: f32,
+    #[pyo3(get)]
+    pub x2: f32,
+    #[pyo3(get)]
+    pub y2: f32,
+}
+
+#[pymethods]
+impl CubicBezier {
+    #[new]
+    pub fn new(x1: f32, y1: f32, x2: f32, y2: f32) -> PyResult<Self> {
+        if !(0.0..=1.0).contains(&x1) || !(0.0..=1.0).contains(&x2) {
+            return Err(PyValueError::new_err("x1 and x2 must be between 0.0 and 1.0."));
+        }
+        if !y1.is_finite() || !y2.is_finite() {
+            return Err(PyValueError::new_err("y1 and y2 must be finite numbers."));
+        }
+        Ok(CubicBezier { x1, y1, x2, y2 })
+    }
+
+    fn __repr__(&self) -> String {
+        format!("CubicBezier({}, {}, {}, {})", self.x1, self.y1, self.x2, self.y2)
+    }
+}
+
+impl CubicBezier {
+    fn bezier(a: f32, b: f32, s: f32) -> f32 {
+        let inv = 1.0 - s;
+        3.0 * inv * inv * s * a + 3.0 * inv * s * s * b + s * s * s
+    }
+
+    /// Same as CSS `cubic-bezier()`: finds the curve parameter whose x is `t` and returns its y.
+    fn apply(&self, t: f32) -> f32 {
+        let (mut low, mut high) = (0.0f32, 1.0f32);
+        let mut s = t;
+        for _ in 0..32 {
+            let x = Self::bezier(self.x1, self.x2, s) - t;
+            if x.abs() < 1e-6 {
+                break;
+            }
+            if x > 0.0 {
+                high = s;
+            } else {
+                low = s;
+            }
+            s = (low + high) / 2.0;
+        }
+        Self::bezier(self.y1, self.y2, s)
+    }
+}
+
+/// The shape of an effect's ramp, either a preset `Curve` or a `CubicBezier`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, FromPyObject)]
+#[serde(untagged)]
+pub enum Easing {
+    Preset(Curve),
+    CubicBezier(CubicBezier),
+}
+
+impl Default for Easing {
+    fn default() -> Self {
+        Easing::Preset(Curve::Linear)
+    }
+}
+
+impl IntoPy<PyObject> for Easing {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        match self {
+            Easing::Preset(curve) => curve.into_py(py),
+            Easing::CubicBezier(bezier) => bezier.into_py(py),
+        }
+    }
+}
+
+impl Easing {
+    /// Maps linear progress to eased progress. Equal power follows a sine when rising and a
+    /// cosine when `falling`, so a fade out and a fade in of the same length keep constant power.
+    pub fn apply(&self, t: f32, falling: bool) -> f32 {
+        match self {
+            Easing::Preset(Curve::Linear) => t,
+            Easing::Preset(Curve::Exponential) => {
+                ((CURVE_RANGE * t).exp() - 1.0) / (CURVE_RANGE.exp() - 1.0)
+            }
+            Easing::Preset(Curve::Logarithmic) => {
+                (1.0 + (CURVE_RANGE.exp() - 1.0) * t).ln() / CURVE_RANGE
+            }
+            Easing::Preset(Curve::EqualPower) if falling => 1.0 - (t * FRAC_PI_2).cos(),
+            Easing::Preset(Curve::EqualPower) => (t * FRAC_PI_2).sin(),
+            Easing::Preset(Curve::SCurve) => (1.0 - (t * PI).cos()) / 2.0,
+            Easing::CubicBezier(bezier) => bezier.apply(t),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Copy, PartialEq, Serialize, Deserialize)]
 #[pyclass]
 pub struct FadeIn {
-    #[pyo3(get, set)]
+    #[pyo3(get)]
     pub duration: Option<f32>,
-    #[pyo3(get, set)]
+    #[pyo3(get)]
     pub start_val: Option<f32>,
-    #[pyo3(get, set)]
+    #[pyo3(get)]
     pub end_val: Option<f32>,
-    #[pyo3(get, set)]
+    #[pyo3(get)]
     pub apply_after: Option<f32>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub anchor: Option<Anchor>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub curve: Easing,
+}
+
+#[pyclass(eq, eq_int)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum AnchorKind {
+    Now,
+    Absolute,
+    BeforeEnd,
+    NextPlay,
+    Seek,
+}
+
+/// The point an effect's `apply_after` is measured from.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[pyclass(eq)]
+pub struct Anchor {
+    #[pyo3(get)]
+    pub kind: AnchorKind,
+    #[pyo3(get)]
+    pub seconds: Option<f32>,
+}
+
+#[pymethods]
+impl Anchor {
+    /// The position when the sink receives the effect.
+    #[staticmethod]
+    pub fn now() -> Self {
+        Anchor {
+            kind: AnchorKind::Now,
+            seconds: None,
+        }
+    }
+
+    #[staticmethod]
+    pub fn absolute(position: f32) -> PyResult<Self> {
+        if !(position.is_finite() && position >= 0.0) {
+            return Err(PyValueError::new_err("Position must be a non-negative number."));
+        }
+        Ok(Anchor {
+            kind: AnchorKind::Absolute,
+            seconds: Some(position),
+        })
+    }
+
+    /// `seconds` before the end of the audio, or the effect's duration if None so the effect
+    /// finishes with the audio.
+    #[staticmethod]
+    #[pyo3(signature = (seconds=None))]
+    pub fn before_end(seconds: Option<f32>) -> PyResult<Self> {
+        if seconds.is_some_and(|seconds| !(seconds.is_finite() && seconds >= 0.0)) {
+            return Err(PyValueError::new_err("Seconds must be a non-negative number."));
+        }
+        Ok(Anchor {
+            kind: AnchorKind::BeforeEnd,
+            seconds,
+        })
+    }
+
+    #[staticmethod]
+    pub fn on_next_play() -> Self {
+        Anchor {
+            kind: AnchorKind::NextPlay,
+            seconds: None,
+        }
+    }
+
+    #[staticmethod]
+    pub fn on_seek() -> Self {
+        Anchor {
+            kind: AnchorKind::Seek,
+            seconds: None,
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        match self.seconds {
+            Some(seconds) => format!("Anchor({:?}, {})", self.kind, seconds),
+            None => format!("Anchor({:?})", self.kind),
+        }
+    }
 }
 
 pub trait ExtractableEffect {
@@ -27,25 +242,73 @@ impl ExtractableEffect for Bound<'_, PyAny> {
         self.extract::<FadeIn>().map(ActionType::FadeIn)
             .or_else(|_| self.extract::<FadeOut>().map(ActionType::FadeOut))
             .or_else(|_| self.extract::<ChangeSpeed>().map(ActionType::ChangeSpeed))
+            .or_else(|_| self.extract::<Envelope>().map(ActionType::Envelope))
+            .or_else(|_| self.extract::<Automation>().map(ActionType::Automation))
+            .or_else(|_| self.extract::<Filter>().map(ActionType::Filter))
+            .or_else(|_| self.extract::<ParametricEQ>().map(ActionType::ParametricEQ))
+            .or_else(|_| self.extract::<Reverb>().map(ActionType::Reverb))
+            .or_else(|_| self.extract::<Delay>().map(ActionType::Delay))
+            .or_else(|_| self.extract::<Compressor>().map(ActionType::Compressor))
+            .or_else(|_| self.extract::<Limiter>().map(ActionType::Limiter))
+            .or_else(|_| self.extract::<NoiseGate>().map(ActionType::NoiseGate))
+            .or_else(|_| self.extract::<Distortion>().map(ActionType::Distortion))
+            .or_else(|_| self.extract::<Bitcrusher>().map(ActionType::Bitcrusher))
+            .or_else(|_| self.extract::<Chorus>().map(ActionType::Chorus))
+            .or_else(|_| self.extract::<Flanger>().map(ActionType::Flanger))
+            .or_else(|_| self.extract::<Phaser>().map(ActionType::Phaser))
+            .or_else(|_| self.extract::<Tremolo>().map(ActionType::Tremolo))
+            .or_else(|_| self.extract::<Vibrato>().map(ActionType::Vibrato))
+            .or_else(|_| self.extract::<PythonEffect>().map(ActionType::PythonEffect))
     }
 }
 
 #[pymethods]
 impl FadeIn {
     #[new]
-    #[pyo3(signature = (duration=None, start_val=None, end_val=None, apply_after=None))]
+    #[pyo3(signature = (duration=None, start_val=None, end_val=None, apply_after=None, anchor=None, curve=Easing::default()))]
     pub fn new(
         duration: Option<f32>,
         start_val: Option<f32>,
         end_val: Option<f32>,
         apply_after: Option<f32>,
+        anchor: Option<Anchor>,
+        curve: Easing,
     ) -> PyResult<Self> {
-        Ok(FadeIn {
+        let effect = FadeIn {
             duration,
             start_val,
             end_val,
             apply_after,
-        })
+            anchor,
+            curve,
+        };
+        effect.validate()?;
+        Ok(effect)
+    }
+
+    #[setter]
+    fn set_duration(&mut self, duration: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::FADE_IN, "duration", &mut self.duration, duration)
+    }
+
+    #[setter]
+    fn set_start_val(&mut self, start_val: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::FADE_IN, "start_val", &mut self.start_val, start_val)
+    }
+
+    #[setter]
+    fn set_end_val(&mut self, end_val: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::FADE_IN, "end_val", &mut self.end_val, end_val)
+    }
+
+    #[setter]
+    fn set_apply_after(&mut self, apply_after: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::FADE_IN, "apply_after", &mut self.apply_after, apply_after)
+    }
+
+    #[staticmethod]
+    fn schema(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+        schema::to_dict(py, &schema::FADE_IN)
     }
 
     fn as_dict<'py>(&self, py: Python<'py>) -> Bound<'py, PyDict> {
@@ -53,74 +316,342 @@ impl FadeIn {
     }
 }
 
-#[derive(Clone, Debug, Copy, PartialEq, Serialize)]
+#[derive(Clone, Debug, Copy, PartialEq, Serialize, Deserialize)]
 #[pyclass]
 pub struct FadeOut {
-    #[pyo3(get, set)]
+    #[pyo3(get)]
     pub duration: Option<f32>,
-    #[pyo3(get, set)]
+    #[pyo3(get)]
     pub start_val: Option<f32>,
-    #[pyo3(get, set)]
+    #[pyo3(get)]
     pub end_val: Option<f32>,
-    #[pyo3(get, set)]
+    #[pyo3(get)]
     pub apply_after: Option<f32>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub anchor: Option<Anchor>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub curve: Easing,
 }
 
 #[pymethods]
 impl FadeOut {
     #[new]
-    #[pyo3(signature = (duration=None, start_val=None, end_val=None, apply_after=None))]
+    #[pyo3(signature = (duration=None, start_val=None, end_val=None, apply_after=None, anchor=None, curve=Easing::default()))]
     pub fn new(
         duration: Option<f32>,
         start_val: Option<f32>,
         end_val: Option<f32>,
         apply_after: Option<f32>,
+        anchor: Option<Anchor>,
+        curve: Easing,
     ) -> PyResult<Self> {
-        Ok(FadeOut {
+        let effect = FadeOut {
             duration,
             start_val,
             end_val,
             apply_after,
-        })
+            anchor,
+            curve,
+        };
+        effect.validate()?;
+        Ok(effect)
+    }
+
+    #[setter]
+    fn set_duration(&mut self, duration: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::FADE_OUT, "duration", &mut self.duration, duration)
+    }
+
+    #[setter]
+    fn set_start_val(&mut self, start_val: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::FADE_OUT, "start_val", &mut self.start_val, start_val)
+    }
+
+    #[setter]
+    fn set_end_val(&mut self, end_val: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::FADE_OUT, "end_val", &mut self.end_val, end_val)
+    }
+
+    #[setter]
+    fn set_apply_after(&mut self, apply_after: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::FADE_OUT, "apply_after", &mut self.apply_after, apply_after)
+    }
+
+    #[staticmethod]
+    fn schema(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+        schema::to_dict(py, &schema::FADE_OUT)
     }
 }
 
-#[derive(Clone, Debug, Copy, PartialEq, Serialize)]
+#[derive(Clone, Debug, Copy, PartialEq, Serialize, Deserialize)]
 #[pyclass]
 pub struct ChangeSpeed {
-    #[pyo3(get, set)]
+    #[pyo3(get)]
     pub duration: Option<f32>,
-    #[pyo3(get, set)]
+    #[pyo3(get)]
     pub start_val: Option<f32>,
-    #[pyo3(get, set)]
+    #[pyo3(get)]
     pub end_val: Option<f32>,
-    #[pyo3(get, set)]
+    #[pyo3(get)]
     pub apply_after: Option<f32>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub anchor: Option<Anchor>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub curve: Easing,
 }
 
 #[pymethods]
 impl ChangeSpeed {
     #[new]
-    #[pyo3(signature = (duration=None, start_val=None, end_val=None, apply_after=None))]
+    #[pyo3(signature = (duration=None, start_val=None, end_val=None, apply_after=None, anchor=None, curve=Easing::default()))]
     pub fn new(
         duration: Option<f32>,
         start_val: Option<f32>,
         end_val: Option<f32>,
         apply_after: Option<f32>,
+        anchor: Option<Anchor>,
+        curve: Easing,
     ) -> PyResult<Self> {
-        Ok(ChangeSpeed {
+        let effect = ChangeSpeed {
             duration,
             start_val,
             end_val,
             apply_after,
-        })
+            anchor,
+            curve,
+        };
+        effect.validate()?;
+        Ok(effect)
+    }
+
+    #[setter]
+    fn set_duration(&mut self, duration: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::CHANGE_SPEED, "duration", &mut self.duration, duration)
+    }
+
+    #[setter]
+    fn set_start_val(&mut self, start_val: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::CHANGE_SPEED, "start_val", &mut self.start_val, start_val)
+    }
+
+    #[setter]
+    fn set_end_val(&mut self, end_val: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::CHANGE_SPEED, "end_val", &mut self.end_val, end_val)
+    }
+
+    #[setter]
+    fn set_apply_after(&mut self, apply_after: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::CHANGE_SPEED, "apply_after", &mut self.apply_after, apply_after)
+    }
+
+    #[staticmethod]
+    fn schema(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+        schema::to_dict(py, &schema::CHANGE_SPEED)
+    }
+}
+
+#[pyclass(eq, eq_int)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum AutomationTarget {
+    #[default]
+    Volume,
+    Speed,
+    Pan,
+    FilterCutoff,
+}
+
+impl AutomationTarget {
+    pub fn validate(&self, value: f32) -> PyResult<()> {
+        let valid = match self {
+            AutomationTarget::Volume => (0.0..=1.0).contains(&value),
+            AutomationTarget::Speed | AutomationTarget::FilterCutoff => value > 0.0,
+            AutomationTarget::Pan => (-1.0..=1.0).contains(&value),
+        };
+        if valid {
+            return Ok(());
+        }
+        Err(PyValueError::new_err(match self {
+            AutomationTarget::Volume => "Volume values must be between 0.0 and 1.0.",
+            AutomationTarget::Speed => "Speed values must be greater than 0.",
+            AutomationTarget::Pan => "Pan values must be between -1.0 and 1.0.",
+            AutomationTarget::FilterCutoff => "Filter cutoff values must be greater than 0.",
+        }))
     }
 }
 
+/// How manual `set_volume`/`set_speed` calls and overlapping effects on the same parameter
+/// combine.
+#[pyclass(eq, eq_int)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum ConflictPolicy {
+    /// Manual changes raise `EffectConflictException` while an effect runs; of overlapping
+    /// effects, the newest one that has started is heard.
+    #[default]
+    Reject,
+    /// Manual changes and newer effects cancel the effects already running.
+    Override,
+    /// Effect values scale the manual value and each other.
+    Multiply,
+    /// Manual changes and newer effects wait for the running effects to finish.
+    Queue,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Breakpoint {
+    pub time: f32,
+    pub value: f32,
+    #[serde(default)]
+    pub curve: Easing,
+}
+
+#[derive(FromPyObject)]
+pub enum BreakpointArg {
+    Curved((f32, f32, Easing)),
+    Plain((f32, f32)),
+}
+
+/// Automation through a list of breakpoints. Each breakpoint's curve shapes the segment that
+/// leads from it to the next one.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[pyclass]
+pub struct Envelope {
+    pub points: Vec<Breakpoint>,
+    #[pyo3(get)]
+    pub target: AutomationTarget,
+    #[pyo3(get)]
+    pub apply_after: Option<f32>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub anchor: Option<Anchor>,
+}
+
+#[pymethods]
+impl Envelope {
+    #[new]
+    #[pyo3(signature = (points, target=AutomationTarget::Volume, apply_after=None, anchor=None))]
+    pub fn new(
+        points: Vec<BreakpointArg>,
+        target: AutomationTarget,
+        apply_after: Option<f32>,
+        anchor: Option<Anchor>,
+    ) -> PyResult<Self> {
+        let points: Vec<Breakpoint> = points
+            .into_iter()
+            .map(|point| match point {
+                BreakpointArg::Curved((time, value, curve)) => Breakpoint { time, value, curve },
+                BreakpointArg::Plain((time, value)) => Breakpoint {
+                    time,
+                    value,
+                    curve: Easing::default(),
+                },
+            })
+            .collect();
+
+        let envelope = Envelope {
+            points,
+            target,
+            apply_after,
+            anchor,
+        };
+        envelope.validate()?;
+        Ok(envelope)
+    }
+
+    #[getter]
+    fn points(&self) -> Vec<(f32, f32, Easing)> {
+        self.points
+            .iter()
+            .map(|point| (point.time, point.value, point.curve))
+            .collect()
+    }
+
+    #[getter]
+    fn duration(&self) -> f32 {
+        self.points[self.points.len() - 1].time
+    }
+
+    #[setter]
+    fn set_apply_after(&mut self, apply_after: Option<f32>) -> PyResult<()> {
+        schema::update(&schema::ENVELOPE, "apply_after", &mut self.apply_after, apply_after)
+    }
+
+    #[staticmethod]
+    fn schema(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+        schema::to_dict(py, &schema::ENVELOPE)
+    }
+
+    fn as_dict<'py>(&self, py: Python<'py>) -> Bound<'py, PyDict> {
+        self.clone().into_py_dict_bound(py)
+    }
+}
+
+impl Envelope {
+    /// Checks the breakpoints against the target's range and `apply_after` against
+    /// `schema::ENVELOPE`.
+    pub fn validate(&self) -> PyResult<()> {
+        if self.points.is_empty() {
+            return Err(PyValueError::new_err("An envelope needs at least one breakpoint."));
+        }
+        for point in &self.points {
+            if !point.time.is_finite() || point.time < 0.0 {
+                return Err(PyValueError::new_err(
+                    "Breakpoint times must be non-negative numbers.",
+                ));
+            }
+            self.target.validate(point.value)?;
+        }
+        if self.points.windows(2).any(|pair| pair[1].time <= pair[0].time) {
+            return Err(PyValueError::new_err(
+                "Breakpoint times must be strictly increasing.",
+            ));
+        }
+        schema::find(&schema::ENVELOPE, "apply_after").check(self.apply_after)
+    }
+
+    /// Value `elapsed` seconds after the envelope started.
+    pub fn value_at(&self, elapsed: f32) -> f32 {
+        let next = self.points.partition_point(|point| point.time <= elapsed);
+        if next == 0 {
+            return self.points[0].value;
+        }
+        if next == self.points.len() {
+            return self.points[next - 1].value;
+        }
+
+        let from = self.points[next - 1];
+        let to = self.points[next];
+        let progress = (elapsed - from.time) / (to.time - from.time);
+        let progress = from.curve.apply(progress, to.value < from.value);
+        from.value + (to.value - from.value) * progress
+    }
+}
+
+impl fmt::Display for Envelope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Envelope {{ target: {:?}, points: {:?}, apply_after: {:?} }}",
+            self.target, self.points, self.apply_after)
+    }
+}
+
+impl IntoPyDict for Envelope {
+    fn into_py_dict_bound(self, py: Python<'_>) -> Bound<'_, PyDict> {
+        let value = serde_json::to_value(self).unwrap();
+        let dict: Bound<'_, PyDict> = json_to_py(py, &value).extract().unwrap();
+        dict.set_item("type", "Envelope").unwrap();
+        dict
+    }
+}
+
+schema::validate!(FadeIn, schema::FADE_IN, [duration, start_val, end_val, apply_after]);
+
 impl fmt::Display for FadeIn {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "FadeIn {{ duration: {:?}, start_val: {:?}, end_val: {:?} apply_after: {:?} }}", 
-            self.duration, self.start_val, self.end_val, self.apply_after)
+        write!(f, "FadeIn {{ duration: {:?}, start_val: {:?}, end_val: {:?} apply_after: {:?} curve: {:?} }}", 
+            self.duration, self.start_val, self.end_val, self.apply_after, self.curve)
     }
 }
 
@@ -133,10 +664,12 @@ impl IntoPyDict for FadeIn {
     }
 }
 
+schema::validate!(FadeOut, schema::FADE_OUT, [duration, start_val, end_val, apply_after]);
+
 impl fmt::Display for FadeOut {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "FadeOut {{ duration: {:?}, start_val: {:?}, end_val: {:?} apply_after: {:?} }}", 
-            self.duration, self.start_val, self.end_val, self.apply_after)
+        write!(f, "FadeOut {{ duration: {:?}, start_val: {:?}, end_val: {:?} apply_after: {:?} curve: {:?} }}", 
+            self.duration, self.start_val, self.end_val, self.apply_after, self.curve)
     }
 }
 
@@ -149,10 +682,12 @@ impl IntoPyDict for FadeOut {
     }
 }
 
+schema::validate!(ChangeSpeed, schema::CHANGE_SPEED, [duration, start_val, end_val, apply_after]);
+
 impl fmt::Display for ChangeSpeed {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "ChangeSpeed {{ duration: {:?}, start_val: {:?}, end_val: {:?} apply_after: {:?} }}", 
-            self.duration, self.start_val, self.end_val, self.apply_after)
+        write!(f, "ChangeSpeed {{ duration: {:?}, start_val: {:?}, end_val: {:?} apply_after: {:?} curve: {:?} }}", 
+            self.duration, self.start_val, self.end_val, self.apply_after, self.curve)
     }
 }
 
@@ -165,16 +700,186 @@ impl IntoPyDict for ChangeSpeed {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
 #[allow(non_upper_case_globals)]
 #[pyclass]
 pub enum ActionType {
     FadeIn(FadeIn),
     FadeOut(FadeOut),
     ChangeSpeed(ChangeSpeed),
+    Envelope(Envelope),
+    Automation(Automation),
+    Filter(Filter),
+    ParametricEQ(ParametricEQ),
+    Reverb(Reverb),
+    Delay(Delay),
+    Compressor(Compressor),
+    Limiter(Limiter),
+    NoiseGate(NoiseGate),
+    Distortion(Distortion),
+    Bitcrusher(Bitcrusher),
+    Chorus(Chorus),
+    Flanger(Flanger),
+    Phaser(Phaser),
+    Tremolo(Tremolo),
+    Vibrato(Vibrato),
+    PythonEffect(PythonEffect),
+}
+
+impl ActionType {
+    /// The parameter an effect automates, for effects that drive one through the sink's
+    /// controls.
+    pub fn target(&self) -> Option<AutomationTarget> {
+        match self {
+            ActionType::FadeIn(_) | ActionType::FadeOut(_) => Some(AutomationTarget::Volume),
+            ActionType::ChangeSpeed(_) => Some(AutomationTarget::Speed),
+            ActionType::Envelope(envelope) => Some(envelope.target),
+            ActionType::Automation(automation) => Some(automation.target),
+            _ => None,
+        }
+    }
+
+    /// Rejects effects that hold a Python callable, which cannot be written out or read back.
+    pub fn check_saveable(&self, container: &str) -> PyResult<()> {
+        if matches!(self, ActionType::PythonEffect(_) | ActionType::Automation(_)) {
+            return Err(PyValueError::new_err(format!(
+                "{} runs Python code and cannot be saved in {}.",
+                self, container
+            )));
+        }
+        Ok(())
+    }
+
+    /// Checks the effect's parameters as its constructor would, for effects that did not come
+    /// through one, such as those read from a preset or a snapshot.
+    pub fn validate(&self) -> PyResult<()> {
+        match self {
+            ActionType::FadeIn(fade_in) => fade_in.validate(),
+            ActionType::FadeOut(fade_out) => fade_out.validate(),
+            ActionType::ChangeSpeed(change_speed) => change_speed.validate(),
+            ActionType::Envelope(envelope) => envelope.validate(),
+            ActionType::Automation(automation) => automation.validate(),
+            ActionType::Filter(filter) => filter.validate(),
+            ActionType::ParametricEQ(equalizer) => equalizer.validate(),
+            ActionType::Reverb(reverb) => reverb.validate(),
+            ActionType::Delay(delay) => delay.validate(),
+            ActionType::Compressor(compressor) => compressor.validate(),
+            ActionType::Limiter(limiter) => limiter.validate(),
+            ActionType::NoiseGate(gate) => gate.validate(),
+            ActionType::Distortion(distortion) => distortion.validate(),
+            ActionType::Bitcrusher(bitcrusher) => bitcrusher.validate(),
+            ActionType::Chorus(chorus) => chorus.validate(),
+            ActionType::Flanger(flanger) => flanger.validate(),
+            ActionType::Phaser(phaser) => phaser.validate(),
+            ActionType::Tremolo(tremolo) => tremolo.validate(),
+            ActionType::Vibrato(vibrato) => vibrato.validate(),
+            ActionType::PythonEffect(effect) => effect.validate(),
+        }
+    }
+
+    pub fn apply_after(&self) -> Option<f32> {
+        match self {
+            ActionType::FadeIn(fade_in) => fade_in.apply_after,
+            ActionType::FadeOut(fade_out) => fade_out.apply_after,
+            ActionType::ChangeSpeed(change_speed) => change_speed.apply_after,
+            ActionType::Envelope(envelope) => envelope.apply_after,
+            ActionType::Automation(automation) => automation.apply_after,
+            ActionType::Filter(filter) => filter.apply_after,
+            ActionType::ParametricEQ(equalizer) => equalizer.apply_after,
+            ActionType::Reverb(reverb) => reverb.apply_after,
+            ActionType::Delay(delay) => delay.apply_after,
+            ActionType::Compressor(compressor) => compressor.apply_after,
+            ActionType::Limiter(limiter) => limiter.apply_after,
+            ActionType::NoiseGate(gate) => gate.apply_after,
+            ActionType::Distortion(distortion) => distortion.apply_after,
+            ActionType::Bitcrusher(bitcrusher) => bitcrusher.apply_after,
+            ActionType::Chorus(chorus) => chorus.apply_after,
+            ActionType::Flanger(flanger) => flanger.apply_after,
+            ActionType::Phaser(phaser) => phaser.apply_after,
+            ActionType::Tremolo(tremolo) => tremolo.apply_after,
+            ActionType::Vibrato(vibrato) => vibrato.apply_after,
+            ActionType::PythonEffect(effect) => effect.apply_after,
+        }
+    }
+
+    pub fn anchor(&self) -> Option<Anchor> {
+        match self {
+            ActionType::FadeIn(fade_in) => fade_in.anchor,
+            ActionType::FadeOut(fade_out) => fade_out.anchor,
+            ActionType::ChangeSpeed(change_speed) => change_speed.anchor,
+            ActionType::Envelope(envelope) => envelope.anchor,
+            ActionType::Automation(automation) => automation.anchor,
+            ActionType::Filter(filter) => filter.anchor,
+            ActionType::ParametricEQ(equalizer) => equalizer.anchor,
+            ActionType::Reverb(reverb) => reverb.anchor,
+            ActionType::Delay(delay) => delay.anchor,
+            ActionType::Compressor(compressor) => compressor.anchor,
+            ActionType::Limiter(limiter) => limiter.anchor,
+            ActionType::NoiseGate(gate) => gate.anchor,
+            ActionType::Distortion(distortion) => distortion.anchor,
+            ActionType::Bitcrusher(bitcrusher) => bitcrusher.anchor,
+            ActionType::Chorus(chorus) => chorus.anchor,
+            ActionType::Flanger(flanger) => flanger.anchor,
+            ActionType::Phaser(phaser) => phaser.anchor,
+            ActionType::Tremolo(tremolo) => tremolo.anchor,
+            ActionType::Vibrato(vibrato) => vibrato.anchor,
+            ActionType::PythonEffect(effect) => effect.anchor,
+        }
+    }
+
+    pub fn to_object(&self, py: Python) -> PyObject {
+        match self {
+            ActionType::FadeIn(fade_in) => Py::new(py, *fade_in).unwrap().into_py(py),
+            ActionType::FadeOut(fade_out) => Py::new(py, *fade_out).unwrap().into_py(py),
+            ActionType::ChangeSpeed(change_speed) => Py::new(py, *change_speed).unwrap().into_py(py),
+            ActionType::Envelope(envelope) => Py::new(py, envelope.clone()).unwrap().into_py(py),
+            ActionType::Automation(automation) => Py::new(py, automation.clone()).unwrap().into_py(py),
+            ActionType::Filter(filter) => Py::new(py, *filter).unwrap().into_py(py),
+            ActionType::ParametricEQ(equalizer) => Py::new(py, equalizer.clone()).unwrap().into_py(py),
+            ActionType::Reverb(reverb) => Py::new(py, reverb.clone()).unwrap().into_py(py),
+            ActionType::Delay(delay) => Py::new(py, delay.clone()).unwrap().into_py(py),
+            ActionType::Compressor(compressor) => Py::new(py, compressor.clone()).unwrap().into_py(py),
+            ActionType::Limiter(limiter) => Py::new(py, limiter.clone()).unwrap().into_py(py),
+            ActionType::NoiseGate(gate) => Py::new(py, gate.clone()).unwrap().into_py(py),
+            ActionType::Distortion(distortion) => Py::new(py, distortion.clone()).unwrap().into_py(py),
+            ActionType::Bitcrusher(bitcrusher) => Py::new(py, bitcrusher.clone()).unwrap().into_py(py),
+            ActionType::Chorus(chorus) => Py::new(py, chorus.clone()).unwrap().into_py(py),
+            ActionType::Flanger(flanger) => Py::new(py, flanger.clone()).unwrap().into_py(py),
+            ActionType::Phaser(phaser) => Py::new(py, phaser.clone()).unwrap().into_py(py),
+            ActionType::Tremolo(tremolo) => Py::new(py, tremolo.clone()).unwrap().into_py(py),
+            ActionType::Vibrato(vibrato) => Py::new(py, vibrato.clone()).unwrap().into_py(py),
+            ActionType::PythonEffect(effect) => Py::new(py, effect.clone()).unwrap().into_py(py),
+        }
+    }
+
+    pub fn to_dict<'py>(&self, py: Python<'py>) -> Bound<'py, PyDict> {
+        match self {
+            ActionType::FadeIn(fade_in) => fade_in.into_py_dict_bound(py),
+            ActionType::FadeOut(fade_out) => fade_out.into_py_dict_bound(py),
+            ActionType::ChangeSpeed(change_speed) => change_speed.into_py_dict_bound(py),
+            ActionType::Envelope(envelope) => envelope.clone().into_py_dict_bound(py),
+            ActionType::Automation(automation) => automation.clone().into_py_dict_bound(py),
+            ActionType::Filter(filter) => filter.into_py_dict_bound(py),
+            ActionType::ParametricEQ(equalizer) => equalizer.clone().into_py_dict_bound(py),
+            ActionType::Reverb(reverb) => reverb.clone().into_py_dict_bound(py),
+            ActionType::Delay(delay) => delay.clone().into_py_dict_bound(py),
+            ActionType::Compressor(compressor) => compressor.clone().into_py_dict_bound(py),
+            ActionType::Limiter(limiter) => limiter.clone().into_py_dict_bound(py),
+            ActionType::NoiseGate(gate) => gate.clone().into_py_dict_bound(py),
+            ActionType::Distortion(distortion) => distortion.clone().into_py_dict_bound(py),
+            ActionType::Bitcrusher(bitcrusher) => bitcrusher.clone().into_py_dict_bound(py),
+            ActionType::Chorus(chorus) => chorus.clone().into_py_dict_bound(py),
+            ActionType::Flanger(flanger) => flanger.clone().into_py_dict_bound(py),
+            ActionType::Phaser(phaser) => phaser.clone().into_py_dict_bound(py),
+            ActionType::Tremolo(tremolo) => tremolo.clone().into_py_dict_bound(py),
+            ActionType::Vibrato(vibrato) => vibrato.clone().into_py_dict_bound(py),
+            ActionType::PythonEffect(effect) => effect.clone().into_py_dict_bound(py),
+        }
+    }
 }
 
-#[derive(PartialEq, Clone, Debug)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct EffectSync {
     pub start_position: f32,
     duration: f32,
@@ -183,6 +888,11 @@ pub struct EffectSync {
     completion_pos: f32,
     current_position: f32,
     apply_after: Option<f32>,
+    #[serde(default)]
+    curve: Easing,
+    /// Set while the effect waits for the event its anchor names.
+    #[serde(default)]
+    waiting: Option<AnchorKind>,
     pub action: ActionType,
 }
 
@@ -214,13 +924,47 @@ impl fmt::Display for ActionType {
             ActionType::FadeOut(_) => write!(f, "FadeOut"),
 
             ActionType::ChangeSpeed(_) => write!(f, "ChangeSpeed"),
+
+            ActionType::Envelope(_) => write!(f, "Envelope"),
+
+            ActionType::Automation(_) => write!(f, "Automation"),
+
+            ActionType::Filter(_) => write!(f, "Filter"),
+
+            ActionType::ParametricEQ(_) => write!(f, "ParametricEQ"),
+
+            ActionType::Reverb(_) => write!(f, "Reverb"),
+
+            ActionType::Delay(_) => write!(f, "Delay"),
+
+            ActionType::Compressor(_) => write!(f, "Compressor"),
+
+            ActionType::Limiter(_) => write!(f, "Limiter"),
+
+            ActionType::NoiseGate(_) => write!(f, "NoiseGate"),
+
+            ActionType::Distortion(_) => write!(f, "Distortion"),
+
+            ActionType::Bitcrusher(_) => write!(f, "Bitcrusher"),
+
+            ActionType::Chorus(_) => write!(f, "Chorus"),
+
+            ActionType::Flanger(_) => write!(f, "Flanger"),
+
+            ActionType::Phaser(_) => write!(f, "Phaser"),
+
+            ActionType::Tremolo(_) => write!(f, "Tremolo"),
+
+            ActionType::Vibrato(_) => write!(f, "Vibrato"),
+
+            ActionType::PythonEffect(_) => write!(f, "PythonEffect"),
         }
     }
 }
 
 impl EffectSync {
     pub fn new(action: ActionType, current_position: f32, sink_duration: Option<f32>) -> Self {
-        let (start_position, duration, start_val, end_val, apply_after) = match action {
+        let (start_position, duration, start_val, end_val, apply_after, curve) = match &action {
             ActionType::FadeIn(fade_in) => {
                 let duration = fade_in.duration.unwrap_or(2.0);
                 let start_val = fade_in.start_val.unwrap_or(0.0);
@@ -233,6 +977,7 @@ impl EffectSync {
                     start_val,
                     end_val,
                     fade_in.apply_after,
+                    fade_in.curve,
                 )
             }
             ActionType::FadeOut(fade_out) => {
@@ -251,6 +996,7 @@ impl EffectSync {
                     start_val,
                     end_val,
                     fade_out.apply_after,
+                    fade_out.curve,
                 )
             }
             ActionType::ChangeSpeed(change_speed) => {
@@ -265,10 +1011,85 @@ impl EffectSync {
                     start_val,
                     end_val,
                     change_speed.apply_after,
+                    change_speed.curve,
+                )
+            }
+            ActionType::Envelope(envelope) => {
+                let first = envelope.points[0];
+                let last = envelope.points[envelope.points.len() - 1];
+                let start_position = current_position + envelope.apply_after.unwrap_or(0.0);
+
+                (
+                    start_position,
+                    last.time,
+                    first.value,
+                    last.value,
+                    envelope.apply_after,
+                    Easing::default(),
+                )
+            }
+            ActionType::Filter(filter) => {
+                let start_position = current_position + filter.apply_after.unwrap_or(0.0);
+
+                (
+                    start_position,
+                    filter.duration.unwrap_or(0.0),
+                    filter.start_cutoff.unwrap_or(filter.cutoff),
+                    filter.cutoff,
+                    filter.apply_after,
+                    filter.curve,
                 )
             }
+            ActionType::Distortion(distortion) => (
+                current_position + distortion.apply_after.unwrap_or(0.0),
+                distortion.duration.unwrap_or(0.0),
+                distortion.start_amount.unwrap_or(distortion.amount.get()),
+                distortion.amount.get(),
+                distortion.apply_after,
+                distortion.curve,
+            ),
+            ActionType::Bitcrusher(bitcrusher) => (
+                current_position + bitcrusher.apply_after.unwrap_or(0.0),
+                bitcrusher.duration.unwrap_or(0.0),
+                bitcrusher.start_amount.unwrap_or(bitcrusher.amount.get()),
+                bitcrusher.amount.get(),
+                bitcrusher.apply_after,
+                bitcrusher.curve,
+            ),
+            ActionType::Tremolo(Tremolo { duration, apply_after, .. })
+            | ActionType::Vibrato(Vibrato { duration, apply_after, .. })
+            | ActionType::Automation(Automation { duration, apply_after, .. }) => (
+                current_position + apply_after.unwrap_or(0.0),
+                duration.unwrap_or(0.0),
+                0.0,
+                0.0,
+                *apply_after,
+                Easing::default(),
+            ),
+            // Processor effects switch on at their start position and stay on.
+            _ => (
+                current_position + action.apply_after().unwrap_or(0.0),
+                0.0,
+                0.0,
+                0.0,
+                action.apply_after(),
+                Easing::default(),
+            ),
         };
 
+        let offset = apply_after.unwrap_or(0.0);
+        let (start_position, waiting) = match action.anchor() {
+            None => (start_position, None),
+            Some(anchor) => match anchor.kind {
+                AnchorKind::Now => (current_position + offset, None),
+                AnchorKind::Absolute => (anchor.seconds.unwrap_or(0.0) + offset, None),
+                AnchorKind::BeforeEnd => {
+                    let end = sink_duration.unwrap_or(current_position);
+                    (end - anchor.seconds.unwrap_or(duration) + offset, None)
+                }
+                AnchorKind::NextPlay | AnchorKind::Seek => (current_position, Some(anchor.kind)),
+            },
+        };
         let completion_pos = start_position + duration;
 
         Self {
@@ -279,25 +1100,271 @@ impl EffectSync {
             completion_pos,
             current_position,
             apply_after,
+            curve,
+            waiting,
             action,
         }
     }
 
+    pub fn started(&self, position: f32) -> bool {
+        self.waiting.is_none() && position > self.start_position
+    }
+
+    /// Starts an effect waiting for `event`, measuring `apply_after` from `position`.
+    pub fn trigger(&mut self, event: AnchorKind, position: f32) {
+        if self.waiting == Some(event) {
+            self.waiting = None;
+            self.defer(position + self.apply_after.unwrap_or(0.0));
+        }
+    }
+
+    /// Moves the effect's start and end by `offset` seconds.
+    pub fn shift(&mut self, offset: f32) {
+        self.start_position += offset;
+        self.completion_pos += offset;
+    }
+
+    /// Whether the effect has started and reached its completion position.
+    pub fn finished(&self, position: f32) -> bool {
+        self.started(position) && position >= self.completion_pos
+    }
+
+    /// Lengthens the effect by `seconds`, or shortens it if negative. A running ramp restarts
+    /// from its current value so it does not jump. Envelope breakpoints are not rescaled: the
+    /// envelope holds its last value for the added time, and shortening cuts off its tail.
+    /// Callers keep `seconds` at or above `-self.remaining(position)`.
+    pub fn extend(&mut self, position: f32, seconds: f32) {
+        let running = position > self.start_position && position < self.completion_pos;
+        if running && !matches!(self.action, ActionType::Envelope(_)) {
+            if let EffectResult::Value(value) = self.update(position) {
+                self.start_val = value;
+                self.start_position = position;
+                self.duration = self.completion_pos - position;
+            }
+        }
+        self.duration += seconds;
+        self.completion_pos += seconds;
+    }
+
+    /// Seconds left before the effect completes.
+    pub fn remaining(&self, position: f32) -> f32 {
+        self.completion_pos - position.max(self.start_position)
+    }
+
+    /// Places a restored effect so it continues from `progress` at `position`. Effects that
+    /// had not started keep their start.
+    pub fn resume(&mut self, position: f32, progress: f32) {
+        if self.waiting.is_none() && progress > 0.0 {
+            self.defer(position - progress.min(1.0) * self.duration);
+        }
+    }
+
+    /// Moves the effect's start to `position`, keeping its duration.
+    pub fn defer(&mut self, position: f32) {
+        self.start_position = position;
+        self.completion_pos = position + self.duration;
+    }
+
+    pub fn progress(&self, current_position: f32) -> f32 {
+        if !self.started(current_position) {
+            0.0
+        } else if current_position >= self.completion_pos {
+            1.0
+        } else {
+            (current_position - self.start_position) / (self.completion_pos - self.start_position)
+        }
+    }
+
     pub fn update(&self, current_position: f32) -> EffectResult {
-        if current_position <= self.start_position {
-            return EffectResult::Ignored;
+        if !self.started(current_position) {
+            EffectResult::Ignored
+        } else if current_position >= self.completion_pos {
+            EffectResult::Completed(self.end_val)
+        } else if let ActionType::Envelope(envelope) = &self.action {
+            EffectResult::Value(envelope.value_at(current_position - self.start_position))
         } else {
-            if current_position >= self.completion_pos {
-                let rounded_end_val = (self.end_val * 100.0).round() / 100.0;
-                return EffectResult::Completed(rounded_end_val);
-            } else {
-                let progress = (current_position - self.start_position)
-                    / (self.completion_pos - self.start_position);
-                let rounded_progresss = (progress * 100.0).round() / 100.0;
-                let progress = rounded_progresss.clamp(0.0, 1.0);
-                let set_val = self.start_val + (self.end_val - self.start_val) * progress;
-                return EffectResult::Value(set_val);
-            }
+            let progress = (current_position - self.start_position)
+                / (self.completion_pos - self.start_position);
+            let progress = self
+                .curve
+                .apply(progress.clamp(0.0, 1.0), self.end_val < self.start_val);
+            EffectResult::Value(self.start_val + (self.end_val - self.start_val) * progress)
+        }
+    }
+}
+
+/// Converts a `time.monotonic()` timestamp to an `Instant`.
+fn monotonic_instant(py: Python, timestamp: f64) -> PyResult<Instant> {
+    if !timestamp.is_finite() {
+        return Err(PyValueError::new_err("Timestamp must be a finite number."));
+    }
+
+    let now: f64 = py.import_bound("time")?.call_method0("monotonic")?.extract()?;
+    let instant = Instant::now();
+    let delay = Duration::from_secs_f64((timestamp - now).abs());
+    Ok(if timestamp >= now {
+        instant + delay
+    } else {
+        instant.checked_sub(delay).unwrap_or(instant)
+    })
+}
+
+#[pyclass(eq, eq_int)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EffectState {
+    Pending,
+    Active,
+    Paused,
+    Done,
+}
+
+/// A handle to one effect sent to a sink by `apply_effects`.
+#[derive(Clone)]
+#[pyclass]
+pub struct EffectHandle {
+    voice: VoiceId,
+    status: Arc<EffectStatus>,
+    action: ActionType,
+}
+
+impl EffectHandle {
+    pub fn new(voice: VoiceId, status: Arc<EffectStatus>, action: ActionType) -> Self {
+        EffectHandle {
+            voice,
+            status,
+            action,
+        }
+    }
+
+    fn control(&self, py: Python, control: EffectControl) -> PyResult<bool> {
+        let id = self.voice;
+        let effect = self.status.id();
+        engine::request(py, |reply| EngineCommand::ControlEffect {
+            id,
+            effect,
+            control,
+            reply,
+        })
+    }
+}
+
+#[pymethods]
+impl EffectHandle {
+    #[getter]
+    pub fn id(&self) -> EffectId {
+        self.status.id()
+    }
+
+    #[getter]
+    pub fn effect(&self, py: Python) -> PyObject {
+        self.action.to_object(py)
+    }
+
+    #[getter]
+    pub fn state(&self) -> EffectState {
+        self.status.state()
+    }
+
+    #[getter]
+    pub fn progress(&self) -> f32 {
+        self.status.progress()
+    }
+
+    #[getter]
+    pub fn value(&self) -> f32 {
+        self.status.value()
+    }
+
+    pub fn cancel(&self, py: Python) -> PyResult<bool> {
+        self.control(py, EffectControl::Cancel)
+    }
+
+    pub fn pause(&self, py: Python) -> PyResult<bool> {
+        self.control(py, EffectControl::Pause)
+    }
+
+    pub fn resume(&self, py: Python) -> PyResult<bool> {
+        self.control(py, EffectControl::Resume)
+    }
+
+    pub fn extend(&self, py: Python, seconds: f32) -> PyResult<bool> {
+        if !seconds.is_finite() {
+            return Err(PyValueError::new_err("Seconds must be a finite number."));
         }
+        self.control(py, EffectControl::Extend(seconds))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "EffectHandle(id={}, effect={}, state={:?}, progress={:.2})",
+            self.id(),
+            self.action,
+            self.state(),
+            self.progress()
+        )
+    }
+}
+
+/// A play or stop scheduled for a `time.monotonic()` timestamp.
+#[derive(Clone)]
+#[pyclass]
+pub struct ScheduledEvent {
+    id: EventId,
+    #[pyo3(get)]
+    timestamp: f64,
+    #[pyo3(get)]
+    action: String,
+    pending: Arc<AtomicBool>,
+}
+
+impl ScheduledEvent {
+    pub fn schedule(
+        py: Python,
+        ids: Vec<VoiceId>,
+        timestamp: f64,
+        action: ScheduledAction,
+    ) -> PyResult<Self> {
+        let at = monotonic_instant(py, timestamp)?;
+        let pending = Arc::new(AtomicBool::new(true));
+        let id = engine::request(py, |reply| EngineCommand::Schedule {
+            ids,
+            at,
+            action,
+            pending: pending.clone(),
+            reply,
+        })?;
+
+        Ok(ScheduledEvent {
+            id,
+            timestamp,
+            action: match action {
+                ScheduledAction::Play => "play",
+                ScheduledAction::Stop => "stop",
+            }
+            .to_string(),
+            pending,
+        })
+    }
+}
+
+#[pymethods]
+impl ScheduledEvent {
+    #[getter]
+    pub fn pending(&self) -> bool {
+        self.pending.load(Ordering::SeqCst)
+    }
+
+    pub fn cancel(&self, py: Python) -> PyResult<bool> {
+        let event = self.id;
+        engine::request(py, |reply| EngineCommand::CancelScheduled { event, reply })
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ScheduledEvent(action='{}', timestamp={}, pending={})",
+            self.action,
+            self.timestamp,
+            if self.pending() { "True" } else { "False" }
+        )
     }
 }
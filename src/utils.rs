@@ -1,5 +1,6 @@
-use pyo3::{Bound, IntoPy, PyAny, Python, ToPyObject};
-use pyo3::types::{PyAnyMethods, PyDict, PyList};
+use pyo3::exceptions::PyValueError;
+use pyo3::{Bound, IntoPy, PyAny, PyResult, Python, ToPyObject};
+use pyo3::types::{PyAnyMethods, PyBool, PyDict, PyDictMethods, PyFloat, PyInt, PyList, PyString, PyTuple, PyTypeMethods};
 
 pub fn json_to_py<'py>(py: Python<'py>, value: &serde_json::Value) -> Bound<'py, PyAny> {
     match value {
@@ -28,3 +29,36 @@ pub fn json_to_py<'py>(py: Python<'py>, value: &serde_json::Value) -> Bound<'py,
         serde_json::Value::Null => py.None()
     }.into_bound(py)
 }
+
+pub fn py_to_json(value: &Bound<'_, PyAny>) -> PyResult<serde_json::Value> {
+    if value.is_none() {
+        Ok(serde_json::Value::Null)
+    } else if let Ok(b) = value.downcast::<PyBool>() {
+        Ok(serde_json::Value::Bool(b.extract()?))
+    } else if value.is_instance_of::<PyInt>() {
+        Ok(serde_json::Value::from(value.extract::<i64>()?))
+    } else if value.is_instance_of::<PyFloat>() {
+        Ok(serde_json::Number::from_f64(value.extract::<f64>()?)
+            .map(serde_json::Value::Number)
+            .unwrap_or(serde_json::Value::Null))
+    } else if let Ok(s) = value.downcast::<PyString>() {
+        Ok(serde_json::Value::String(s.extract()?))
+    } else if let Ok(dict) = value.downcast::<PyDict>() {
+        let mut map = serde_json::Map::new();
+        for (k, v) in dict.iter() {
+            map.insert(k.extract::<String>()?, py_to_json(&v)?);
+        }
+        Ok(serde_json::Value::Object(map))
+    } else if value.is_instance_of::<PyList>() || value.is_instance_of::<PyTuple>() {
+        let items = value
+            .iter()?
+            .map(|item| py_to_json(&item?))
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(serde_json::Value::Array(items))
+    } else {
+        Err(PyValueError::new_err(format!(
+            "Cannot convert {} to JSON",
+            value.get_type().name()?
+        )))
+    }
+}
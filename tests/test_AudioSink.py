@@ -1,7 +1,10 @@
 import pytest
 import asyncio
+import json
+import threading
+import time
 from unittest.mock import MagicMock
-from rpaudio.effects import FadeIn, FadeOut, ChangeSpeed
+from rpaudio.effects import FadeIn, FadeOut, ChangeSpeed, Curve, CubicBezier, Envelope, AutomationTarget, Filter, FilterType, ParametricEQ, EQBand, Reverb, Delay, NoteValue, Compressor, Limiter, NoiseGate, Distortion, DistortionType, Bitcrusher, Chorus, Flanger, Phaser, Tremolo, Vibrato, Waveform, PythonEffect, Automation, ConflictPolicy, Anchor, AnchorKind, dumps_preset, loads_preset
 import rpaudio
 import rpaudio.exceptions
 
@@ -100,6 +103,255 @@ async def test_effects_applied_over_time_speed_exception(audio_handler):
 
     handler.stop()
 
+async def wait_for_pos(handler, position):
+    for _ in range(200):
+        if handler.get_pos() >= position or not handler.is_playing:
+            break
+        await asyncio.sleep(0.01)
+
+
+@pytest.mark.asyncio
+async def test_conflict_policy_override(audio_handler):
+    handler, _ = audio_handler
+    assert handler.conflict_policy == ConflictPolicy.Reject
+    handler.conflict_policy = ConflictPolicy.Override
+    handler.apply_effects([FadeIn(start_val=0.0, end_val=1.0, duration=8.0)])
+    handler.play()
+    await wait_for_pos(handler, 0.5)
+
+    handler.set_volume(0.3)
+    assert handler.get_volume() == pytest.approx(0.3)
+    assert handler.playback_data()["effects"] == []
+    await wait_for_pos(handler, 1.5)
+    assert handler.get_volume() == pytest.approx(0.3)
+    handler.stop()
+
+
+@pytest.mark.asyncio
+async def test_conflict_policy_multiply(audio_handler):
+    handler, _ = audio_handler
+    handler.conflict_policy = ConflictPolicy.Multiply
+    handler.set_volume(0.5)
+    handler.apply_effects([FadeIn(start_val=0.0, end_val=1.0, duration=2.0)])
+    handler.play()
+    await wait_for_pos(handler, 0.5)
+
+    handler.set_volume(0.4)
+    assert handler.get_volume() < 0.4
+    await wait_for_pos(handler, 3.0)
+    assert handler.get_volume() == pytest.approx(0.4)
+    handler.stop()
+
+
+@pytest.mark.asyncio
+async def test_conflict_policy_queue(audio_handler):
+    handler, _ = audio_handler
+    handler.conflict_policy = ConflictPolicy.Queue
+    handler.apply_effects([
+        FadeIn(start_val=0.0, end_val=1.0, duration=2.0),
+        FadeOut(start_val=1.0, end_val=0.2, duration=2.0),
+    ])
+    handler.play()
+    await wait_for_pos(handler, 0.5)
+
+    handler.set_volume(0.3)
+    assert handler.get_volume() != pytest.approx(0.3)
+    await wait_for_pos(handler, 2.5)
+    # The fade out waits for the fade in, and the manual volume for the fade out.
+    assert handler.get_volume() > 0.2
+    await wait_for_pos(handler, 5.0)
+    assert handler.get_volume() == pytest.approx(0.3)
+    handler.stop()
+
+
+@pytest.mark.asyncio
+async def test_effect_handles(audio_handler):
+    handler, _ = audio_handler
+    fade, speed = handler.apply_effects([
+        FadeIn(start_val=0.0, end_val=1.0, duration=8.0),
+        ChangeSpeed(start_val=1.0, end_val=1.2, duration=1.0, apply_after=6.0),
+    ])
+    assert fade.id != speed.id
+    assert isinstance(fade.effect, FadeIn)
+    assert speed.state == rpaudio.EffectState.Pending
+
+    handler.play()
+    await wait_for_pos(handler, 0.5)
+    assert fade.state == rpaudio.EffectState.Active
+    assert 0.0 < fade.progress < 1.0
+    assert speed.state == rpaudio.EffectState.Pending
+
+    assert fade.pause()
+    assert fade.state == rpaudio.EffectState.Paused
+    progress = fade.progress
+    await wait_for_pos(handler, 1.5)
+    assert fade.progress == pytest.approx(progress)
+    assert fade.resume()
+
+    assert fade.extend(2.0)
+    with pytest.raises(ValueError):
+        fade.extend(-100.0)
+
+    assert speed.cancel()
+    assert speed.state == rpaudio.EffectState.Done
+    assert not speed.cancel()
+    assert [effect["type"] for effect in handler.playback_data()["effects"]] == ["FadeIn"]
+    handler.stop()
+
+
+@pytest.mark.asyncio
+async def test_effect_extend(audio_handler):
+    handler, _ = audio_handler
+    fade, sweep = handler.apply_effects([
+        FadeIn(start_val=0.0, end_val=1.0, duration=1.0),
+        Filter(FilterType.LowPass, cutoff=500.0, start_cutoff=8000.0, duration=0.2),
+    ])
+    handler.play()
+    await wait_for_pos(handler, 0.5)
+    progress = fade.progress
+    volume = handler.get_volume()
+    assert 0.0 < volume < 1.0
+
+    assert fade.extend(1.0)
+    await asyncio.sleep(0.05)
+    assert fade.progress < progress
+    assert handler.get_volume() == pytest.approx(volume, abs=0.1)
+    await wait_for_pos(handler, 1.2)
+    assert fade.state == rpaudio.EffectState.Active
+    assert handler.get_volume() < 1.0
+
+    assert sweep.progress == 1.0
+    assert not sweep.extend(1.0)
+    assert sweep.progress == 1.0
+    assert sweep.value == pytest.approx(500.0)
+    with pytest.raises(ValueError):
+        fade.extend(float("nan"))
+    handler.stop()
+
+
+@pytest.mark.asyncio
+async def test_effect_anchors(audio_handler):
+    handler, _ = audio_handler
+    on_play, absolute, before_end, on_seek = handler.apply_effects([
+        FadeIn(duration=0.5, anchor=Anchor.on_next_play()),
+        Reverb(anchor=Anchor.absolute(3.0)),
+        ChangeSpeed(end_val=1.0, duration=1.0, anchor=Anchor.before_end()),
+        Distortion(apply_after=0.5, anchor=Anchor.on_seek()),
+    ])
+    assert before_end.effect.anchor == Anchor.before_end()
+    assert absolute.effect.as_dict()["anchor"] == {"kind": "Absolute", "seconds": 3.0}
+    await asyncio.sleep(0.1)
+    assert on_play.state == rpaudio.EffectState.Pending
+
+    handler.play()
+    await wait_for_pos(handler, 1.0)
+    assert on_play.state == rpaudio.EffectState.Done
+    assert absolute.state == rpaudio.EffectState.Pending
+    assert before_end.state == rpaudio.EffectState.Pending
+    assert on_seek.state == rpaudio.EffectState.Pending
+
+    handler.try_seek(7.0)
+    await wait_for_pos(handler, 7.8)
+    assert absolute.state == rpaudio.EffectState.Active
+    assert on_seek.state == rpaudio.EffectState.Active
+    assert before_end.state == rpaudio.EffectState.Pending
+    handler.stop()
+
+
+def test_anchor_invalid():
+    assert Anchor.absolute(2.0).kind == AnchorKind.Absolute
+    assert Anchor.before_end(1.5).seconds == 1.5
+    with pytest.raises(ValueError):
+        Anchor.absolute(-1.0)
+    with pytest.raises(ValueError):
+        Anchor.before_end(-1.0)
+
+
+def test_effect_parameters_invalid():
+    with pytest.raises(ValueError, match="duration"):
+        FadeIn(duration=-1.0)
+    with pytest.raises(ValueError, match="start_val"):
+        FadeOut(start_val=5.0)
+    with pytest.raises(ValueError, match="end_val"):
+        ChangeSpeed(end_val=0.0)
+    with pytest.raises(ValueError):
+        FadeIn(apply_after=float("nan"))
+
+    fade_in = FadeIn(duration=1.0)
+    fade_in.duration = None
+    change_speed = ChangeSpeed()
+    change_speed.start_val = 0.5
+    assert change_speed.start_val == 0.5
+    with pytest.raises(ValueError, match="start_val"):
+        loads_preset(json.dumps({"version": 1, "effects": [{"type": "FadeIn", "start_val": 3.0}]}))
+
+
+def test_effect_schema():
+    schema = FadeIn.schema()
+    assert list(schema) == ["duration", "start_val", "end_val", "apply_after", "anchor", "curve"]
+    assert schema["start_val"] == {
+        "type": "float",
+        "default": 0.0,
+        "min": 0.0,
+        "max": 1.0,
+        "exclusive_min": False,
+        "exclusive_max": False,
+        "description": "Volume at the start of the fade.",
+    }
+    assert FadeOut.schema()["duration"]["default"] == 2.0
+    speed = ChangeSpeed().schema()["end_val"]
+    assert speed["min"] == 0.0 and speed["exclusive_min"] and speed["max"] is None
+    assert FadeIn.schema()["curve"]["type"] == "Curve | CubicBezier"
+    assert Filter.schema()["curve"]["type"] == "Curve | CubicBezier"
+
+    feedback = Delay.schema()["feedback"]
+    assert feedback["max"] == 1.0 and feedback["exclusive_max"]
+    assert Phaser.schema()["stages"]["type"] == "int"
+    for effect in [Filter, EQBand, ParametricEQ, Reverb, Delay, Compressor, Limiter, NoiseGate,
+                   Distortion, Bitcrusher, Chorus, Flanger, Phaser, Tremolo, Vibrato, Envelope,
+                   Automation, PythonEffect]:
+        assert all(param["description"] for param in effect.schema().values())
+
+
+EFFECT_FACTORIES = [
+    FadeIn, FadeOut, ChangeSpeed, lambda: Envelope([(0.0, 1.0)]), Filter, ParametricEQ, Reverb,
+    Delay, Compressor, Limiter, NoiseGate, Distortion, Bitcrusher, Chorus, Flanger, Phaser,
+    Tremolo, Vibrato, lambda: PythonEffect(lambda block, sample_rate, channels: None),
+    lambda: Automation(lambda t: 0.5),
+]
+
+
+def out_of_range_values(factory):
+    """Yields a value past each declared bound of every settable numeric parameter."""
+    effect = factory()
+    for name, param in effect.schema().items():
+        if param["type"] not in ("float", "int"):
+            continue
+        try:
+            setattr(effect, name, getattr(effect, name))
+        except AttributeError:
+            continue
+        if param["min"] is not None:
+            yield name, param["min"] if param["exclusive_min"] else param["min"] - 1
+        if param["max"] is not None:
+            yield name, param["max"] if param["exclusive_max"] else param["max"] + 1
+        if param["type"] == "float":
+            yield name, float("nan")
+
+
+@pytest.mark.parametrize("factory, field, bad_value", [
+    (factory, field, bad_value)
+    for factory in EFFECT_FACTORIES
+    for field, bad_value in out_of_range_values(factory)
+])
+def test_effect_setter_invalid(factory, field, bad_value):
+    effect = factory()
+    before = getattr(effect, field)
+    with pytest.raises(ValueError, match=field):
+        setattr(effect, field, bad_value)
+    assert getattr(effect, field) == before
+
+
 @pytest.mark.asyncio
 async def test_effect_completion(audio_handler):
     handler, _ = audio_handler
@@ -118,6 +370,347 @@ async def test_effect_completion(audio_handler):
     handler.stop()
 
 
+@pytest.mark.asyncio
+async def test_effect_curves(audio_handler):
+    handler, _ = audio_handler
+    fade_in = FadeIn(duration=0.5, start_val=0.0, end_val=0.737, curve=Curve.SCurve)
+    assert fade_in.curve == Curve.SCurve
+    assert FadeIn().curve == Curve.Linear
+    assert fade_in.as_dict()["curve"] == "SCurve"
+
+    bezier = CubicBezier(0.42, 0.0, 0.58, 1.0)
+    speed = ChangeSpeed(duration=0.5, end_val=1.25, curve=bezier)
+    assert speed.curve.x1 == pytest.approx(0.42)
+    with pytest.raises(ValueError):
+        CubicBezier(1.5, 0.0, 0.5, 1.0)
+
+    handler.apply_effects([fade_in])
+    handler.play()
+    await asyncio.sleep(1.5)
+    assert handler.get_volume() == pytest.approx(0.737)
+    handler.stop()
+
+
+@pytest.mark.asyncio
+async def test_envelope(audio_handler):
+    handler, _ = audio_handler
+    envelope = Envelope([(0.0, 1.0), (0.3, 0.2, Curve.Exponential), (0.6, 0.5)])
+    assert envelope.target == AutomationTarget.Volume
+    assert envelope.duration == pytest.approx(0.6)
+    assert envelope.points[1][2] == Curve.Exponential
+    assert envelope.as_dict()["type"] == "Envelope"
+
+    handler.apply_effects([envelope, Envelope([(0.0, 0.0), (0.6, -1.0)], target=AutomationTarget.Pan)])
+    handler.play()
+    await asyncio.sleep(1.5)
+    assert handler.get_volume() == pytest.approx(0.5)
+    handler.stop()
+
+
+def test_envelope_invalid():
+    with pytest.raises(ValueError):
+        Envelope([])
+    with pytest.raises(ValueError):
+        Envelope([(0.5, 1.0), (0.2, 0.5)])
+    with pytest.raises(ValueError):
+        Envelope([(0.0, 2.0)], target=AutomationTarget.Pan)
+    with pytest.raises(ValueError):
+        Envelope([(0.0, -10.0)], target=AutomationTarget.FilterCutoff)
+
+    envelope = Envelope([(0.0, 1.0)])
+    envelope.apply_after = 2.0
+    assert envelope.apply_after == 2.0
+
+
+
+@pytest.mark.asyncio
+async def test_parametric_eq(audio_handler):
+    handler, _ = audio_handler
+    eq = ParametricEQ([
+        EQBand(FilterType.HighPass, frequency=40.0),
+        EQBand(FilterType.Peak, frequency=2500.0, gain=-4.0, q=1.4),
+    ])
+    assert len(eq) == 2
+    assert eq.as_dict()["bands"][1]["gain"] == -4.0
+
+    handler.apply_effects([eq])
+    handler.play()
+    await asyncio.sleep(0.3)
+    eq.set_band(1, gain=6.0)
+    applied = handler.playback_data()["effects"][0]
+    assert applied["type"] == "ParametricEQ"
+    assert applied["bands"][1]["gain"] == 6.0
+    await asyncio.sleep(0.2)
+    assert handler.is_playing
+    handler.stop()
+
+    with pytest.raises(IndexError):
+        eq.set_band(2, gain=1.0)
+    with pytest.raises(ValueError):
+        eq.set_band(0, frequency=-10.0)
+    with pytest.raises(ValueError):
+        EQBand(q=0.0)
+    with pytest.raises(ValueError):
+        ParametricEQ(apply_after=-1.0)
+
+
+@pytest.mark.asyncio
+async def test_reverb_tail():
+    finished = []
+    handler = rpaudio.AudioSink(callback=lambda: finished.append(True))
+    handler.load_audio(r"tests/test_audio_files/test_md_wav.wav")
+    handler.try_seek(handler.metadata.duration - 0.2)
+
+    reverb = Reverb(room_size=1.0, damping=0.2, mix=0.5, pre_delay=0.02)
+    assert reverb.as_dict()["type"] == "Reverb"
+    handler.apply_effects([reverb])
+    handler.play()
+
+    # The source ends long before the tail does.
+    await asyncio.sleep(0.2)
+    assert not finished
+    assert handler.is_playing
+    for _ in range(100):
+        if finished:
+            break
+        await asyncio.sleep(0.1)
+    assert finished
+
+
+def test_reverb_invalid():
+    with pytest.raises(ValueError):
+        Reverb(room_size=1.5)
+    with pytest.raises(ValueError):
+        Reverb(mix=-0.1)
+    with pytest.raises(ValueError):
+        Reverb(pre_delay=2.0)
+
+    reverb = Reverb()
+    reverb.mix = 0.75
+    assert reverb.mix == 0.75
+
+
+def test_delay_tempo_sync():
+    dotted = Delay(note=NoteValue.DottedEighth, bpm=120)
+    assert dotted.time == pytest.approx(0.375)
+    with pytest.raises(ValueError):
+        Delay(note=NoteValue.Quarter)
+
+    dotted.time = 0.5
+    assert dotted.time == 0.5
+    assert dotted.note is None and dotted.bpm is None
+
+
+@pytest.mark.asyncio
+async def test_dynamics_gain_reduction(audio_handler):
+    handler, _ = audio_handler
+    compressor = Compressor(threshold=-60.0, ratio=8.0, makeup=3.0)
+    limiter = Limiter(threshold=-40.0)
+    gate = NoiseGate(threshold=-0.5)
+    assert compressor.gain_reduction == 0.0
+    assert compressor.as_dict()["type"] == "Compressor"
+    assert "gain_reduction" not in limiter.as_dict()
+
+    handler.apply_effects([compressor, limiter, gate])
+    handler.play()
+    readings = []
+    for _ in range(50):
+        await asyncio.sleep(0.01)
+        readings.append((compressor.gain_reduction, limiter.gain_reduction, gate.gain_reduction))
+    handler.stop()
+
+    assert max(reading[0] for reading in readings) > 0.0
+    assert max(reading[1] for reading in readings) > 0.0
+    assert max(reading[2] for reading in readings) > 40.0
+
+
+def test_dynamics_invalid():
+    with pytest.raises(ValueError):
+        Compressor(ratio=0.5)
+    with pytest.raises(ValueError):
+        Compressor(threshold=3.0)
+    with pytest.raises(ValueError):
+        Limiter(release=0.0)
+    with pytest.raises(ValueError):
+        NoiseGate(range=-10.0)
+
+    with pytest.raises(ValueError, match="makeup"):
+        Compressor().makeup = float("inf")
+    gate = NoiseGate()
+    gate.threshold = -40.0
+    assert gate.threshold == -40.0
+
+
+@pytest.mark.asyncio
+async def test_python_effect():
+    blocks = []
+
+    def halve(block, sample_rate, channels):
+        blocks.append((len(block), sample_rate, channels))
+        for i in range(len(block)):
+            block[i] *= 0.5
+
+    finished = []
+    handler = rpaudio.AudioSink(callback=lambda: finished.append(True))
+    handler.load_audio(r"tests/test_audio_files/test_md_wav.wav")
+    effect = PythonEffect(halve, block_size=512)
+    assert effect.callback is halve
+    assert effect.as_dict() == {
+        "type": "PythonEffect",
+        "block_size": 512,
+        "apply_after": None,
+        "anchor": None,
+    }
+
+    handler.apply_effects([effect, PythonEffect(lambda block, sample_rate, channels: [0.0])])
+    handler.play()
+    for _ in range(200):
+        if finished:
+            break
+        await asyncio.sleep(0.05)
+    assert finished
+    length, sample_rate, channels = blocks[0]
+    assert length == 512 * channels
+    assert sample_rate > 0
+
+
+def test_python_effect_invalid():
+    with pytest.raises(TypeError):
+        PythonEffect(1.0)
+    with pytest.raises(ValueError):
+        PythonEffect(lambda block, sample_rate, channels: None, block_size=4)
+    with pytest.raises(ValueError):
+        PythonEffect(lambda block, sample_rate, channels: None, apply_after=-1.0)
+
+@pytest.mark.asyncio
+async def test_automation(audio_handler):
+    handler, _ = audio_handler
+    times = []
+
+    def level(t):
+        times.append(t)
+        return 0.25
+
+    automation = Automation(level, control_rate=200.0)
+    assert automation.function is level
+    assert automation.as_dict() == {
+        "type": "Automation",
+        "target": "Volume",
+        "control_rate": 200.0,
+        "duration": None,
+        "apply_after": None,
+        "anchor": None,
+    }
+
+    handler.apply_effects([automation])
+    handler.play()
+    for _ in range(400):
+        if times and handler.get_volume() == 0.25:
+            break
+        await asyncio.sleep(0.005)
+    assert handler.get_volume() == 0.25
+    assert times == sorted(times)
+    handler.stop()
+
+
+@pytest.mark.asyncio
+async def test_automation_pending_is_idle(audio_handler):
+    handler, _ = audio_handler
+    handler.apply_effects([Automation(lambda t: 0.25, apply_after=30.0)])
+    handler.play()
+    await asyncio.sleep(0.2)
+    start = time.process_time()
+    await asyncio.sleep(1.0)
+    assert time.process_time() - start < 0.5
+    handler.stop()
+
+
+def test_automation_invalid():
+    with pytest.raises(TypeError):
+        Automation(0.5)
+    with pytest.raises(ValueError):
+        Automation(lambda t: 0.5, duration=0.0)
+    with pytest.raises(ValueError):
+        Automation(lambda t: 0.5, control_rate=0.0)
+    with pytest.raises(ValueError):
+        Automation(lambda t: 0.5, control_rate=2000.0)
+
+    automation = Automation(lambda t: 0.5)
+    automation.duration = 2.0
+    assert automation.duration == 2.0
+
+def test_effects_preset_round_trip():
+    chain = [
+        FadeIn(duration=1.5, curve=Curve.SCurve),
+        Envelope([(0.0, 1.0), (2.0, 0.5, Curve.Exponential)], target=AutomationTarget.Volume),
+        ParametricEQ([EQBand(FilterType.HighShelf, frequency=8000.0, gain=-3.0)]),
+        Compressor(threshold=-24.0, ratio=3.0),
+        Tremolo(rate=4.0, waveform=Waveform.Triangle, anchor=Anchor.before_end(5.0)),
+    ]
+    preset = dumps_preset(chain, name="Broadcast")
+    data = json.loads(preset)
+    assert data["version"] == 1
+    assert data["name"] == "Broadcast"
+    assert [effect["type"] for effect in data["effects"]] == [
+        "FadeIn", "Envelope", "ParametricEQ", "Compressor", "Tremolo"
+    ]
+
+    loaded = loads_preset(preset)
+    assert [effect.as_dict() for effect in loaded] == [effect.as_dict() for effect in chain]
+    assert loaded[2].bands[0].gain == -3.0
+    assert loaded[4].anchor == Anchor.before_end(5.0)
+
+
+def test_effects_preset_invalid():
+    with pytest.raises(ValueError):
+        dumps_preset([PythonEffect(lambda block, sample_rate, channels: None)])
+    with pytest.raises(ValueError):
+        loads_preset("not json")
+    with pytest.raises(ValueError, match="version"):
+        loads_preset(json.dumps({"version": 99, "effects": []}))
+    with pytest.raises(ValueError):
+        loads_preset(json.dumps({"version": 1, "effects": [{"type": "Unknown"}]}))
+
+
+def test_effects_preset_bad_parameters():
+    band = {"band_type": "Peak", "frequency": 1000.0, "gain": 0.0, "q": 0.0}
+    cases = [
+        (FadeIn(), "start_val", 3.0),
+        (FadeOut(), "duration", -1.0),
+        (ChangeSpeed(), "end_val", 0.0),
+        (Envelope([(0.0, 1.0)]), "points", []),
+        (Filter(), "cutoff", 0.0),
+        (ParametricEQ([EQBand()]), "bands", [band]),
+        (Reverb(), "room_size", 1.5),
+        (Delay(), "time", 1e12),
+        (Delay(), "feedback", 1.0),
+        (Compressor(), "ratio", 0.5),
+        (Limiter(), "release", 0.0),
+        (NoiseGate(), "range", -10.0),
+        (Distortion(), "duration", -1.0),
+        (Bitcrusher(), "bits", 0.0),
+        (Chorus(), "rate", 0.0),
+        (Flanger(), "feedback", 1.0),
+        (Phaser(), "stages", 100),
+        (Tremolo(), "depth", 1.5),
+        (Vibrato(), "apply_after", -1.0),
+    ]
+    for effect, name, value in cases:
+        preset = json.loads(dumps_preset([effect]))
+        assert len(loads_preset(json.dumps(preset))) == 1
+        preset["effects"][0][name] = value
+        with pytest.raises(ValueError):
+            loads_preset(json.dumps(preset))
+
+    for params in [{"type": "PythonEffect", "block_size": 4},
+                   {"type": "Automation", "target": "Volume", "control_rate": 0.0},
+                   {"type": "PythonEffect", "block_size": 1024, "apply_after": None},
+                   {"type": "Automation", "target": "Volume", "control_rate": 50.0,
+                    "duration": None, "apply_after": None}]:
+        with pytest.raises(ValueError):
+            loads_preset(json.dumps({"version": 1, "effects": [params]}))
+
+
 @pytest.mark.asyncio
 async def test_set_duration(audio_handler):
     """Test that set_duration properly updates metadata."""
@@ -281,6 +874,11 @@ async def test_try_seek(audio_handler):
     handler.try_seek(4)
     await asyncio.sleep(0.2)
     assert handler.get_pos() >= 4
+    handler.try_seek(0)
+    await asyncio.sleep(0.2)
+    assert handler.get_pos() < 1
+    with pytest.raises(ValueError):
+        handler.try_seek(-1)
     handler.stop()
 
 
@@ -347,3 +945,270 @@ async def test_callback_called(audio_handler):
     handler.stop()
     await asyncio.sleep(0.2)
     mock_callback.assert_called_once()
+
+
+@pytest.mark.asyncio
+async def test_load_after_stop(audio_handler):
+    handler, mock_callback = audio_handler
+    handler.set_volume(0.4)
+    handler.play()
+    await asyncio.sleep(0.2)
+    handler.stop()
+    await asyncio.sleep(0.2)
+    handler.load_audio(r"tests/test_audio_files/test_md_flac.flac")
+    await asyncio.sleep(0.1)
+    assert handler.metadata.title == "rpaudio"
+    assert round(handler.get_volume(), 1) == 0.4
+    assert handler.callback is mock_callback
+    handler.stop()
+
+
+def test_load_while_loaded_raises(audio_handler):
+    handler, _ = audio_handler
+    with pytest.raises(RuntimeError):
+        handler.load_audio(r"tests/test_audio_files/test_md_flac.flac")
+
+
+@pytest.mark.asyncio
+async def test_unload(audio_handler):
+    handler, mock_callback = audio_handler
+    handler.play()
+    await asyncio.sleep(0.2)
+    handler.unload()
+    await asyncio.sleep(0.2)
+    mock_callback.assert_not_called()
+    assert handler.is_playing is False
+    with pytest.raises(RuntimeError):
+        handler.play()
+
+
+@pytest.mark.asyncio
+async def test_replay(audio_handler):
+    handler, _ = audio_handler
+    handler.play()
+    await asyncio.sleep(0.3)
+    handler.replay()
+    await asyncio.sleep(0.1)
+    assert handler.is_playing is True
+    assert handler.get_pos() < 0.3
+    handler.stop()
+
+
+@pytest.mark.asyncio
+async def test_snapshot_restore(audio_handler):
+    handler, _ = audio_handler
+    handler.apply_effects([FadeOut(duration=3.0, apply_after=2.0)])
+    handler.play()
+    await asyncio.sleep(0.5)
+    handler.pause()
+    snapshot = handler.snapshot()
+    assert snapshot["file_path"] == r"tests/test_audio_files/test_md_wav.wav"
+    assert snapshot["effects_chain"][0]["type"] == "FadeOut"
+    assert len(snapshot["active_effects"]) == 1
+
+    restored = rpaudio.AudioSink.from_snapshot(snapshot)
+    assert abs(restored.get_pos() - snapshot["position"]) < 0.1
+    assert restored.conflict_policy == ConflictPolicy.Reject
+    assert restored.is_playing is False
+
+    restored_json = rpaudio.AudioSink.from_snapshot(handler.snapshot_json())
+    assert abs(restored_json.get_pos() - snapshot["position"]) < 0.1
+    handler.stop()
+    restored.stop()
+    restored_json.stop()
+
+
+@pytest.mark.asyncio
+async def test_snapshot_round_trip_playing(audio_handler):
+    handler, _ = audio_handler
+    handler.apply_effects([FadeIn(duration=4.0, start_val=0.0, end_val=1.0)])
+    handler.play()
+    await asyncio.sleep(1.0)
+    snapshot = handler.snapshot()
+    assert snapshot["is_playing"] is True
+    progress = snapshot["active_effects"][0]["progress"]
+    assert 0.0 < progress < 1.0
+
+    restored = rpaudio.AudioSink.from_snapshot(snapshot)
+    assert restored.is_playing is True
+    restored_progress = restored.snapshot()["active_effects"][0]["progress"]
+    assert abs(restored_progress - progress) < 0.1
+    handler.stop()
+    restored.stop()
+
+
+@pytest.mark.asyncio
+async def test_snapshot_mid_fade(audio_handler):
+    handler, _ = audio_handler
+    handler.set_volume(0.5)
+    handler.set_replay_gain(rpaudio.ReplayGainMode.Album, preamp=2.0)
+    handler.apply_effects([FadeOut(duration=2.0, start_val=1.0, end_val=0.0, apply_after=0.0)])
+    handler.play()
+    handler.set_speed(1.25)
+    await asyncio.sleep(1.0)
+    assert handler.get_volume() < 0.5
+    snapshot = handler.snapshot()
+    assert snapshot["volume"] == 0.5
+    assert snapshot["speed"] == 1.25
+    assert snapshot["replay_gain"] == {"mode": "Album", "preamp": 2.0, "prevent_clipping": True}
+
+    restored = rpaudio.AudioSink.from_snapshot(snapshot)
+    assert restored.replay_gain == rpaudio.ReplayGainMode.Album
+    restored_snapshot = restored.snapshot()
+    assert restored_snapshot["volume"] == 0.5
+    assert restored_snapshot["speed"] == 1.25
+    handler.stop()
+    restored.stop()
+
+
+def test_snapshot_invalid(audio_handler):
+    with pytest.raises(ValueError):
+        rpaudio.AudioSink.from_snapshot({"version": 1})
+
+    handler, _ = audio_handler
+    handler.apply_effects([Delay(apply_after=5.0)])
+    snapshot = handler.snapshot()
+    snapshot["effects_chain"][0]["time"] = 1e12
+    with pytest.raises(ValueError):
+        rpaudio.AudioSink.from_snapshot(snapshot)
+
+    snapshot = handler.snapshot()
+    snapshot["active_effects"][0]["action"]["feedback"] = 1.0
+    with pytest.raises(ValueError):
+        rpaudio.AudioSink.from_snapshot(snapshot)
+
+    snapshot = handler.snapshot()
+    snapshot["volume"] = 5.0
+    with pytest.raises(ValueError):
+        rpaudio.AudioSink.from_snapshot(snapshot)
+
+    snapshot = handler.snapshot()
+    snapshot["effects_chain"].append({"type": "Automation", "target": "Volume", "control_rate": 50.0,
+                                      "duration": None, "apply_after": None})
+    with pytest.raises(ValueError, match="Python"):
+        rpaudio.AudioSink.from_snapshot(snapshot)
+
+    handler.apply_effects([PythonEffect(lambda block, sample_rate, channels: None)])
+    with pytest.raises(ValueError, match="Python"):
+        handler.snapshot()
+    with pytest.raises(ValueError, match="Python"):
+        handler.snapshot_json()
+    handler.stop()
+
+
+def test_concurrent_control(audio_handler):
+    handler, _ = audio_handler
+    handler.play()
+    errors = []
+
+    def worker(index):
+        try:
+            for i in range(50):
+                if (index + i) % 2 == 0:
+                    handler.pause()
+                else:
+                    handler.play()
+                handler.set_volume((i % 10) / 10)
+                handler.get_pos()
+                handler.get_volume()
+                handler.is_playing
+        except Exception as e:
+            errors.append(e)
+
+    threads = [threading.Thread(target=worker, args=(i,)) for i in range(8)]
+    for thread in threads:
+        thread.start()
+    for thread in threads:
+        thread.join(timeout=10)
+
+    assert not any(thread.is_alive() for thread in threads)
+    assert errors == []
+    handler.stop()
+
+
+def test_output_format(audio_handler):
+    handler, _ = audio_handler
+    output_format = handler.output_format()
+    assert output_format["sample_rate"] > 0
+    assert output_format["channels"] > 0
+    assert output_format["resampler"] == "Linear"
+
+
+def test_output_config():
+    config = rpaudio.OutputConfig(sample_rate=48000, buffer_size=1024, resampler=rpaudio.ResamplerQuality.Sinc)
+    handler = rpaudio.AudioSink(output_config=config)
+    handler.load_audio(r"tests/test_audio_files/test_md_wav.wav")
+    assert handler.output_config.sample_rate == 48000
+    output_format = handler.output_format()
+    assert output_format["sample_rate"] == 48000
+    assert output_format["resampler"] == "Sinc"
+    assert output_format["latency"] is not None
+
+
+def test_output_config_invalid():
+    with pytest.raises(ValueError):
+        rpaudio.OutputConfig(channels=0)
+    with pytest.raises(ValueError):
+        rpaudio.OutputConfig(latency=-0.1)
+
+
+@pytest.mark.asyncio
+async def test_set_output_config_while_playing(audio_handler):
+    handler, mock_callback = audio_handler
+    handler.play()
+    await asyncio.sleep(0.2)
+    handler.set_output_config(rpaudio.OutputConfig(resampler=rpaudio.ResamplerQuality.Cubic))
+    assert handler.is_playing is True
+    assert handler.get_pos() > 0.0
+    assert handler.output_format()["resampler"] == "Cubic"
+    mock_callback.assert_not_called()
+    handler.stop()
+
+
+@pytest.mark.asyncio
+async def test_set_output_config_keeps_effect_handles(audio_handler):
+    handler, _ = audio_handler
+    fade, = handler.apply_effects([FadeIn(start_val=0.0, end_val=1.0, duration=8.0)])
+    handler.play()
+    await wait_for_pos(handler, 0.5)
+
+    handler.set_output_config(rpaudio.OutputConfig(resampler=rpaudio.ResamplerQuality.Cubic))
+    await asyncio.sleep(0.05)
+    progress = fade.progress
+    await wait_for_pos(handler, handler.get_pos() + 0.5)
+    assert fade.progress > progress
+    assert fade.cancel()
+    assert fade.state == rpaudio.EffectState.Done
+    handler.stop()
+
+
+@pytest.mark.asyncio
+async def test_play_at(audio_handler):
+    handler, _ = audio_handler
+    event = handler.play_at(time.monotonic() + 0.3)
+    assert event.action == "play"
+    await asyncio.sleep(0.1)
+    assert event.pending is True
+    assert handler.is_playing is False
+    await asyncio.sleep(0.4)
+    assert event.pending is False
+    assert handler.is_playing is True
+    handler.stop()
+
+
+@pytest.mark.asyncio
+async def test_stop_at_and_cancel(audio_handler):
+    handler, mock_callback = audio_handler
+    handler.play()
+    cancelled = handler.stop_at(time.monotonic() + 0.3)
+    assert cancelled.cancel() is True
+    assert cancelled.pending is False
+
+    event = handler.stop_at(time.monotonic() + 0.3)
+    await asyncio.sleep(0.1)
+    assert handler.is_playing is True
+    await asyncio.sleep(0.4)
+    assert event.pending is False
+    assert event.cancel() is False
+    assert handler.is_playing is False
+    mock_callback.assert_called_once()
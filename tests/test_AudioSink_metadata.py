@@ -5,7 +5,9 @@ import rpaudio
 
 
 test_dict = {'duration': None, 'date': None, 'total_tracks': None, 'channels': None, 'album_title': None, 'total_discs': None, 'genre': None, 'disc_number': None,
-             'artist': None, 'year': None, 'title': None, 'album_artist': None, 'track_number': None, 'composer': None, 'sample_rate': None, 'comment': None}
+             'artist': None, 'year': None, 'title': None, 'album_artist': None, 'track_number': None, 'composer': None, 'sample_rate': None, 'comment': None,
+             'integrated_loudness': None, 'loudness_range': None, 'true_peak': None,
+             'replaygain_track_gain': None, 'replaygain_track_peak': None, 'replaygain_album_gain': None, 'replaygain_album_peak': None}
 
 
 @pytest.fixture
@@ -77,3 +79,36 @@ def test_metadata_flac(audio_handler_metadata_flac):
     assert metadata.track_number == '1'
     assert metadata.year == '2024'
     assert metadata is not None
+
+
+def test_analyze_loudness_wav():
+    handler = rpaudio.AudioSink()
+    handler.load_audio(r"tests/test_audio_files/test_md_wav.wav")
+    assert handler.metadata.integrated_loudness is None
+    stats = handler.analyze_loudness()
+    assert stats["integrated_loudness"] < 0.0
+    assert stats["true_peak"] <= 0.5
+    assert handler.metadata.integrated_loudness == stats["integrated_loudness"]
+    assert handler.metadata_dict["loudness_range"] == stats["loudness_range"]
+
+
+def test_normalization_wav():
+    handler = rpaudio.AudioSink()
+    handler.load_audio(r"tests/test_audio_files/test_md_wav.wav")
+    assert handler.normalization is None
+    handler.normalization = -23.0
+    assert handler.normalization == -23.0
+    assert handler.metadata.integrated_loudness is not None
+    handler.normalization = None
+    assert handler.normalization is None
+
+
+def test_replay_gain_untagged_wav():
+    handler = rpaudio.AudioSink()
+    handler.load_audio(r"tests/test_audio_files/test_md_wav.wav")
+    assert handler.metadata.replaygain_track_gain is None
+    assert handler.metadata.replaygain_album_gain is None
+    handler.set_replay_gain(rpaudio.ReplayGainMode.Album, preamp=2.0)
+    assert handler.replay_gain == rpaudio.ReplayGainMode.Album
+    handler.set_replay_gain(None)
+    assert handler.replay_gain is None
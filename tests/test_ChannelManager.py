@@ -1,7 +1,7 @@
 import asyncio
 from unittest.mock import MagicMock
 import pytest
-from rpaudio import ChannelManager, AudioChannel, AudioSink
+from rpaudio import ChannelManager, AudioChannel, AudioSink, OutputConfig
 
 
 @pytest.fixture
@@ -114,3 +114,17 @@ def test_drop_channel_not_found(setup_channel_manager):
 
     with pytest.raises(RuntimeError, match="Channel not found"):
         manager.drop_channel("NonExistentChannel")
+
+
+def test_output_config():
+    config = OutputConfig(buffer_size=1024)
+    manager = ChannelManager(output_config=config)
+    channel = AudioChannel()
+    audio = AudioSink()
+    audio.load_audio(r"tests/test_audio_files/test_md_mp3.mp3")
+    channel.push(audio)
+    manager.add_channel("Channel1", channel)
+
+    assert channel.output_config.buffer_size == 1024
+    assert audio.output_config.buffer_size == 1024
+    assert manager.output_format()["buffer_size"] is not None
@@ -3,7 +3,7 @@ from unittest.mock import MagicMock
 import pytest
 import rpaudio as rpaudio
 from unittest.mock import Mock
-from rpaudio.effects import FadeIn, FadeOut, ChangeSpeed
+from rpaudio.effects import FadeIn, FadeOut, ChangeSpeed, ParametricEQ, EQBand, FilterType, Reverb, Delay, save_preset
 
 
 @pytest.fixture
@@ -79,6 +79,47 @@ def test_audio_channel_effects_chain(audio_callback):
         assert actual_effect.duration == effect.duration
 
 
+
+def test_audio_channel_parametric_eq():
+    channel = rpaudio.AudioChannel()
+    eq = ParametricEQ([EQBand(FilterType.LowShelf, frequency=120.0, gain=3.0)])
+    channel.set_effects_chain([eq])
+
+    eq.set_band(0, gain=-2.0)
+    assert channel.effects[0].bands[0].gain == -2.0
+    assert channel.effects[0].bands[0].band_type == FilterType.LowShelf
+
+
+def test_audio_channel_load_effects_preset(tmp_path):
+    path = tmp_path / "preset.json"
+    save_preset([FadeIn(duration=1.0), Delay(time=0.2, feedback=0.5)], str(path), name="Echo")
+
+    channel = rpaudio.AudioChannel()
+    channel.load_effects_preset(str(path))
+    fade_in, delay = channel.effects
+    assert isinstance(fade_in, FadeIn)
+    assert fade_in.duration == 1.0
+    assert delay.feedback == 0.5
+
+    with pytest.raises(RuntimeError):
+        channel.load_effects_preset(str(tmp_path / "missing.json"))
+
+
+@pytest.mark.asyncio
+async def test_audio_channel_reverb_and_delay():
+    channel = rpaudio.AudioChannel()
+    channel.set_effects_chain([Reverb(room_size=0.8, mix=0.4), Delay(time=0.2, apply_after=0.1)])
+    sink = rpaudio.AudioSink()
+    sink.load_audio(r"tests/test_audio_files/test_md_wav.wav")
+    channel.push(sink)
+    channel.auto_consume = True
+    await asyncio.sleep(0.3)
+    assert channel.is_playing
+    effects = channel.current_audio.playback_data()["effects"]
+    assert [effect["type"] for effect in effects] == ["Reverb", "Delay"]
+    channel.auto_consume = False
+    channel.current_audio.stop()
+
 @pytest.mark.asyncio
 async def test_audio_channel_auto_consume(audio_callback):
     """Test if the AudioChannel can auto-consume and switch between audio sinks."""
@@ -1,4 +1,5 @@
 import asyncio
+import time
 from unittest.mock import MagicMock
 import pytest
 import rpaudio
@@ -143,3 +144,30 @@ async def test_callbacks_called():
 
     mock_callback_1.assert_called_once()
     mock_callback_2.assert_not_called()
+
+
+def test_channel_replay_gain(audio_channel):
+    """Test that the channel ReplayGain mode is passed on to queued sinks."""
+    channel, _, _ = audio_channel
+    channel.set_replay_gain(rpaudio.ReplayGainMode.Track, preamp=-3.0)
+    assert channel.replay_gain == rpaudio.ReplayGainMode.Track
+    for sink in channel.queue_contents:
+        assert sink.replay_gain == rpaudio.ReplayGainMode.Track
+
+
+@pytest.mark.asyncio
+async def test_start_at():
+    """Test that start_at starts the next queued sink at the given time."""
+    audio = rpaudio.AudioSink()
+    audio.load_audio(r"tests/test_audio_files/test_md_wav.wav")
+    channel = rpaudio.AudioChannel()
+    channel.push(audio)
+    event = channel.start_at(time.monotonic() + 0.3)
+    assert channel.current_audio is not None
+    await asyncio.sleep(0.1)
+    assert channel.is_playing is False
+    await asyncio.sleep(0.4)
+    assert event.pending is False
+    assert channel.auto_consume is True
+    assert channel.is_playing is True
+    channel.current_audio.stop()
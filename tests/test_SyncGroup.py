@@ -0,0 +1,145 @@
+import asyncio
+import time
+import pytest
+import rpaudio
+import rpaudio.exceptions
+from rpaudio.effects import ChangeSpeed
+
+
+@pytest.fixture
+def stems():
+    sinks = []
+    for _ in range(2):
+        sink = rpaudio.AudioSink()
+        sink.load_audio(r"tests/test_audio_files/test_md_wav.wav")
+        sinks.append(sink)
+    return sinks
+
+
+@pytest.mark.asyncio
+async def test_group_play_pause(stems):
+    group = rpaudio.SyncGroup(stems)
+    group.play()
+    await asyncio.sleep(0.3)
+    assert group.is_playing
+    assert all(sink.is_playing for sink in stems)
+
+    group.pause()
+    await asyncio.sleep(0.1)
+    assert not group.is_playing
+    positions = [sink.get_pos() for sink in stems]
+    assert positions[0] > 0.0
+    assert positions[0] == positions[1]
+    group.stop()
+
+
+@pytest.mark.asyncio
+async def test_group_seek_and_speed(stems):
+    group = rpaudio.SyncGroup(stems)
+    group.try_seek(2.0)
+    assert [sink.get_pos() for sink in stems] == [2.0, 2.0]
+
+    group.set_speed(1.5)
+    assert all(sink.get_speed() == 1.5 for sink in stems)
+
+    group.play()
+    await asyncio.sleep(0.2)
+    group.pause()
+    await asyncio.sleep(0.1)
+    positions = [sink.get_pos() for sink in stems]
+    assert positions[0] > 2.0
+    assert positions[0] == positions[1]
+    group.stop()
+
+
+@pytest.mark.asyncio
+async def test_group_channel_member(stems):
+    channel = rpaudio.AudioChannel()
+    channel.push(stems[1])
+    group = rpaudio.SyncGroup([stems[0], channel])
+
+    group.play()
+    await asyncio.sleep(0.2)
+    assert channel.auto_consume
+    assert channel.current_audio is not None
+    assert channel.is_playing
+
+    group.pause()
+    await asyncio.sleep(0.1)
+    assert stems[0].get_pos() == channel.current_audio.get_pos()
+    group.stop()
+
+
+@pytest.mark.asyncio
+async def test_group_play_at(stems):
+    group = rpaudio.SyncGroup(stems)
+    event = group.play_at(time.monotonic() + 0.2)
+    await asyncio.sleep(0.4)
+    assert event.pending is False
+    assert group.is_playing
+
+    group.stop_at(time.monotonic() + 0.1)
+    await asyncio.sleep(0.3)
+    assert not group.is_playing
+    assert stems[0].get_pos() == stems[1].get_pos()
+
+
+def test_group_members(stems):
+    group = rpaudio.SyncGroup()
+    group.add(stems[0])
+    group.add(stems[0])
+    group.add(stems[1])
+    assert len(group) == 2
+
+    group.remove(stems[0])
+    assert len(group) == 1
+    with pytest.raises(ValueError):
+        group.remove(stems[0])
+
+
+def test_group_mixed_output_configs(stems):
+    sink = rpaudio.AudioSink(output_config=rpaudio.OutputConfig(buffer_size=1024))
+    sink.load_audio(r"tests/test_audio_files/test_md_wav.wav")
+    group = rpaudio.SyncGroup([stems[0], sink])
+    with pytest.raises(ValueError):
+        group.play()
+
+
+def test_group_unloaded_member_keeps_queue(stems):
+    channel = rpaudio.AudioChannel()
+    channel.push(stems[1])
+    group = rpaudio.SyncGroup([rpaudio.AudioSink(), channel])
+    with pytest.raises(RuntimeError):
+        group.play()
+    assert channel.current_audio is None
+    assert len(channel.queue_contents) == 1
+
+
+def test_group_invalid_values(stems):
+    group = rpaudio.SyncGroup(stems)
+    with pytest.raises(ValueError):
+        group.set_speed(0.0)
+    with pytest.raises(ValueError):
+        group.try_seek(-1.0)
+
+
+@pytest.mark.asyncio
+async def test_group_speed_conflict(stems):
+    ramp, = stems[1].apply_effects([ChangeSpeed(end_val=1.5, duration=8.0)])
+    group = rpaudio.SyncGroup(stems)
+    group.play()
+    for _ in range(100):
+        if ramp.state == rpaudio.EffectState.Active:
+            break
+        await asyncio.sleep(0.02)
+
+    speeds = [stem.get_speed() for stem in stems]
+    with pytest.raises(rpaudio.exceptions.EffectConflictException):
+        group.set_speed(0.9)
+    for index, stem in enumerate(stems):
+        if index == 1:
+            # The ramp keeps driving its stem towards 1.5.
+            assert stem.get_speed() >= speeds[index]
+        else:
+            assert stem.get_speed() == speeds[index]
+    group.stop()